@@ -1,3 +1,18 @@
+use std::fs;
+
+/// Name of the environment variable the app identifier is passed to the crate in
+const APP_IDENTIFIER_ENV: &str = "ATTACHE_APP_IDENTIFIER";
+
 fn main() {
+    // The CLI finds the app's data directory by the identifier in tauri.conf.json
+    println!("cargo:rerun-if-changed=tauri.conf.json");
+    let config = fs::read_to_string("tauri.conf.json").expect("Failed to read tauri.conf.json");
+    let config: serde_json::Value =
+        serde_json::from_str(&config).expect("Failed to parse tauri.conf.json");
+    let identifier = config["identifier"]
+        .as_str()
+        .expect("tauri.conf.json has no identifier");
+    println!("cargo:rustc-env={}={}", APP_IDENTIFIER_ENV, identifier);
+
     tauri_build::build()
 }
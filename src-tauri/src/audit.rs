@@ -0,0 +1,503 @@
+use crate::logging::log_error;
+use crate::models::{BriefCase, BriefCaseGroup, Profile, Task};
+use crate::settings::Settings;
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+/// Number of entries the audit log keeps, older ones are dropped
+pub const MAX_AUDIT_ENTRIES: usize = 5000;
+
+/// Kind of data an audit entry describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditEntity {
+    /// A browser profile
+    Profile,
+    /// A social media user account
+    BriefCase,
+    /// A named set of social media user accounts
+    Group,
+    /// A task of the current session
+    Task,
+    /// The application settings
+    Settings,
+    /// An imported backup archive
+    Backup,
+}
+
+/// What happened to the audited data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditAction {
+    /// The record was added
+    Create,
+    /// The record was changed
+    Update,
+    /// The record was removed
+    Delete,
+}
+
+/// A single recorded data mutation
+///
+/// Entries are only ever appended; reverting an entry appends new entries
+/// pointing back at it through `reverts`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Unique identifier for this entry
+    pub id: Uuid,
+    /// When the mutation happened
+    pub timestamp: DateTime<Utc>,
+    /// OS user that made the mutation
+    pub actor: String,
+    /// The command that made the mutation
+    pub command: String,
+    /// Kind of data that was mutated
+    pub entity: AuditEntity,
+    /// ID of the mutated record, None for settings
+    pub entity_id: Option<Uuid>,
+    /// What happened to the record
+    pub action: AuditAction,
+    /// The record before the mutation, only its changed fields for updates;
+    /// None if it was created
+    pub before: Option<Value>,
+    /// The record after the mutation, only its changed fields for updates;
+    /// None if it was deleted
+    pub after: Option<Value>,
+    /// The entry this mutation reverted, if any
+    pub reverts: Option<Uuid>,
+}
+
+/// The change that undoes an audit entry
+///
+/// Saved like any other change of its kind, with `AuditSource::revert` so the
+/// new entries point back at the reverted one.
+#[derive(Debug, Clone)]
+pub enum Revert {
+    /// All profiles with the entry undone
+    Profiles(Vec<Profile>),
+    /// All BriefCases with the entry undone
+    BriefCases(Vec<BriefCase>),
+    /// All groups with the entry undone
+    Groups(Vec<BriefCaseGroup>),
+    /// The settings with the changed fields restored
+    Settings(Settings),
+    /// The comment a session task showed before the entry
+    CommentIndex {
+        /// Index of the task in the session
+        task_index: usize,
+        /// The comment index to restore
+        comment_index: usize,
+    },
+}
+
+/// What caused a mutation, recorded with every entry it produces
+#[derive(Debug, Clone, Copy)]
+pub struct AuditSource {
+    /// Name of the command that made the mutation
+    pub command: &'static str,
+    /// The entry being reverted, if the mutation is a revert
+    pub reverts: Option<Uuid>,
+}
+
+impl AuditSource {
+    /// A mutation made directly by a command
+    pub fn command(command: &'static str) -> Self {
+        Self {
+            command,
+            reverts: None,
+        }
+    }
+
+    /// A mutation reverting an earlier entry
+    pub fn revert(entry_id: Uuid) -> Self {
+        Self {
+            command: "revert_audit_entry",
+            reverts: Some(entry_id),
+        }
+    }
+}
+
+impl AuditEntry {
+    /// Creates a new audit entry stamped with the current time and OS user
+    ///
+    /// # Arguments
+    /// * `source` - What caused the mutation
+    /// * `entity` - Kind of data that was mutated
+    /// * `entity_id` - ID of the mutated record
+    /// * `before` - The record before the mutation
+    /// * `after` - The record after the mutation
+    ///
+    /// # Returns
+    /// A new AuditEntry with the action derived from `before` and `after`
+    pub fn new(
+        source: AuditSource,
+        entity: AuditEntity,
+        entity_id: Option<Uuid>,
+        before: Option<Value>,
+        after: Option<Value>,
+    ) -> Self {
+        let action = match (&before, &after) {
+            (None, _) => AuditAction::Create,
+            (Some(_), None) => AuditAction::Delete,
+            (Some(_), Some(_)) => AuditAction::Update,
+        };
+
+        Self {
+            id: Uuid::new_v4(),
+            timestamp: Utc::now(),
+            actor: current_actor(),
+            command: source.command.to_string(),
+            entity,
+            entity_id,
+            action,
+            before,
+            after,
+            reverts: source.reverts,
+        }
+    }
+}
+
+/// Returns the name of the OS user running the app
+fn current_actor() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Records mutations in the audit log, dropping the oldest entries beyond the cap
+///
+/// # Arguments
+/// * `entries` - The entries to record, nothing is written if empty
+pub async fn record(state: &AppState, entries: Vec<AuditEntry>) -> Result<(), String> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    state
+        .storage()
+        .append_audit(&entries)
+        .await
+        .map_err(log_error("Failed to write audit log"))?;
+    state
+        .storage()
+        .trim_audit(MAX_AUDIT_ENTRIES)
+        .await
+        .map_err(log_error("Failed to trim audit log"))
+}
+
+/// Reduces two versions of a value to the parts that differ
+///
+/// Objects are compared field by field, recursively, so only changed fields
+/// are kept; any other value is kept whole.
+///
+/// # Returns
+/// The differing parts of `before` and `after`, None if they are equal
+pub fn changed_fields(before: &Value, after: &Value) -> Option<(Value, Value)> {
+    if before == after {
+        return None;
+    }
+
+    let (Value::Object(old), Value::Object(new)) = (before, after) else {
+        return Some((before.clone(), after.clone()));
+    };
+
+    let mut old_fields = Map::new();
+    let mut new_fields = Map::new();
+    for key in old
+        .keys()
+        .chain(new.keys().filter(|key| !old.contains_key(*key)))
+    {
+        let old_value = old.get(key).unwrap_or(&Value::Null);
+        let new_value = new.get(key).unwrap_or(&Value::Null);
+        if let Some((old_value, new_value)) = changed_fields(old_value, new_value) {
+            old_fields.insert(key.clone(), old_value);
+            new_fields.insert(key.clone(), new_value);
+        }
+    }
+
+    Some((Value::Object(old_fields), Value::Object(new_fields)))
+}
+
+/// Writes recorded fields over a value, recursing into objects
+fn apply_fields(target: &mut Value, fields: &Value) {
+    match (target, fields) {
+        (Value::Object(target), Value::Object(fields)) => {
+            for (key, value) in fields {
+                match target.get_mut(key) {
+                    Some(existing) => apply_fields(existing, value),
+                    None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, fields) => *target = fields.clone(),
+    }
+}
+
+/// Creates an entry for a changed record holding only its changed fields
+///
+/// # Arguments
+/// * `source` - What caused the mutation
+/// * `entity` - Kind of data that was mutated
+/// * `entity_id` - ID of the mutated record
+/// * `before` - The record before the mutation
+/// * `after` - The record after the mutation
+///
+/// # Returns
+/// The entry, None if the record did not change
+pub fn update<T: Serialize>(
+    source: AuditSource,
+    entity: AuditEntity,
+    entity_id: Option<Uuid>,
+    before: &T,
+    after: &T,
+) -> Option<AuditEntry> {
+    let before = serde_json::to_value(before).ok()?;
+    let after = serde_json::to_value(after).ok()?;
+    changed_fields(&before, &after).map(|(before, after)| {
+        AuditEntry::new(source, entity, entity_id, Some(before), Some(after))
+    })
+}
+
+/// Undoes an update entry on the current version of its record
+///
+/// Only the fields the entry changed are restored, later changes to other
+/// fields are kept.
+///
+/// # Errors
+/// Returns an error if the entry has no previous value or the result cannot be
+/// deserialized
+pub fn revert_fields<T: Serialize + DeserializeOwned>(
+    entry: &AuditEntry,
+    current: &T,
+) -> Result<T, String> {
+    let before = entry
+        .before
+        .as_ref()
+        .ok_or("Audit entry has no previous value")?;
+    let mut value =
+        serde_json::to_value(current).map_err(|e| format!("Failed to read record: {}", e))?;
+    apply_fields(&mut value, before);
+    serde_json::from_value(value).map_err(|e| format!("Failed to read audit entry: {}", e))
+}
+
+/// Compares two versions of a record list and creates an entry for every change
+///
+/// Created and deleted records are recorded whole, updated ones by their
+/// changed fields.
+///
+/// # Arguments
+/// * `source` - What caused the mutation
+/// * `entity` - Kind of records in the lists
+/// * `before` - The records before the mutation
+/// * `after` - The records after the mutation
+/// * `id_of` - Extracts the ID records are matched by
+///
+/// # Returns
+/// One entry per created, updated or deleted record
+pub fn diff<T: Serialize + PartialEq>(
+    source: AuditSource,
+    entity: AuditEntity,
+    before: &[T],
+    after: &[T],
+    id_of: fn(&T) -> Uuid,
+) -> Vec<AuditEntry> {
+    diff_values(source, entity, before, after, id_of, |item| {
+        serde_json::to_value(item).ok()
+    })
+}
+
+/// Compares two versions of the session's tasks like `diff`
+///
+/// Tasks are recorded without their accounts, which are derived from the
+/// briefcases and recorded with them.
+pub fn diff_tasks(source: AuditSource, before: &[Task], after: &[Task]) -> Vec<AuditEntry> {
+    diff_values(
+        source,
+        AuditEntity::Task,
+        before,
+        after,
+        |t| t.task_id,
+        |task| {
+            let mut value = serde_json::to_value(task).ok()?;
+            if let Some(fields) = value.as_object_mut() {
+                fields.remove("related_brief_cases");
+            }
+            Some(value)
+        },
+    )
+}
+
+/// Compares two versions of a record list by the values recorded for them
+fn diff_values<T: PartialEq>(
+    source: AuditSource,
+    entity: AuditEntity,
+    before: &[T],
+    after: &[T],
+    id_of: fn(&T) -> Uuid,
+    value_of: fn(&T) -> Option<Value>,
+) -> Vec<AuditEntry> {
+    let mut entries = Vec::new();
+
+    for old in before {
+        match after.iter().find(|new| id_of(new) == id_of(old)) {
+            Some(new) if new != old => {
+                let changed = value_of(old)
+                    .zip(value_of(new))
+                    .and_then(|(old, new)| changed_fields(&old, &new));
+                if let Some((old_fields, new_fields)) = changed {
+                    entries.push(AuditEntry::new(
+                        source,
+                        entity,
+                        Some(id_of(old)),
+                        Some(old_fields),
+                        Some(new_fields),
+                    ));
+                }
+            }
+            Some(_) => {}
+            None => entries.push(AuditEntry::new(
+                source,
+                entity,
+                Some(id_of(old)),
+                value_of(old),
+                None,
+            )),
+        }
+    }
+
+    for new in after {
+        if !before.iter().any(|old| id_of(old) == id_of(new)) {
+            entries.push(AuditEntry::new(
+                source,
+                entity,
+                Some(id_of(new)),
+                None,
+                value_of(new),
+            ));
+        }
+    }
+
+    entries
+}
+
+/// Applies the reverse of an entry to a record list
+///
+/// Removes the record if the entry created it, re-adds it if the entry deleted
+/// it and otherwise restores the fields the entry changed.
+///
+/// # Errors
+/// Returns an error if the recorded value cannot be deserialized or an updated
+/// record no longer exists
+pub fn revert_in<T: Serialize + DeserializeOwned>(
+    entry: &AuditEntry,
+    items: &mut Vec<T>,
+    id_of: fn(&T) -> Uuid,
+) -> Result<(), String> {
+    let entity_id = entry.entity_id.ok_or("Audit entry has no record ID")?;
+    let existing = items.iter_mut().find(|item| id_of(item) == entity_id);
+
+    match (entry.action, existing) {
+        (AuditAction::Create, _) => items.retain(|item| id_of(item) != entity_id),
+        (AuditAction::Update, Some(item)) => *item = revert_fields(entry, item)?,
+        (AuditAction::Update, None) => {
+            return Err("The record no longer exists, revert its deletion first".to_string())
+        }
+        (AuditAction::Delete, existing) => {
+            let before = entry
+                .before
+                .clone()
+                .ok_or("Audit entry has no previous value")?;
+            let restored: T = serde_json::from_value(before)
+                .map_err(|e| format!("Failed to read audit entry: {}", e))?;
+            match existing {
+                Some(item) => *item = restored,
+                None => items.push(restored),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Works out the change that undoes a recorded mutation
+///
+/// Restores the record to its state before the entry, or removes it if the entry
+/// created it. Only comment changes can be reverted for session tasks, and
+/// backup imports only through the changes they made. Nothing is changed
+/// until the result is saved.
+///
+/// # Arguments
+/// * `entry_id` - ID of the audit entry to revert
+///
+/// # Errors
+/// Returns an error if the entry does not exist or cannot be reverted
+pub async fn revert(state: &AppState, entry_id: Uuid) -> Result<Revert, String> {
+    let entry = state
+        .storage()
+        .read_audit()
+        .await
+        .map_err(log_error("Failed to read audit log"))?
+        .into_iter()
+        .find(|entry| entry.id == entry_id)
+        .ok_or("Audit entry not found")?;
+
+    log::info!(
+        "Reverting audit entry {} ({:?} {:?})",
+        entry.id,
+        entry.action,
+        entry.entity
+    );
+
+    match entry.entity {
+        AuditEntity::Profile => {
+            let mut profiles = state.get_profiles().await;
+            revert_in(&entry, &mut profiles, |p| p.profile_id)?;
+            Ok(Revert::Profiles(profiles))
+        }
+        AuditEntity::BriefCase => {
+            let mut briefcases = state.get_brief_cases().await;
+            revert_in(&entry, &mut briefcases, |b| b.id)?;
+            Ok(Revert::BriefCases(briefcases))
+        }
+        AuditEntity::Group => {
+            let mut groups = state.get_groups().await;
+            revert_in(&entry, &mut groups, |g| g.id)?;
+            Ok(Revert::Groups(groups))
+        }
+        AuditEntity::Settings => Ok(Revert::Settings(revert_fields(
+            &entry,
+            &state.get_settings().await,
+        )?)),
+        AuditEntity::Backup => Err(
+            "Backup imports cannot be reverted, revert the changes they made instead".to_string(),
+        ),
+        AuditEntity::Task => {
+            let comment_index = match entry.action {
+                AuditAction::Update => entry
+                    .before
+                    .as_ref()
+                    .and_then(|before| before.get("comment_index"))
+                    .and_then(|index| index.as_u64()),
+                _ => None,
+            }
+            .ok_or("Only comment changes of session tasks can be reverted")?;
+            let task_id = entry.entity_id.ok_or("Audit entry has no record ID")?;
+
+            let task_index = state
+                .get_tasks()
+                .await
+                .iter()
+                .position(|t| t.task_id == task_id)
+                .ok_or("Task is no longer part of the current session")?;
+
+            Ok(Revert::CommentIndex {
+                task_index,
+                comment_index: comment_index as usize,
+            })
+        }
+    }
+}
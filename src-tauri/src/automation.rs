@@ -0,0 +1,656 @@
+use crate::audit::{self, AuditSource};
+use crate::execution::{ExecutionPlan, ExecutionStep, PlanPreview};
+use crate::logging::log_error;
+use crate::models::{BriefCase, BriefCaseGroup, Session, SessionStep, Task};
+use crate::platforms::PlatformRegistry;
+use crate::state::AppState;
+use chrono::Utc;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// Result structure for task execution operations
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutionResult {
+    /// Whether the task was completed successfully
+    pub completed: bool,
+    /// ID of the profile to use for this execution
+    pub profile_id: Uuid,
+    /// URL to load in the browser
+    pub link: String,
+    /// Whether to switch to a new profile
+    pub should_change_profile: bool,
+    /// Index of the current task
+    pub task_index: usize,
+    /// Current comment to post
+    pub comment: String,
+    /// A problem presenting the step that did not stop the plan, e.g. the
+    /// comment could not be copied to the clipboard
+    pub warning: Option<String>,
+}
+
+impl ExecutionResult {
+    /// The result reported once every step of the plan has been passed
+    fn completed() -> Self {
+        Self {
+            completed: true,
+            profile_id: Uuid::nil(),
+            link: String::new(),
+            should_change_profile: false,
+            task_index: 0,
+            comment: String::new(),
+            warning: None,
+        }
+    }
+}
+
+/// Time spent on the running session so far
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionTime {
+    /// Whether the session is paused
+    pub paused: bool,
+    /// Milliseconds spent working on the session
+    pub active_ms: i64,
+    /// Milliseconds the session spent paused
+    pub paused_ms: i64,
+}
+
+impl SessionTime {
+    /// Measures a session up to now
+    fn of(session: &Session) -> Self {
+        let now = Utc::now();
+        Self {
+            paused: session.is_paused(),
+            active_ms: session.active_duration(now).num_milliseconds(),
+            paused_ms: session.paused_duration(now).num_milliseconds(),
+        }
+    }
+}
+
+/// Parses tasks sent by the frontend and assigns the available briefcases to them
+///
+/// Tasks targeting a group only get the group's members.
+///
+/// # Errors
+/// Returns an error if the tasks cannot be parsed, are for an unknown platform
+/// or target an unknown group
+pub fn build_tasks(
+    tasks_json: &str,
+    platforms: &PlatformRegistry,
+    briefcases: &[BriefCase],
+    groups: &[BriefCaseGroup],
+) -> Result<Vec<Task>, String> {
+    let tasks: Vec<Task> =
+        serde_json::from_str(tasks_json).map_err(log_error("Failed to parse tasks"))?;
+
+    tasks
+        .into_iter()
+        .map(|task| build_task(task, platforms, briefcases, groups))
+        .collect()
+}
+
+/// Assigns the available briefcases to a task sent by the frontend
+///
+/// The task's link is canonicalized by the rules of its platform.
+///
+/// # Errors
+/// Returns an error if the task is for an unknown platform or targets an unknown group
+pub fn build_task(
+    task: Task,
+    platforms: &PlatformRegistry,
+    briefcases: &[BriefCase],
+    groups: &[BriefCaseGroup],
+) -> Result<Task, String> {
+    let platform = platforms.get(&task.social_media).ok_or_else(|| {
+        format!(
+            "Task {} is for unknown platform {}",
+            task.link, task.social_media
+        )
+    })?;
+
+    let group = task
+        .target_group_id
+        .map(|id| {
+            groups
+                .iter()
+                .find(|g| g.id == id)
+                .ok_or_else(|| format!("Task {} targets unknown group {}", task.link, id))
+        })
+        .transpose()?;
+
+    Ok(Task::new(
+        platform.canonicalize(&task.link),
+        task.comments,
+        task.social_media,
+        briefcases,
+        group,
+    ))
+}
+
+/// Shows what automation would do with the given tasks without starting it
+///
+/// # Arguments
+/// * `tasks_json` - The tasks as sent by the frontend
+pub async fn preview(state: &AppState, tasks_json: &str) -> Result<PlanPreview, String> {
+    let briefcases = state.get_brief_cases().await;
+    let profiles = state.get_profiles().await;
+    let groups = state.get_groups().await;
+    let tasks = build_tasks(
+        tasks_json,
+        &state.get_platforms().await,
+        &briefcases,
+        &groups,
+    )?;
+
+    Ok(ExecutionPlan::new(&tasks, &profiles).preview(&tasks, &briefcases, &profiles, &groups))
+}
+
+/// Plans the given tasks, records a new session and moves to its first step
+///
+/// A session that is still running or paused is ended first, so its history
+/// record gets an end time.
+///
+/// # Arguments
+/// * `tasks_json` - The tasks as sent by the frontend
+/// * `source` - The command starting the session, recorded in the audit log
+///
+/// # Returns
+/// The first step of the session
+///
+/// # Errors
+/// Returns an error if no available briefcase can post on any of the tasks
+pub async fn start(
+    state: &AppState,
+    tasks_json: &str,
+    source: AuditSource,
+) -> Result<ExecutionResult, String> {
+    if state.get_login_check().await.is_some() {
+        return Err("Finish the login check before starting automation".to_string());
+    }
+
+    let briefcases = state.get_brief_cases().await;
+
+    if briefcases.is_empty() {
+        return Err(
+            "No briefcases found. Please add briefcases before starting automation.".to_string(),
+        );
+    }
+
+    let groups = state.get_groups().await;
+    let tasks_with_briefcases = build_tasks(
+        tasks_json,
+        &state.get_platforms().await,
+        &briefcases,
+        &groups,
+    )?;
+
+    // Check if any tasks have matching briefcases
+    if tasks_with_briefcases
+        .iter()
+        .all(|t| t.related_brief_cases.is_empty())
+    {
+        return Err("No active briefcases match the task platforms. Please add or enable briefcases for the social media platforms you want to automate.".to_string());
+    }
+
+    if state.get_session().await.is_some() {
+        log::info!("Ending the running session before starting a new one");
+        end(state).await?;
+    }
+
+    // Set tasks and create execution plan
+    let task_count = tasks_with_briefcases.len();
+    let previous_tasks = state.get_tasks().await;
+    state.set_tasks(tasks_with_briefcases.clone()).await;
+    audit::record(
+        state,
+        audit::diff_tasks(source, &previous_tasks, &tasks_with_briefcases),
+    )
+    .await?;
+    state.set_running(true).await;
+
+    let total_steps = state.get_progress().await.1;
+    let session = Session::new(tasks_with_briefcases, total_steps);
+    state
+        .storage()
+        .write_session(&session)
+        .await
+        .map_err(log_error("Failed to record session"))?;
+    state.set_session(Some(session)).await;
+    log::info!(
+        "Starting automation with {} tasks and {} steps",
+        task_count,
+        total_steps
+    );
+
+    next_step(state).await
+}
+
+/// Moves the plan to its next step
+///
+/// # Returns
+/// The new current step, or a completed result if the plan has no steps left
+pub async fn next_step(state: &AppState) -> Result<ExecutionResult, String> {
+    ensure_navigable(state).await?;
+    if state.is_complete().await {
+        return Ok(ExecutionResult::completed());
+    }
+
+    // Check if we need to change profile
+    let should_change = state.should_change_profile().await;
+
+    // Get the next execution step (this advances the plan)
+    let step = state
+        .next_execution_step()
+        .await
+        .ok_or("No more execution steps")?;
+
+    let comment = state.current_comment().await.unwrap_or_default();
+
+    Ok(ExecutionResult {
+        completed: false,
+        profile_id: step.profile_id,
+        link: step.link,
+        should_change_profile: should_change,
+        task_index: step.task_index,
+        comment,
+        warning: None,
+    })
+}
+
+/// Marks the current step as done
+///
+/// Records the step in the session history with the comment shown for it and
+/// marks it as completed. The task rotates to its next comment only the first
+/// time a step is completed, so redoing a step does not skip a comment.
+/// Does not move the plan.
+pub async fn complete_current(state: &AppState) -> Result<(), String> {
+    ensure_navigable(state).await?;
+    let Some(step) = state.current_step().await else {
+        return Ok(());
+    };
+    let Some(task) = state.get_task(step.task_index).await else {
+        return Ok(());
+    };
+    let Some(briefcase) = task.related_brief_cases.get(step.briefcase_index) else {
+        return Ok(());
+    };
+
+    let comment_index = state.current_comment_index().await;
+    let comment = comment_index.and_then(|index| task.comments.get(index).cloned());
+    record_step(state, &step, task.task_id, briefcase.id, comment).await?;
+
+    if state.complete_current_step(comment_index).await {
+        state.increment_comment_index(step.task_index).await;
+    }
+
+    Ok(())
+}
+
+/// Moves to the next step, ending the session once the last step has been passed
+///
+/// # Returns
+/// The new current step, or a completed result if the session just ended
+pub async fn advance(state: &AppState) -> Result<ExecutionResult, String> {
+    let result = next_step(state).await?;
+
+    if result.completed {
+        log::info!("Automation session completed");
+        end(state).await?;
+    }
+
+    Ok(result)
+}
+
+/// Moves back to the previous step
+///
+/// # Errors
+/// Returns an error if the plan is already at its first step
+pub async fn previous(state: &AppState) -> Result<ExecutionResult, String> {
+    ensure_navigable(state).await?;
+    let previous_profile_id = state.current_profile_id().await;
+
+    let step = state
+        .previous_execution_step()
+        .await
+        .ok_or("Already at the first step")?;
+
+    Ok(moved_to(state, step, previous_profile_id).await)
+}
+
+/// Jumps to any step of the plan
+///
+/// A completed step shows the comment that was posted on it; completing it
+/// again records it again without moving the task's comment rotation. Any
+/// other step shows the task's next comment.
+///
+/// # Arguments
+/// * `position` - Position of the step in the execution order
+///
+/// # Errors
+/// Returns an error if no session is running, it is paused, or the plan has
+/// no step at that position
+pub async fn goto_step(state: &AppState, position: usize) -> Result<ExecutionResult, String> {
+    ensure_navigable(state).await?;
+    let previous_profile_id = state.current_profile_id().await;
+
+    let step = state
+        .goto_execution_step(position)
+        .await
+        .ok_or_else(|| format!("The plan has no step {}", position + 1))?;
+
+    Ok(moved_to(state, step, previous_profile_id).await)
+}
+
+/// Jumps to the step of a task posted by a specific BriefCase
+///
+/// # Arguments
+/// * `task_index` - Index of the task
+/// * `briefcase_id` - The BriefCase to post with, None for the task's next open step
+///
+/// # Errors
+/// Returns an error if no session is running, it is paused, or the task has
+/// no matching step
+pub async fn goto_task(
+    state: &AppState,
+    task_index: usize,
+    briefcase_id: Option<Uuid>,
+) -> Result<ExecutionResult, String> {
+    ensure_navigable(state).await?;
+    let position = state
+        .task_step_position(task_index, briefcase_id)
+        .await
+        .ok_or_else(|| match briefcase_id {
+            Some(id) => format!("Task {} has no step for briefcase {}", task_index + 1, id),
+            None => format!("Task {} has no steps", task_index + 1),
+        })?;
+
+    goto_step(state, position).await
+}
+
+/// Adds a task to the running session without resetting its progress
+///
+/// The task's steps are spliced into the plan; completed steps and the current
+/// position are kept.
+///
+/// # Arguments
+/// * `task` - The task as sent by the frontend
+/// * `source` - The command adding the task, recorded in the audit log
+///
+/// # Returns
+/// The index of the new task
+///
+/// # Errors
+/// Returns an error if no session is running or no available briefcase can post on the task
+pub async fn add_task(state: &AppState, task: Task, source: AuditSource) -> Result<usize, String> {
+    let mut session = state
+        .get_session()
+        .await
+        .ok_or("Automation is not running")?;
+
+    let task = build_task(
+        task,
+        &state.get_platforms().await,
+        &state.get_brief_cases().await,
+        &state.get_groups().await,
+    )?;
+    if task.related_brief_cases.is_empty() {
+        return Err(format!("No active briefcases can post on {}", task.link));
+    }
+
+    let (task_index, added) = state.add_task(task.clone()).await;
+    session.tasks.push(task.clone());
+    session.total_steps = state.get_progress().await.1;
+    store_session(state, session).await?;
+
+    audit::record(
+        state,
+        audit::diff_tasks(source, &[], std::slice::from_ref(&task)),
+    )
+    .await?;
+    log::info!("Added task {} with {} steps", task.link, added);
+
+    Ok(task_index)
+}
+
+/// Removes a task from the running session without resetting its progress
+///
+/// The session history keeps the task, so steps already recorded for it stay
+/// readable. If the current step belonged to the task, the plan moves on to
+/// the step after it.
+///
+/// # Arguments
+/// * `task_index` - Index of the task to remove
+/// * `source` - The command removing the task, recorded in the audit log
+///
+/// # Returns
+/// The new current step if the plan moved, None otherwise
+///
+/// # Errors
+/// Returns an error if no session is running, there is no such task, or the
+/// current task is removed while automation is paused
+pub async fn remove_task(
+    state: &AppState,
+    task_index: usize,
+    source: AuditSource,
+) -> Result<Option<ExecutionResult>, String> {
+    let mut session = state
+        .get_session()
+        .await
+        .ok_or("Automation is not running")?;
+    if session.is_paused() && state.current_task_index().await == Some(task_index) {
+        return Err("Resume automation before removing the current task".to_string());
+    }
+
+    let previous_profile_id = state.current_profile_id().await;
+    let (task, current_removed) = state
+        .remove_task(task_index)
+        .await
+        .ok_or_else(|| format!("The session has no task {}", task_index + 1))?;
+    session.total_steps = state.get_progress().await.1;
+    store_session(state, session).await?;
+
+    audit::record(
+        state,
+        audit::diff_tasks(source, std::slice::from_ref(&task), &[]),
+    )
+    .await?;
+    log::info!("Removed task {} from the session", task.link);
+
+    if !current_removed {
+        return Ok(None);
+    }
+
+    // The window still shows the removed step, so compare against its profile
+    let mut result = advance(state).await?;
+    result.should_change_profile = previous_profile_id != Some(result.profile_id);
+    Ok(Some(result))
+}
+
+/// Replaces the comments of a task in the running session
+///
+/// The task's comment index is kept if it still points at a comment.
+///
+/// # Arguments
+/// * `task_index` - Index of the task
+/// * `comments` - The new comments
+/// * `source` - The command editing the task, recorded in the audit log
+///
+/// # Errors
+/// Returns an error if no session is running or there is no such task
+pub async fn edit_task_comments(
+    state: &AppState,
+    task_index: usize,
+    comments: Vec<String>,
+    source: AuditSource,
+) -> Result<(), String> {
+    let mut session = state
+        .get_session()
+        .await
+        .ok_or("Automation is not running")?;
+    let previous = state
+        .get_task(task_index)
+        .await
+        .ok_or_else(|| format!("The session has no task {}", task_index + 1))?;
+
+    state.set_task_comments(task_index, comments).await;
+    let task = state.get_task(task_index).await.unwrap_or(previous.clone());
+
+    if let Some(recorded) = session.tasks.iter_mut().find(|t| t.task_id == task.task_id) {
+        *recorded = task.clone();
+    }
+    store_session(state, session).await?;
+
+    audit::record(state, audit::diff_tasks(source, &[previous], &[task])).await
+}
+
+/// Pauses the running session, freezing the plan at its current step
+///
+/// # Errors
+/// Returns an error if no session is running or it is already paused
+pub async fn pause(state: &AppState) -> Result<SessionTime, String> {
+    let mut session = state
+        .get_session()
+        .await
+        .ok_or("Automation is not running")?;
+    if !session.pause(Utc::now()) {
+        return Err("Automation is already paused".to_string());
+    }
+
+    let time = SessionTime::of(&session);
+    store_session(state, session).await?;
+    log::info!("Automation paused after {}s of work", time.active_ms / 1000);
+    Ok(time)
+}
+
+/// Resumes the paused session
+///
+/// # Errors
+/// Returns an error if no session is running or it is not paused
+pub async fn resume(state: &AppState) -> Result<SessionTime, String> {
+    let mut session = state
+        .get_session()
+        .await
+        .ok_or("Automation is not running")?;
+    if !session.resume(Utc::now()) {
+        return Err("Automation is not paused".to_string());
+    }
+
+    let time = SessionTime::of(&session);
+    store_session(state, session).await?;
+    log::info!(
+        "Automation resumed after {}s of pause",
+        time.paused_ms / 1000
+    );
+    Ok(time)
+}
+
+/// Gets the time spent on the running session, None if no session is running
+pub async fn session_time(state: &AppState) -> Option<SessionTime> {
+    state.get_session().await.as_ref().map(SessionTime::of)
+}
+
+/// Stops the automation and ends the running session
+pub async fn close(state: &AppState) -> Result<(), String> {
+    state.clear_current_window_label().await;
+    state.set_running(false).await;
+    end(state).await
+}
+
+/// Describes the step the plan just moved to outside of its normal order
+async fn moved_to(
+    state: &AppState,
+    step: ExecutionStep,
+    previous_profile_id: Option<Uuid>,
+) -> ExecutionResult {
+    let comment = state.current_comment().await.unwrap_or_default();
+
+    ExecutionResult {
+        completed: false,
+        profile_id: step.profile_id,
+        link: step.link,
+        should_change_profile: previous_profile_id != Some(step.profile_id),
+        task_index: step.task_index,
+        comment,
+        warning: None,
+    }
+}
+
+/// Refuses to move the plan unless a session is running and not paused
+async fn ensure_navigable(state: &AppState) -> Result<(), String> {
+    let session = state
+        .get_session()
+        .await
+        .ok_or("Automation is not running")?;
+    if session.is_paused() {
+        return Err("Automation is paused, resume it first".to_string());
+    }
+    Ok(())
+}
+
+/// Persists a changed session and makes it the current one
+async fn store_session(state: &AppState, session: Session) -> Result<(), String> {
+    state
+        .storage()
+        .write_session(&session)
+        .await
+        .map_err(log_error("Failed to record session"))?;
+
+    state.set_session(Some(session)).await;
+    Ok(())
+}
+
+/// Records a step as completed in the session history
+///
+/// # Arguments
+/// * `comment` - The comment posted on the step
+async fn record_step(
+    state: &AppState,
+    step: &ExecutionStep,
+    task_id: Uuid,
+    briefcase_id: Uuid,
+    comment: Option<String>,
+) -> Result<(), String> {
+    let Some(session) = state.get_session().await else {
+        return Ok(());
+    };
+
+    // The session keeps removed tasks, so its indices can differ from the plan's
+    let task_index = session
+        .tasks
+        .iter()
+        .position(|task| task.task_id == task_id)
+        .unwrap_or(step.task_index);
+
+    let record = SessionStep {
+        session_id: session.id,
+        step_index: state.current_position().await.unwrap_or_default(),
+        task_index,
+        briefcase_id,
+        profile_id: step.profile_id,
+        link: step.link.clone(),
+        comment,
+        completed_at: Utc::now(),
+    };
+
+    state
+        .storage()
+        .append_session_step(&record)
+        .await
+        .map_err(log_error("Failed to record session step"))
+}
+
+/// Marks the current session as ended in the session history
+async fn end(state: &AppState) -> Result<(), String> {
+    let Some(mut session) = state.take_session().await else {
+        return Ok(());
+    };
+
+    // A pause still in progress counts as paused time
+    let now = Utc::now();
+    session.resume(now);
+    session.ended_at = Some(now);
+    state
+        .storage()
+        .write_session(&session)
+        .await
+        .map_err(log_error("Failed to record session"))
+}
@@ -0,0 +1,832 @@
+use crate::audit::{AuditEntity, AuditEntry, AuditSource};
+use crate::data;
+use crate::logging::log_error;
+use crate::models::profile::PROFILES_DIR;
+use crate::profile_data;
+use crate::security::{self, DataKey, SecurityConfig, SecurityError};
+use crate::state::AppState;
+use crate::storage::{
+    self, DataSnapshot, AUDIT_FILE, BRIEFCASES_FILE, GROUPS_FILE, PLATFORMS_FILE, PROFILES_FILE,
+    SESSIONS_FILE, SESSION_STEPS_FILE, SETTINGS_FILE,
+};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use walkdir::WalkDir;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Version of the archive layout written by this build
+///
+/// Version 1 stored the data files encrypted with the app's key; since version 2
+/// they are stored decrypted, optionally sealed with an export passphrase.
+pub const BACKUP_FORMAT_VERSION: u32 = 2;
+/// Name of the manifest entry inside the archive
+const MANIFEST_FILE: &str = "manifest.json";
+/// Archive directory holding the files of the config directory
+const CONFIG_ENTRY_DIR: &str = "config";
+/// Directory inside the app data directory profile data is extracted into before
+/// it replaces the existing directories
+const STAGING_DIR: &str = "backup-import";
+/// Data files a backup may contain under `config/`
+const CONFIG_FILES: [&str; 8] = [
+    PROFILES_FILE,
+    BRIEFCASES_FILE,
+    GROUPS_FILE,
+    SESSIONS_FILE,
+    AUDIT_FILE,
+    SESSION_STEPS_FILE,
+    SETTINGS_FILE,
+    PLATFORMS_FILE,
+];
+
+/// A single file stored in a backup archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupFile {
+    /// Path of the entry inside the archive, e.g. `config/profiles.json`
+    pub path: String,
+    /// Uncompressed size in bytes
+    pub size_bytes: u64,
+    /// Hex-encoded SHA-256 of the uncompressed contents
+    pub sha256: String,
+}
+
+/// Description of a backup archive, stored as `manifest.json` inside it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Archive layout version, see `BACKUP_FORMAT_VERSION`
+    pub format_version: u32,
+    /// Version of the application that wrote the archive
+    pub app_version: String,
+    /// When the archive was written
+    pub created_at: DateTime<Utc>,
+    /// Whether the profile data directories are included
+    pub includes_profile_data: bool,
+    /// Every file in the archive except the manifest
+    pub files: Vec<BackupFile>,
+    /// Key parameters of the export passphrase the data files are sealed with,
+    /// None if they are stored in plaintext
+    #[serde(default)]
+    pub encryption: Option<SecurityConfig>,
+}
+
+/// How an imported backup is combined with the existing data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportMode {
+    /// Only add records whose IDs do not exist yet, keeping everything else
+    Merge,
+    /// Replace all data with the contents of the backup
+    Replace,
+}
+
+/// What an imported backup held, recorded in the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSummary {
+    /// When the archive was written
+    pub created_at: DateTime<Utc>,
+    /// Version of the application that wrote the archive
+    pub app_version: String,
+    /// How the backup was combined with the existing data
+    pub mode: ImportMode,
+    /// Number of files in the archive
+    pub file_count: usize,
+}
+
+/// Verified contents of a backup archive
+pub struct BackupContents {
+    /// The archive's manifest
+    pub manifest: BackupManifest,
+    /// Files of the config directory by file name
+    pub config_files: BTreeMap<String, Vec<u8>>,
+}
+
+/// A backup combined with the existing data, ready to be applied
+///
+/// Built by `prepare_import` without changing anything; `apply_import` writes it.
+pub struct PreparedImport {
+    /// The manifest of the imported archive
+    pub manifest: BackupManifest,
+    /// All data as it will be once the import is applied
+    pub data: DataSnapshot,
+    /// Audit entries to append: the backup's entries the log lacks, then the import
+    history: Vec<AuditEntry>,
+    /// Profiles whose data directories are restored from the archive
+    restored_profile_ids: HashSet<Uuid>,
+    /// Path of the archive
+    path: PathBuf,
+}
+
+/// Writer hashing everything written through it
+struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+    written: u64,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Streams data into a new archive entry, hashing it on the way
+fn add_entry<W: Write + io::Seek>(
+    zip: &mut ZipWriter<W>,
+    entry_path: String,
+    mut source: impl Read,
+    large_file: bool,
+) -> Result<BackupFile, String> {
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(large_file);
+    zip.start_file(entry_path.as_str(), options)
+        .map_err(|e| format!("Failed to write backup: {}", e))?;
+
+    let mut writer = HashingWriter {
+        inner: zip,
+        hasher: Sha256::new(),
+        written: 0,
+    };
+    io::copy(&mut source, &mut writer)
+        .map_err(|e| format!("Failed to back up {}: {}", entry_path, e))?;
+
+    Ok(BackupFile {
+        path: entry_path,
+        size_bytes: writer.written,
+        sha256: format!("{:x}", writer.hasher.finalize()),
+    })
+}
+
+/// Streams a file into the archive
+fn add_file<W: Write + io::Seek>(
+    zip: &mut ZipWriter<W>,
+    entry_path: String,
+    source: &Path,
+) -> Result<BackupFile, String> {
+    let file =
+        File::open(source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+    let large_file = file
+        .metadata()
+        .map(|m| m.len() >= u32::MAX as u64)
+        .unwrap_or(false);
+
+    add_entry(zip, entry_path, file, large_file)
+}
+
+/// Converts a path relative to the app data directory into an archive entry path
+fn entry_path(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Writes a backup archive
+///
+/// Includes the given data files under `config/` and, optionally, the profile
+/// data directories without their caches. Blocks, so run it on a blocking thread.
+///
+/// # Arguments
+/// * `target` - Path of the archive to write
+/// * `config_files` - Contents of the data files by file name
+/// * `app_data_dir` - The app data directory holding the profile data
+/// * `include_profile_data` - Whether the profile data directories are included
+/// * `encryption` - Key parameters the data files are sealed with, stored in the manifest
+/// * `app_version` - Version of the running application, stored in the manifest
+///
+/// # Returns
+/// The manifest written into the archive
+pub fn write_archive(
+    target: &Path,
+    config_files: &BTreeMap<String, Vec<u8>>,
+    app_data_dir: &Path,
+    include_profile_data: bool,
+    encryption: Option<SecurityConfig>,
+    app_version: String,
+) -> Result<BackupManifest, String> {
+    let file = File::create(target).map_err(|e| format!("Failed to create backup file: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let mut files = Vec::new();
+
+    for (name, data) in config_files {
+        files.push(add_entry(
+            &mut zip,
+            format!("{}/{}", CONFIG_ENTRY_DIR, name),
+            data.as_slice(),
+            false,
+        )?);
+    }
+
+    if include_profile_data {
+        let root = app_data_dir.join(PROFILES_DIR);
+        for entry in WalkDir::new(&root)
+            .into_iter()
+            .filter_entry(|entry| !profile_data::is_cache_dir(entry))
+        {
+            let entry = entry.map_err(|e| format!("Failed to read app data: {}", e))?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let relative = entry
+                .path()
+                .strip_prefix(app_data_dir)
+                .map_err(|e| format!("Failed to back up app data: {}", e))?;
+            files.push(add_file(&mut zip, entry_path(relative), entry.path())?);
+        }
+    }
+
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        app_version,
+        created_at: Utc::now(),
+        includes_profile_data: include_profile_data,
+        files,
+        encryption,
+    };
+
+    let data = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to write manifest: {}", e))?;
+    zip.start_file(MANIFEST_FILE, SimpleFileOptions::default())
+        .and_then(|_| zip.write_all(&data).map_err(Into::into))
+        .and_then(|_| zip.finish().map(|_| ()))
+        .map_err(|e| format!("Failed to write backup: {}", e))?;
+
+    Ok(manifest)
+}
+
+/// Serializes records into the contents of a JSON array file
+fn list_file<T: Serialize>(items: &[T]) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(items)
+}
+
+/// Serializes records into the contents of an append-only file, one per line
+fn lines_file<T: Serialize>(items: &[T]) -> Result<String, serde_json::Error> {
+    let mut data = String::new();
+    for item in items {
+        data.push_str(&serde_json::to_string(item)?);
+        data.push('\n');
+    }
+    Ok(data)
+}
+
+/// Writes all application data into a single backup archive
+///
+/// The data files are stored decrypted, so the archive does not depend on the
+/// app's passphrase. With an export passphrase they are sealed with a key
+/// derived from it instead; profile data directories are never sealed.
+///
+/// # Arguments
+/// * `path` - Where to write the archive
+/// * `include_profile_data` - Whether the profile data directories (logins) are included
+/// * `passphrase` - Passphrase to seal the data files with, None to store them in plaintext
+/// * `app_version` - Version of the running application, stored in the manifest
+///
+/// # Returns
+/// The manifest describing the archive's contents
+pub async fn export(
+    state: &AppState,
+    path: PathBuf,
+    include_profile_data: bool,
+    passphrase: Option<String>,
+    app_version: String,
+) -> Result<BackupManifest, String> {
+    let app_data_dir = state.app_data_dir().to_path_buf();
+
+    let encryption = match passphrase {
+        Some(passphrase) => {
+            security::validate_passphrase(&passphrase)?;
+            let sealed =
+                tokio::task::spawn_blocking(move || SecurityConfig::create(&passphrase, true))
+                    .await
+                    .map_err(log_error("Failed to protect backup"))?
+                    .map_err(log_error("Failed to protect backup"))?;
+            Some(sealed)
+        }
+        None => None,
+    };
+
+    // The backup holds the data files of the JSON backend, whichever backend is in use
+    let audit = state
+        .storage()
+        .read_audit()
+        .await
+        .map_err(log_error("Failed to read audit log"))?;
+    let platforms = state
+        .storage()
+        .read_platforms()
+        .await
+        .map_err(log_error("Failed to read platforms"))?;
+    let (sessions, steps) = data::read_history(state).await?;
+
+    let mut config_files = BTreeMap::new();
+    for (name, data) in [
+        (PROFILES_FILE, list_file(&state.get_profiles().await)),
+        (BRIEFCASES_FILE, list_file(&state.get_brief_cases().await)),
+        (GROUPS_FILE, list_file(&state.get_groups().await)),
+        (SESSIONS_FILE, list_file(&sessions)),
+        (AUDIT_FILE, lines_file(&audit)),
+        (SESSION_STEPS_FILE, lines_file(&steps)),
+        (
+            SETTINGS_FILE,
+            serde_json::to_string_pretty(&state.get_settings().await),
+        ),
+        (PLATFORMS_FILE, list_file(&platforms)),
+    ] {
+        let data = data.map_err(log_error("Failed to write backup"))?;
+        let data = match &encryption {
+            Some((_, key)) => {
+                security::seal_file_with(key, &data).map_err(log_error("Failed to write backup"))?
+            }
+            None => data,
+        };
+        config_files.insert(name.to_string(), data.into_bytes());
+    }
+
+    let manifest = tokio::task::spawn_blocking(move || {
+        write_archive(
+            &path,
+            &config_files,
+            &app_data_dir,
+            include_profile_data,
+            encryption.map(|(config, _)| config),
+            app_version,
+        )
+    })
+    .await
+    .map_err(log_error("Failed to write backup"))?
+    .map_err(log_error("Failed to write backup"))?;
+
+    log::info!("Exported backup with {} files", manifest.files.len());
+    Ok(manifest)
+}
+
+/// Opens a backup archive and reads its manifest
+fn open_archive(source: &Path) -> Result<(ZipArchive<File>, BackupManifest), String> {
+    let file = File::open(source).map_err(|e| format!("Failed to open backup: {}", e))?;
+    let mut archive =
+        ZipArchive::new(file).map_err(|e| format!("Backup is not a valid archive: {}", e))?;
+
+    let manifest: BackupManifest = {
+        let entry = archive
+            .by_name(MANIFEST_FILE)
+            .map_err(|_| "Backup has no manifest".to_string())?;
+        serde_json::from_reader(entry).map_err(|e| format!("Backup manifest is invalid: {}", e))?
+    };
+
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup was written by a newer version ({}), please update the app",
+            manifest.app_version
+        ));
+    }
+
+    Ok((archive, manifest))
+}
+
+/// Reads a backup archive and verifies every file against the manifest
+///
+/// Data files sealed with an export passphrase are decrypted. Blocks, so run it
+/// on a blocking thread.
+///
+/// # Arguments
+/// * `source` - Path of the archive to read
+/// * `passphrase` - The export passphrase, if the archive was protected with one
+///
+/// # Errors
+/// Returns an error if the archive is unreadable, a file is missing, a
+/// checksum does not match, `config/` holds a file this app does not write, or
+/// the passphrase is missing or wrong
+pub fn read_archive(source: &Path, passphrase: Option<&str>) -> Result<BackupContents, String> {
+    let (mut archive, manifest) = open_archive(source)?;
+    let mut config_files = BTreeMap::new();
+
+    for file in &manifest.files {
+        let mut entry = archive
+            .by_name(&file.path)
+            .map_err(|_| format!("Backup is missing {}", file.path))?;
+
+        let config_name = file
+            .path
+            .strip_prefix(CONFIG_ENTRY_DIR)
+            .and_then(|name| name.strip_prefix('/'));
+
+        let mut hasher = Sha256::new();
+        match config_name {
+            Some(name) => {
+                // Only known data files are restored, never a path chosen by the archive
+                if !CONFIG_FILES.contains(&name) {
+                    return Err(format!("Backup contains an unexpected file: {}", file.path));
+                }
+                let mut data = Vec::new();
+                entry
+                    .read_to_end(&mut data)
+                    .map_err(|e| format!("Failed to read {}: {}", file.path, e))?;
+                hasher.update(&data);
+                config_files.insert(name.to_string(), data);
+            }
+            None => {
+                io::copy(&mut entry, &mut hasher)
+                    .map_err(|e| format!("Failed to read {}: {}", file.path, e))?;
+            }
+        }
+
+        if format!("{:x}", hasher.finalize()) != file.sha256 {
+            return Err(format!("Checksum mismatch for {}", file.path));
+        }
+    }
+
+    if let Some(config) = &manifest.encryption {
+        let passphrase = passphrase.ok_or("This backup is protected with a passphrase")?;
+        let key = config.unlock(passphrase).map_err(|e| e.to_string())?;
+        for (name, data) in config_files.iter_mut() {
+            let plaintext = security::open_file_with(&key, &String::from_utf8_lossy(data))
+                .map_err(|e| format!("Failed to decrypt {} from the backup: {}", name, e))?;
+            *data = plaintext.into_bytes();
+        }
+    }
+
+    Ok(BackupContents {
+        manifest,
+        config_files,
+    })
+}
+
+/// Describes why an encrypted file from a backup could not be read
+fn backup_security_error(name: &str, error: SecurityError) -> String {
+    match error {
+        SecurityError::Corrupted => format!(
+            "{} in the backup is encrypted with a different passphrase",
+            name
+        ),
+        e => format!("Failed to read {} from the backup: {}", name, e),
+    }
+}
+
+/// Parses a JSON data file from a backup, None if the backup does not contain it
+///
+/// Archives written before format version 2 hold the files encrypted with the
+/// app's key; they are decrypted with the current passphrase.
+fn parse_file<T: DeserializeOwned>(
+    key: &DataKey,
+    contents: &BackupContents,
+    name: &str,
+) -> Result<Option<T>, String> {
+    let Some(data) = contents.config_files.get(name) else {
+        return Ok(None);
+    };
+
+    let data = key
+        .open_file(&String::from_utf8_lossy(data))
+        .map_err(|e| backup_security_error(name, e))?;
+    serde_json::from_str(&data)
+        .map(Some)
+        .map_err(|e| format!("Backup contains an invalid {}: {}", name, e))
+}
+
+/// Parses an append-only file from a backup, empty if the backup does not contain it
+fn parse_lines<T: DeserializeOwned>(
+    key: &DataKey,
+    contents: &BackupContents,
+    name: &str,
+) -> Result<Vec<T>, String> {
+    match contents.config_files.get(name) {
+        Some(data) => storage::decode_lines(key, &String::from_utf8_lossy(data))
+            .map_err(|e| format!("Failed to read {} from the backup: {}", name, e)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Appends the items whose IDs are not taken yet
+///
+/// # Returns
+/// The IDs of the appended items
+fn add_missing<T, K: PartialEq>(items: &mut Vec<T>, added: Vec<T>, id: impl Fn(&T) -> K) -> Vec<K> {
+    let mut ids = Vec::new();
+    for item in added {
+        if !items.iter().any(|existing| id(existing) == id(&item)) {
+            ids.push(id(&item));
+            items.push(item);
+        }
+    }
+    ids
+}
+
+/// Reads a backup archive and combines it with the existing data
+///
+/// Every file is verified against the manifest's checksums before anything is
+/// parsed. `Merge` only adds profiles, briefcases, groups, platforms and
+/// sessions whose IDs do not exist yet, keeping the existing settings;
+/// `Replace` swaps all data for the backup's. Nothing is changed until the
+/// result is passed to `apply_import`.
+///
+/// # Arguments
+/// * `path` - The archive to import
+/// * `mode` - How the backup is combined with the existing data
+/// * `passphrase` - The passphrase the archive was exported with, if any
+///
+/// # Errors
+/// Returns an error if automation is running, the app is locked or the archive
+/// cannot be read
+pub async fn prepare_import(
+    state: &AppState,
+    path: PathBuf,
+    mode: ImportMode,
+    passphrase: Option<String>,
+) -> Result<PreparedImport, String> {
+    if state.is_running().await {
+        return Err("Stop the automation before importing a backup".to_string());
+    }
+    if state.is_locked().await {
+        return Err("Unlock the app before importing a backup".to_string());
+    }
+
+    let contents = tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || read_archive(&path, passphrase.as_deref())
+    })
+    .await
+    .map_err(log_error("Failed to read backup"))?
+    .map_err(log_error("Failed to read backup"))?;
+
+    let key = state.storage().data_key();
+    let backup = DataSnapshot {
+        profiles: parse_file(key, &contents, PROFILES_FILE)?.unwrap_or_default(),
+        briefcases: parse_file(key, &contents, BRIEFCASES_FILE)?.unwrap_or_default(),
+        groups: parse_file(key, &contents, GROUPS_FILE)?.unwrap_or_default(),
+        settings: parse_file(key, &contents, SETTINGS_FILE)?.unwrap_or_default(),
+        platforms: parse_file(key, &contents, PLATFORMS_FILE)?.unwrap_or_default(),
+        sessions: parse_file(key, &contents, SESSIONS_FILE)?.unwrap_or_default(),
+        steps: parse_lines(key, &contents, SESSION_STEPS_FILE)?,
+    };
+    let backup_audit: Vec<AuditEntry> = parse_lines(key, &contents, AUDIT_FILE)?;
+    log::info!(
+        "Importing backup from {} ({:?})",
+        contents.manifest.created_at,
+        mode
+    );
+
+    // The audit log is only appended to: the backup's entries it lacks, then the import
+    let known: HashSet<Uuid> = state
+        .storage()
+        .read_audit()
+        .await
+        .map_err(log_error("Failed to read audit log"))?
+        .iter()
+        .map(|entry| entry.id)
+        .collect();
+    let mut history: Vec<AuditEntry> = backup_audit
+        .into_iter()
+        .filter(|entry| !known.contains(&entry.id))
+        .collect();
+    let summary = ImportSummary {
+        created_at: contents.manifest.created_at,
+        app_version: contents.manifest.app_version.clone(),
+        mode,
+        file_count: contents.manifest.files.len(),
+    };
+    history.push(AuditEntry::new(
+        AuditSource::command("import_backup"),
+        AuditEntity::Backup,
+        None,
+        None,
+        serde_json::to_value(&summary).ok(),
+    ));
+
+    let (data, restored_profile_ids) = match mode {
+        ImportMode::Replace => {
+            let restored = backup.profiles.iter().map(|p| p.profile_id).collect();
+            (backup, restored)
+        }
+        ImportMode::Merge => {
+            let (mut sessions, mut steps) = data::read_history(state).await?;
+            for session_id in add_missing(&mut sessions, backup.sessions, |s| s.id) {
+                steps.extend(
+                    backup
+                        .steps
+                        .iter()
+                        .filter(|step| step.session_id == session_id)
+                        .cloned(),
+                );
+            }
+
+            let mut profiles = state.get_profiles().await;
+            let restored = add_missing(&mut profiles, backup.profiles, |p| p.profile_id);
+            let mut briefcases = state.get_brief_cases().await;
+            add_missing(&mut briefcases, backup.briefcases, |b| b.id);
+            let mut groups = state.get_groups().await;
+            add_missing(&mut groups, backup.groups, |g| g.id);
+            let mut platforms = state
+                .storage()
+                .read_platforms()
+                .await
+                .map_err(log_error("Failed to read platforms"))?;
+            add_missing(&mut platforms, backup.platforms, |p| p.id.clone());
+
+            let data = DataSnapshot {
+                profiles,
+                briefcases,
+                groups,
+                settings: state.get_settings().await,
+                platforms,
+                sessions,
+                steps,
+            };
+            (data, restored.into_iter().collect())
+        }
+    };
+
+    Ok(PreparedImport {
+        manifest: contents.manifest,
+        data,
+        history,
+        restored_profile_ids,
+        path,
+    })
+}
+
+/// Writes a prepared import
+///
+/// Profile data is staged first so a failed extraction leaves the existing
+/// directories alone; the data is then validated and written in one go by
+/// `data::restore`, and only then do the staged directories replace the
+/// existing ones. The audit log is kept and records the import.
+///
+/// # Returns
+/// The manifest of the imported archive
+pub async fn apply_import(
+    state: &AppState,
+    import: PreparedImport,
+) -> Result<BackupManifest, String> {
+    let PreparedImport {
+        manifest,
+        data,
+        history,
+        restored_profile_ids,
+        path,
+    } = import;
+    let app_data_dir = state.app_data_dir().to_path_buf();
+
+    let staging_dir = if manifest.includes_profile_data {
+        let staging_dir = tokio::task::spawn_blocking({
+            let app_data_dir = app_data_dir.clone();
+            let profile_ids = restored_profile_ids.clone();
+            move || extract_profile_dirs(&path, &app_data_dir, &profile_ids)
+        })
+        .await
+        .map_err(log_error("Failed to restore profile data"))?
+        .map_err(log_error("Failed to restore profile data"))?;
+        Some(staging_dir)
+    } else {
+        None
+    };
+
+    let source = AuditSource::command("import_backup");
+    if let Err(e) = data::restore(state, data, history, source).await {
+        if let Some(staging_dir) = &staging_dir {
+            discard_profile_dirs(staging_dir);
+        }
+        return Err(e);
+    }
+
+    if let Some(staging_dir) = staging_dir {
+        tokio::task::spawn_blocking(move || {
+            install_profile_dirs(&staging_dir, &app_data_dir, &restored_profile_ids)
+        })
+        .await
+        .map_err(log_error("Failed to restore profile data"))?
+        .map_err(log_error("Failed to restore profile data"))?;
+    }
+
+    Ok(manifest)
+}
+
+/// Extracts the data directories of the given profiles into a staging directory
+///
+/// Nothing in the app data directory is touched until `install_profile_dirs`
+/// moves the staged directories into place. Blocks, so run it on a blocking
+/// thread.
+///
+/// # Arguments
+/// * `source` - Path of the archive to read
+/// * `app_data_dir` - The app data directory the staging directory is created in
+/// * `profile_ids` - The profiles whose directories are extracted
+///
+/// # Returns
+/// The staging directory, removed again if extracting fails
+pub fn extract_profile_dirs(
+    source: &Path,
+    app_data_dir: &Path,
+    profile_ids: &HashSet<Uuid>,
+) -> Result<PathBuf, String> {
+    let staging_dir = app_data_dir.join(STAGING_DIR);
+    discard_profile_dirs(&staging_dir);
+
+    let result = stage_profile_dirs(source, &staging_dir, profile_ids);
+    if result.is_err() {
+        discard_profile_dirs(&staging_dir);
+    }
+    result.map(|_| staging_dir)
+}
+
+/// Writes the files of the given profiles from the archive below `staging_dir`
+fn stage_profile_dirs(
+    source: &Path,
+    staging_dir: &Path,
+    profile_ids: &HashSet<Uuid>,
+) -> Result<(), String> {
+    let (mut archive, manifest) = open_archive(source)?;
+
+    for profile_id in profile_ids {
+        let dir = staging_dir.join(PROFILES_DIR).join(profile_id.to_string());
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create profile directory: {}", e))?;
+    }
+
+    for file in &manifest.files {
+        let mut entry = archive
+            .by_name(&file.path)
+            .map_err(|_| format!("Backup is missing {}", file.path))?;
+
+        // Entries escaping the staging directory are never written
+        let Some(relative) = entry.enclosed_name() else {
+            return Err(format!("Backup contains an unsafe path: {}", file.path));
+        };
+
+        let belongs_to_profile = relative
+            .strip_prefix(PROFILES_DIR)
+            .ok()
+            .and_then(|rest| rest.components().next())
+            .and_then(|id| Uuid::parse_str(&id.as_os_str().to_string_lossy()).ok())
+            .is_some_and(|id| profile_ids.contains(&id));
+        if !belongs_to_profile {
+            continue;
+        }
+
+        let destination: PathBuf = staging_dir.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create profile directory: {}", e))?;
+        }
+        let mut output = File::create(&destination)
+            .map_err(|e| format!("Failed to restore {}: {}", file.path, e))?;
+        io::copy(&mut entry, &mut output)
+            .map_err(|e| format!("Failed to restore {}: {}", file.path, e))?;
+    }
+
+    Ok(())
+}
+
+/// Replaces the data directories of the given profiles with the staged ones
+///
+/// Removes the staging directory afterwards. Blocks, so run it on a blocking thread.
+///
+/// # Arguments
+/// * `staging_dir` - The directory returned by `extract_profile_dirs`
+/// * `app_data_dir` - The app data directory to move the directories into
+/// * `profile_ids` - The profiles whose directories are replaced
+pub fn install_profile_dirs(
+    staging_dir: &Path,
+    app_data_dir: &Path,
+    profile_ids: &HashSet<Uuid>,
+) -> Result<(), String> {
+    let root = app_data_dir.join(PROFILES_DIR);
+    fs::create_dir_all(&root).map_err(|e| format!("Failed to create profile directory: {}", e))?;
+
+    for profile_id in profile_ids {
+        let staged = staging_dir.join(PROFILES_DIR).join(profile_id.to_string());
+        let dir = root.join(profile_id.to_string());
+        if dir.exists() {
+            fs::remove_dir_all(&dir)
+                .map_err(|e| format!("Failed to replace profile directory: {}", e))?;
+        }
+        fs::rename(&staged, &dir)
+            .map_err(|e| format!("Failed to replace profile directory: {}", e))?;
+    }
+
+    discard_profile_dirs(staging_dir);
+    Ok(())
+}
+
+/// Removes a staging directory left by `extract_profile_dirs`
+pub fn discard_profile_dirs(staging_dir: &Path) {
+    if staging_dir.exists() {
+        if let Err(e) = fs::remove_dir_all(staging_dir) {
+            log::warn!("Failed to remove {}: {}", staging_dir.display(), e);
+        }
+    }
+}
@@ -0,0 +1,695 @@
+//! Command line access to the attache data, built on the same core as the app
+//!
+//! Works on the data directory of the desktop app unless `--data-dir` or
+//! `ATTACHE_DATA_DIR` points elsewhere. Commands that only read open the data
+//! read-only; commands changing it refuse to run while the app is open, as it
+//! does not notice changes made by other processes.
+
+use attache_lib::audit::AuditSource;
+use attache_lib::execution::PlanPreview;
+use attache_lib::models::{BriefCase, BriefCaseGroup, Profile, SocialMedia, Task};
+use attache_lib::platforms::PlatformRegistry;
+use attache_lib::state::AppState;
+use attache_lib::storage::{StorageKind, StoreLock};
+use attache_lib::{automation, backup, data, profile_data};
+use clap::{Parser, Subcommand};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use uuid::Uuid;
+
+/// Identifier of the desktop app, taken from tauri.conf.json by the build script
+const APP_IDENTIFIER: &str = env!("ATTACHE_APP_IDENTIFIER");
+/// Environment variable overriding the data directory
+const DATA_DIR_ENV: &str = "ATTACHE_DATA_DIR";
+/// Environment variable holding the passphrase of a locked data directory
+const PASSPHRASE_ENV: &str = "ATTACHE_PASSPHRASE";
+/// Environment variable holding the passphrase an exported backup is protected with
+const BACKUP_PASSPHRASE_ENV: &str = "ATTACHE_BACKUP_PASSPHRASE";
+
+/// Manage attache profiles, briefcases and history without the app
+#[derive(Debug, Parser)]
+#[command(name = "attache-cli", version)]
+struct Cli {
+    /// The app data directory, defaults to the one the desktop app uses
+    #[arg(long, global = true)]
+    data_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// Top-level commands
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List, add, rename and delete profiles
+    #[command(subcommand)]
+    Profiles(ProfileCommand),
+    /// List, add, rename and delete briefcases
+    #[command(subcommand)]
+    Briefcases(BriefcaseCommand),
+    /// List the built-in platforms and those added in platforms.json
+    Platforms,
+    /// Import tasks from a file and print them in the format the app uses
+    Tasks {
+        /// JSON file holding the tasks
+        file: PathBuf,
+        /// Print the tasks as JSON instead of a summary
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print what automation would do with the tasks in a file
+    Plan {
+        /// JSON file holding the tasks
+        file: PathBuf,
+    },
+    /// Export backups
+    #[command(subcommand)]
+    Backup(BackupCommand),
+    /// Print a report of every recorded step
+    History {
+        /// Write CSV instead of a table
+        #[arg(long)]
+        csv: bool,
+        /// Write the report to a file instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Check the data files for problems, exits with 1 if any are found
+    Validate,
+}
+
+/// Profile commands
+#[derive(Debug, Subcommand)]
+enum ProfileCommand {
+    /// List all profiles in their user-defined order
+    List,
+    /// Create a profile
+    Add {
+        /// Name of the new profile
+        name: String,
+    },
+    /// Rename a profile
+    Rename {
+        /// ID or name of the profile
+        profile: String,
+        /// The new name
+        name: String,
+    },
+    /// Delete a profile together with its briefcases
+    Delete {
+        /// ID or name of the profile
+        profile: String,
+        /// Also delete the profile's data directory (logins, cookies)
+        #[arg(long)]
+        wipe: bool,
+    },
+}
+
+/// BriefCase commands
+#[derive(Debug, Subcommand)]
+enum BriefcaseCommand {
+    /// List briefcases
+    List {
+        /// Only list the briefcases of this profile (ID or name)
+        #[arg(long)]
+        profile: Option<String>,
+    },
+    /// Add a briefcase to a profile
+    Add {
+        /// ID or name of the profile
+        profile: String,
+        /// Platform of the account, by ID or name (see `platforms`)
+        platform: String,
+        /// User name of the account
+        user_name: String,
+    },
+    /// Change the user name of a briefcase
+    Rename {
+        /// ID of the briefcase
+        id: Uuid,
+        /// The new user name
+        user_name: String,
+    },
+    /// Delete a briefcase, removing it from its groups
+    Delete {
+        /// ID of the briefcase
+        id: Uuid,
+    },
+}
+
+/// Backup commands
+#[derive(Debug, Subcommand)]
+enum BackupCommand {
+    /// Write all data into a backup archive
+    Export {
+        /// Where to write the archive
+        path: PathBuf,
+        /// Include the profile data directories (logins)
+        #[arg(long)]
+        include_profile_data: bool,
+        /// Protect the data files with the passphrase in ATTACHE_BACKUP_PASSPHRASE
+        #[arg(long)]
+        encrypt: bool,
+    },
+}
+
+/// A task as written in a tasks file
+#[derive(Debug, Deserialize)]
+struct TaskInput {
+    /// The URL of the post
+    link: String,
+    /// The platform of the post, by ID or name
+    social_media: SocialMedia,
+    /// The comments, as a list or as pasted text with one comment per line
+    #[serde(default)]
+    comments: CommentsInput,
+    /// Name or ID of the group that should post, all accounts if missing
+    #[serde(default)]
+    group: Option<String>,
+}
+
+/// Comments of a task, either already split or as pasted text
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum CommentsInput {
+    /// One entry per comment
+    List(Vec<String>),
+    /// Text with one comment per line, cleaned up like pasted comments in the app
+    Text(String),
+}
+
+impl Command {
+    /// Whether the command changes the data, so it needs the data directory to itself
+    fn changes_data(&self) -> bool {
+        match self {
+            Self::Profiles(command) => !matches!(command, ProfileCommand::List),
+            Self::Briefcases(command) => !matches!(command, BriefcaseCommand::List { .. }),
+            Self::Platforms
+            | Self::Tasks { .. }
+            | Self::Plan { .. }
+            | Self::Backup(_)
+            | Self::History { .. }
+            | Self::Validate => false,
+        }
+    }
+}
+
+impl Default for CommentsInput {
+    fn default() -> Self {
+        Self::List(Vec::new())
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli).await {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Opens the data directory and runs a command on it
+async fn run(cli: Cli) -> Result<ExitCode, String> {
+    let app_data_dir = resolve_data_dir(cli.data_dir)?;
+    let read_only = !cli.command.changes_data();
+    let _lock = if read_only {
+        None
+    } else {
+        Some(StoreLock::acquire(&app_data_dir).map_err(|e| e.to_string())?)
+    };
+    let state = open(app_data_dir, read_only).await?;
+    let platforms = state.get_platforms().await;
+
+    match cli.command {
+        Command::Profiles(command) => profiles(&state, command).await?,
+        Command::Briefcases(command) => briefcases(&state, command).await?,
+        Command::Platforms => {
+            for platform in platforms.all() {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    platform.id,
+                    platform.name,
+                    platform.home_url,
+                    platform.hosts.join(", ")
+                );
+            }
+        }
+        Command::Tasks { file, json } => {
+            let tasks = read_tasks(&state, &file).await?;
+            if json {
+                println!("{}", to_json(&tasks)?);
+            } else {
+                for (index, task) in tasks.iter().enumerate() {
+                    println!(
+                        "{}\t{}\t{}\t{} comments\t{} accounts",
+                        index + 1,
+                        platforms.name(&task.social_media),
+                        task.link,
+                        task.comments.len(),
+                        task.related_brief_cases.len()
+                    );
+                }
+            }
+        }
+        Command::Plan { file } => {
+            let tasks = read_tasks(&state, &file).await?;
+            let preview = automation::preview(&state, &to_json(&tasks)?).await?;
+            print_preview(&platforms, &tasks, &preview);
+        }
+        Command::Backup(BackupCommand::Export {
+            path,
+            include_profile_data,
+            encrypt,
+        }) => {
+            let passphrase =
+                if encrypt {
+                    Some(std::env::var(BACKUP_PASSPHRASE_ENV).map_err(|_| {
+                        format!("Set {} to encrypt the backup", BACKUP_PASSPHRASE_ENV)
+                    })?)
+                } else {
+                    None
+                };
+            let manifest = backup::export(
+                &state,
+                path.clone(),
+                include_profile_data,
+                passphrase,
+                env!("CARGO_PKG_VERSION").to_string(),
+            )
+            .await?;
+            println!("Wrote {} files to {}", manifest.files.len(), path.display());
+        }
+        Command::History { csv, output } => {
+            let report = history_report(&state, csv).await?;
+            match output {
+                Some(path) => std::fs::write(&path, report)
+                    .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?,
+                None => print!("{}", report),
+            }
+        }
+        Command::Validate => {
+            let problems = data::validate(&state).await;
+            if problems.is_empty() {
+                println!("No problems found");
+                return Ok(ExitCode::SUCCESS);
+            }
+            for problem in &problems {
+                println!("{}", problem);
+            }
+            return Ok(ExitCode::from(1));
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Picks the data directory from the flag, the environment or the platform default
+fn resolve_data_dir(flag: Option<PathBuf>) -> Result<PathBuf, String> {
+    if let Some(dir) = flag {
+        return Ok(dir);
+    }
+    if let Some(dir) = std::env::var_os(DATA_DIR_ENV) {
+        return Ok(PathBuf::from(dir));
+    }
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER))
+        .ok_or_else(|| format!("No data directory found, set {}", DATA_DIR_ENV))
+}
+
+/// Loads the state from a data directory, unlocking it with the passphrase from the environment
+///
+/// # Arguments
+/// * `app_data_dir` - The app data directory
+/// * `read_only` - Whether to open the data without creating or migrating anything
+async fn open(app_data_dir: PathBuf, read_only: bool) -> Result<AppState, String> {
+    let storage = if read_only {
+        StorageKind::from_env().open_read_only(&app_data_dir)
+    } else {
+        StorageKind::from_env().open(&app_data_dir)
+    };
+    let state = AppState::load(storage, app_data_dir)
+        .await
+        .map_err(|e| format!("Failed to open storage: {}", e))?;
+
+    if state.is_locked().await {
+        let passphrase = std::env::var(PASSPHRASE_ENV)
+            .map_err(|_| format!("The data is locked, set {}", PASSPHRASE_ENV))?;
+        data::unlock(&state, passphrase).await?;
+    }
+
+    Ok(state)
+}
+
+/// Audit source of changes made through a CLI command
+fn source(command: &'static str) -> AuditSource {
+    AuditSource::command(command)
+}
+
+/// Runs a profile command
+async fn profiles(state: &AppState, command: ProfileCommand) -> Result<(), String> {
+    let mut profiles = state.get_profiles().await;
+
+    match command {
+        ProfileCommand::List => {
+            let briefcases = state.get_brief_cases().await;
+            for profile in &profiles {
+                let count = briefcases
+                    .iter()
+                    .filter(|b| b.profile_id == profile.profile_id)
+                    .count();
+                println!(
+                    "{}\t{}\t{} briefcases",
+                    profile.profile_id, profile.profile_name, count
+                );
+            }
+        }
+        ProfileCommand::Add { name } => {
+            let mut profile = Profile::new(name, state.app_data_dir())
+                .await
+                .map_err(|e| format!("Failed to create profile: {}", e))?;
+            profile.sort_order = profiles.len() as u32;
+            profiles.push(profile.clone());
+            data::save_profiles(state, profiles, source("cli_add_profile")).await?;
+            println!("{}", profile.profile_id);
+        }
+        ProfileCommand::Rename { profile, name } => {
+            let id = find_profile(&profiles, &profile)?.profile_id;
+            if let Some(profile) = profiles.iter_mut().find(|p| p.profile_id == id) {
+                profile.profile_name = name;
+            }
+            data::save_profiles(state, profiles, source("cli_rename_profile")).await?;
+        }
+        ProfileCommand::Delete { profile, wipe } => {
+            let profile = find_profile(&profiles, &profile)?.clone();
+            profiles.retain(|p| p.profile_id != profile.profile_id);
+
+            // Like the app, deleting a profile deletes its accounts
+            let mut briefcases = state.get_brief_cases().await;
+            briefcases.retain(|b| b.profile_id != profile.profile_id);
+            data::save_profiles(state, profiles, source("cli_delete_profile")).await?;
+            data::save_briefcases(state, briefcases, source("cli_delete_profile")).await?;
+
+            if wipe {
+                let freed = profile_data::wipe(profile.get_data_path(state.app_data_dir())).await?;
+                println!("Freed {} bytes", freed);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs a BriefCase command
+async fn briefcases(state: &AppState, command: BriefcaseCommand) -> Result<(), String> {
+    let profiles = state.get_profiles().await;
+    let mut briefcases = state.get_brief_cases().await;
+    let platforms = state.get_platforms().await;
+
+    match command {
+        BriefcaseCommand::List { profile } => {
+            let profile_id = profile
+                .map(|profile| find_profile(&profiles, &profile).map(|p| p.profile_id))
+                .transpose()?;
+            let names: HashMap<Uuid, &str> = profiles
+                .iter()
+                .map(|p| (p.profile_id, p.profile_name.as_str()))
+                .collect();
+
+            for briefcase in briefcases
+                .iter()
+                .filter(|b| profile_id.is_none_or(|id| b.profile_id == id))
+            {
+                println!(
+                    "{}\t{}\t{}\t{}{}",
+                    briefcase.id,
+                    names.get(&briefcase.profile_id).unwrap_or(&"?"),
+                    platforms.name(&briefcase.social_media),
+                    briefcase.user_name,
+                    if briefcase.is_active {
+                        ""
+                    } else {
+                        "\t(disabled)"
+                    }
+                );
+            }
+        }
+        BriefcaseCommand::Add {
+            profile,
+            platform,
+            user_name,
+        } => {
+            let profile_id = find_profile(&profiles, &profile)?.profile_id;
+            let briefcase = BriefCase::new(
+                parse_platform(&platforms, &platform)?,
+                profile_id,
+                user_name,
+            );
+            briefcases.push(briefcase.clone());
+            data::save_briefcases(state, briefcases, source("cli_add_briefcase")).await?;
+            println!("{}", briefcase.id);
+        }
+        BriefcaseCommand::Rename { id, user_name } => {
+            let briefcase = briefcases
+                .iter_mut()
+                .find(|b| b.id == id)
+                .ok_or_else(|| format!("No briefcase with ID {}", id))?;
+            briefcase.user_name = user_name;
+            data::save_briefcases(state, briefcases, source("cli_rename_briefcase")).await?;
+        }
+        BriefcaseCommand::Delete { id } => {
+            let count = briefcases.len();
+            briefcases.retain(|b| b.id != id);
+            if briefcases.len() == count {
+                return Err(format!("No briefcase with ID {}", id));
+            }
+            data::save_briefcases(state, briefcases, source("cli_delete_briefcase")).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds a profile by ID or by its exact name
+///
+/// # Errors
+/// Returns an error if no profile or more than one profile matches
+fn find_profile<'a>(profiles: &'a [Profile], key: &str) -> Result<&'a Profile, String> {
+    if let Ok(id) = Uuid::parse_str(key) {
+        if let Some(profile) = profiles.iter().find(|p| p.profile_id == id) {
+            return Ok(profile);
+        }
+    }
+
+    let mut matches = profiles.iter().filter(|p| p.profile_name == key);
+    match (matches.next(), matches.next()) {
+        (Some(profile), None) => Ok(profile),
+        (Some(_), Some(_)) => Err(format!(
+            "More than one profile is named {}, use its ID",
+            key
+        )),
+        (None, _) => Err(format!("No profile named {}", key)),
+    }
+}
+
+/// Finds a platform by its ID or name, ignoring case
+fn parse_platform(platforms: &PlatformRegistry, key: &str) -> Result<SocialMedia, String> {
+    platforms
+        .get(&SocialMedia::new(key))
+        .or_else(|| {
+            platforms
+                .all()
+                .iter()
+                .find(|p| p.name.eq_ignore_ascii_case(key.trim()))
+        })
+        .map(|platform| platform.id.clone())
+        .ok_or_else(|| format!("Unknown platform {}", key))
+}
+
+/// Reads a tasks file and assigns the available briefcases to its tasks
+///
+/// Links are canonicalized by the rules of their platform.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or a task is for an unknown
+/// platform or targets an unknown group
+async fn read_tasks(state: &AppState, path: &Path) -> Result<Vec<Task>, String> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let inputs: Vec<TaskInput> = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+    let briefcases = state.get_brief_cases().await;
+    let groups = state.get_groups().await;
+    let platforms = state.get_platforms().await;
+
+    inputs
+        .into_iter()
+        .map(|input| {
+            let group = input
+                .group
+                .as_deref()
+                .map(|key| find_group(&groups, key))
+                .transpose()?;
+            let social_media = parse_platform(&platforms, input.social_media.as_str())?;
+            let link = platforms.get(&social_media).map_or_else(
+                || input.link.clone(),
+                |platform| platform.canonicalize(&input.link),
+            );
+            let comments = match input.comments {
+                CommentsInput::List(comments) => comments,
+                CommentsInput::Text(text) => Task::format_comments(&text),
+            };
+
+            Ok(Task::new(link, comments, social_media, &briefcases, group))
+        })
+        .collect()
+}
+
+/// Finds a group by ID or by its exact name
+fn find_group<'a>(groups: &'a [BriefCaseGroup], key: &str) -> Result<&'a BriefCaseGroup, String> {
+    groups
+        .iter()
+        .find(|g| g.id.to_string() == key)
+        .or_else(|| groups.iter().find(|g| g.name == key))
+        .ok_or_else(|| format!("No group named {}", key))
+}
+
+/// Serializes tasks the way the frontend sends them to the backend
+fn to_json(tasks: &[Task]) -> Result<String, String> {
+    serde_json::to_string_pretty(tasks).map_err(|e| format!("Failed to serialize tasks: {}", e))
+}
+
+/// Prints a plan preview as text
+fn print_preview(platforms: &PlatformRegistry, tasks: &[Task], preview: &PlanPreview) {
+    println!(
+        "{} steps on {} profiles",
+        preview.total_steps,
+        preview.profiles.len()
+    );
+    for profile in &preview.profiles {
+        println!(
+            "  {}\t{} steps",
+            profile
+                .profile_name
+                .as_deref()
+                .unwrap_or("(deleted profile)"),
+            profile.step_count
+        );
+    }
+    for group in &preview.task_groups {
+        println!(
+            "Task {} posts with group {} ({} accounts)",
+            group.task_index + 1,
+            group.group_name.as_deref().unwrap_or("(deleted group)"),
+            group.briefcase_ids.len()
+        );
+    }
+    for excluded in &preview.excluded {
+        println!(
+            "Left out: {} on {} ({:?})",
+            excluded.briefcase.user_name,
+            platforms.name(&excluded.briefcase.social_media),
+            excluded.reason
+        );
+    }
+    for briefcase in &preview.unverified_logins {
+        let status = match briefcase.last_verified {
+            Some(verification) => format!(
+                "{:?} on {}",
+                verification.status,
+                verification.verified_at.date_naive()
+            ),
+            None => "never checked".to_string(),
+        };
+        println!(
+            "Login not verified: {} on {} ({})",
+            briefcase.user_name,
+            platforms.name(&briefcase.social_media),
+            status
+        );
+    }
+    for &index in &preview.tasks_without_accounts {
+        println!(
+            "No account can post on task {}: {}",
+            index + 1,
+            tasks[index].link
+        );
+    }
+}
+
+/// Builds a report with one line per recorded step
+///
+/// # Arguments
+/// * `csv` - Whether to write CSV instead of tab-separated columns
+async fn history_report(state: &AppState, csv: bool) -> Result<String, String> {
+    let (sessions, steps) = data::read_history(state).await?;
+    let profiles: HashMap<Uuid, String> = state
+        .get_profiles()
+        .await
+        .into_iter()
+        .map(|p| (p.profile_id, p.profile_name))
+        .collect();
+    let briefcases: HashMap<Uuid, BriefCase> = state
+        .get_brief_cases()
+        .await
+        .into_iter()
+        .map(|b| (b.id, b))
+        .collect();
+
+    let platforms = state.get_platforms().await;
+
+    let separator = if csv { "," } else { "\t" };
+    let field = |value: &str| {
+        if csv && value.contains([',', '"', '\n']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    };
+
+    let mut report = [
+        "session_started",
+        "completed_at",
+        "profile",
+        "platform",
+        "account",
+        "link",
+        "comment",
+    ]
+    .join(separator);
+    report.push('\n');
+
+    for session in &sessions {
+        for step in steps.iter().filter(|s| s.session_id == session.id) {
+            let briefcase = briefcases.get(&step.briefcase_id);
+            let row = [
+                session.started_at.to_rfc3339(),
+                step.completed_at.to_rfc3339(),
+                profiles
+                    .get(&step.profile_id)
+                    .cloned()
+                    .unwrap_or_else(|| step.profile_id.to_string()),
+                briefcase
+                    .map(|b| platforms.name(&b.social_media))
+                    .unwrap_or_default(),
+                briefcase
+                    .map(|b| b.user_name.clone())
+                    .unwrap_or_else(|| step.briefcase_id.to_string()),
+                step.link.clone(),
+                step.comment.clone().unwrap_or_default(),
+            ];
+            let row: Vec<String> = row.iter().map(|value| field(value)).collect();
+            report.push_str(&row.join(separator));
+            report.push('\n');
+        }
+    }
+
+    Ok(report)
+}
@@ -1,8 +1,29 @@
-use crate::models::{BriefCase, Profile, SocialMedia, Task};
+use crate::audit::{self, AuditEntity, AuditEntry, AuditSource, Revert};
+use crate::automation::{self, ExecutionResult, SessionTime};
+use crate::backup::{self, BackupManifest, ImportMode};
+use crate::data;
+use crate::events::{self, CommentCopied, WindowLost};
+use crate::execution::PlanPreview;
+use crate::layout;
+use crate::logging::{self, log_error, LogEntry};
+use crate::login_check::{self, LoginCheckProgress};
+use crate::models::{
+    BriefCase, BriefCaseGroup, LoginStatus, LoginVerification, Profile, SocialMedia, Task,
+};
+use crate::platforms::Platform;
+use crate::profile_data::{self, OrphanProfileDir, ProfileDiskUsage};
+use crate::security::SecurityStatus;
+use crate::settings::Settings;
+use crate::shortcut_bindings::ShortcutBindings;
+use crate::shortcuts;
 use crate::state::AppState;
-use crate::storage::Storage;
+use crate::storage::DataFileStats;
+use crate::window_layout::{MonitorInfo, WindowLayout};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_clipboard_manager::ClipboardExt;
 use uuid::Uuid;
 
 /// Size configuration for profile windows
@@ -11,27 +32,10 @@ use uuid::Uuid;
 pub enum WindowSize {
     /// Full screen size
     Full,
-    /// Partial size (2/3 of screen width)
+    /// Partial size (the profile side of the window layout)
     Partial,
 }
 
-/// Result structure for task execution operations
-#[derive(Debug, Clone, Serialize)]
-pub struct ExecutionResult {
-    /// Whether the task was completed successfully
-    pub completed: bool,
-    /// ID of the profile to use for this execution
-    pub profile_id: Uuid,
-    /// URL to load in the browser
-    pub link: String,
-    /// Whether to switch to a new profile
-    pub should_change_profile: bool,
-    /// Index of the current task
-    pub task_index: usize,
-    /// Current comment to post
-    pub comment: String,
-}
-
 /// Data sent to the panel UI for display
 #[derive(Debug, Clone, Serialize)]
 pub struct PanelData {
@@ -51,6 +55,76 @@ pub struct PanelData {
     pub task_progress: Vec<(usize, usize, usize)>,
     /// Current comment to post
     pub current_comment: Option<String>,
+    /// Index of the current comment in the current task's comments
+    pub current_comment_index: Option<usize>,
+    /// Whether the user closed the active profile window
+    pub window_lost: bool,
+}
+
+/// Everything needed to diagnose a problem report
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsBundle {
+    /// Version of the application
+    pub app_version: String,
+    /// Operating system the app runs on
+    pub os: String,
+    /// CPU architecture the app runs on
+    pub arch: String,
+    /// Name of the storage backend in use
+    pub storage_backend: String,
+    /// Size and record counts of the persisted data files
+    pub data_files: Vec<DataFileStats>,
+    /// The most recent warnings and errors
+    pub recent_logs: Vec<LogEntry>,
+}
+
+/// Replaces all profiles through the data service and notifies the frontend
+async fn commit_profiles(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+    profiles: Vec<Profile>,
+    source: AuditSource,
+) -> Result<(), String> {
+    data::save_profiles(state, profiles, source).await?;
+
+    app.emit("profiles-changed", ())
+        .map_err(log_error("Failed to emit event"))?;
+
+    Ok(())
+}
+
+/// Replaces all BriefCases through the data service and notifies the frontend
+async fn commit_briefcases(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+    briefcases: Vec<BriefCase>,
+    source: AuditSource,
+) -> Result<(), String> {
+    let pruned = data::save_briefcases(state, briefcases, source).await?;
+
+    app.emit("briefcases-changed", ())
+        .map_err(log_error("Failed to emit event"))?;
+    if pruned {
+        app.emit("groups-changed", ())
+            .map_err(log_error("Failed to emit event"))?;
+    }
+
+    Ok(())
+}
+
+/// Replaces all groups through the data service and notifies the frontend
+async fn commit_groups(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+    groups: Vec<BriefCaseGroup>,
+    source: AuditSource,
+) -> Result<(), String> {
+    data::save_groups(state, groups, source).await?;
+
+    app.emit("groups-changed", ())
+        .map_err(log_error("Failed to emit event"))?;
+
+    Ok(())
 }
 
 /// ==================== Profile Commands ====================
@@ -61,21 +135,26 @@ pub async fn create_profile(
     app: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<Profile, String> {
-    let profile = Profile::new(profile_name, &app)
-        .await
-        .map_err(|e| format!("Failed to create profile: {}", e))?;
-
-    state.add_profile(profile.clone()).await;
-
-    // Save to disk
-    let profiles = state.get_profiles().await;
-    Storage::write_profiles(&app, &profiles)
+    let mut profile = Profile::new(profile_name, state.app_data_dir())
         .await
-        .map_err(|e| format!("Failed to save profiles: {}", e))?;
-
-    // Notify frontend
-    app.emit("profiles-changed", ())
-        .map_err(|e| format!("Failed to emit event: {}", e))?;
+        .map_err(log_error("Failed to create profile"))?;
+
+    log::info!(
+        "Created profile {} ({})",
+        profile.profile_name,
+        profile.profile_id
+    );
+
+    let mut profiles = state.get_profiles().await;
+    profile.sort_order = profiles.len() as u32;
+    profiles.push(profile.clone());
+    commit_profiles(
+        &app,
+        &state,
+        profiles,
+        AuditSource::command("create_profile"),
+    )
+    .await?;
 
     Ok(profile)
 }
@@ -93,16 +172,277 @@ pub async fn save_profiles(
     app: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    state.set_profiles(profiles.clone()).await;
+    commit_profiles(
+        &app,
+        &state,
+        profiles,
+        AuditSource::command("save_profiles"),
+    )
+    .await
+}
+
+/// Updates the name and metadata of a single profile
+///
+/// # Arguments
+/// * `profile_id` - The profile to update
+/// * `profile_name` - New display name
+/// * `notes` - Free-form notes, None to clear them
+/// * `color` - Display color as `#rrggbb`, None to clear it
+/// * `tags` - Labels replacing the current ones
+#[tauri::command]
+pub async fn update_profile_details(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    profile_id: Uuid,
+    profile_name: String,
+    notes: Option<String>,
+    color: Option<String>,
+    tags: Vec<String>,
+) -> Result<Profile, String> {
+    let mut profiles = state.get_profiles().await;
+    let profile = profiles
+        .iter_mut()
+        .find(|p| p.profile_id == profile_id)
+        .ok_or("Profile not found")?;
+
+    profile.profile_name = profile_name.trim().to_string();
+    profile.notes = notes.filter(|notes| !notes.trim().is_empty());
+    profile.color = color;
+    profile.tags.clear();
+    for tag in tags
+        .iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !tag.is_empty())
+    {
+        if !profile.tags.iter().any(|t| t == tag) {
+            profile.tags.push(tag.to_string());
+        }
+    }
+    let profile = profile.clone();
+
+    commit_profiles(
+        &app,
+        &state,
+        profiles,
+        AuditSource::command("update_profile_details"),
+    )
+    .await?;
+
+    Ok(profile)
+}
+
+/// Changes the user-defined profile order used for display and execution
+///
+/// # Arguments
+/// * `profile_ids` - Profile IDs in their new order; unlisted profiles move to the end
+#[tauri::command]
+pub async fn reorder_profiles(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    profile_ids: Vec<Uuid>,
+) -> Result<(), String> {
+    let mut profiles = state.get_profiles().await;
+
+    for profile in &mut profiles {
+        profile.sort_order = profile_ids
+            .iter()
+            .position(|id| *id == profile.profile_id)
+            .map_or(u32::MAX, |position| position as u32);
+    }
+
+    commit_profiles(
+        &app,
+        &state,
+        profiles,
+        AuditSource::command("reorder_profiles"),
+    )
+    .await
+}
+
+/// Creates a new profile from a copy of an existing one
+///
+/// # Arguments
+/// * `source_profile_id` - The profile to copy, its window must be closed
+/// * `profile_name` - Display name of the new profile
+/// * `include_session_data` - Whether cookies and site storage are copied, keeping logins
+/// * `copy_briefcases` - Whether the source's briefcases are duplicated onto the new profile
+#[tauri::command]
+pub async fn clone_profile(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    source_profile_id: Uuid,
+    profile_name: String,
+    include_session_data: bool,
+    copy_briefcases: bool,
+) -> Result<Profile, String> {
+    let source_dir = closed_profile_dir(&app, &state, source_profile_id).await?;
+    let source_profile = state
+        .get_profile_by_id(source_profile_id)
+        .await
+        .ok_or("Profile not found")?;
 
-    Storage::write_profiles(&app, &profiles)
+    let mut profile = Profile::new(profile_name, state.app_data_dir())
         .await
-        .map_err(|e| format!("Failed to save profiles: {}", e))?;
+        .map_err(log_error("Failed to create profile"))?;
+    let target_dir = profile.get_data_path(state.app_data_dir());
 
-    app.emit("profiles-changed", ())
-        .map_err(|e| format!("Failed to emit event: {}", e))?;
+    if let Err(e) = profile_data::copy(source_dir, target_dir.clone(), include_session_data).await {
+        let _ = tokio::fs::remove_dir_all(&target_dir).await;
+        return Err(log_error("Failed to clone profile")(e));
+    }
 
-    Ok(())
+    log::info!(
+        "Cloned profile {} into {} ({})",
+        source_profile_id,
+        profile.profile_name,
+        profile.profile_id
+    );
+
+    let source = AuditSource::command("clone_profile");
+    let mut profiles = state.get_profiles().await;
+    profile.notes = source_profile.notes;
+    profile.color = source_profile.color;
+    profile.tags = source_profile.tags;
+    profile.sort_order = profiles.len() as u32;
+    profiles.push(profile.clone());
+    commit_profiles(&app, &state, profiles, source).await?;
+
+    if copy_briefcases {
+        let mut briefcases = state.get_brief_cases().await;
+        let copies: Vec<BriefCase> = briefcases
+            .iter()
+            .filter(|b| b.profile_id == source_profile_id)
+            .map(|b| {
+                BriefCase::new(
+                    b.social_media.clone(),
+                    profile.profile_id,
+                    b.user_name.clone(),
+                )
+            })
+            .collect();
+
+        if !copies.is_empty() {
+            briefcases.extend(copies);
+            commit_briefcases(&app, &state, briefcases, source).await?;
+        }
+    }
+
+    Ok(profile)
+}
+
+/// ==================== Profile Data Commands ====================
+/// Looks up a profile and its data directory, refusing while its window is open
+///
+/// Webviews lock their data files, so the directory can only be changed once the
+/// profile window is closed.
+async fn closed_profile_dir(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+    profile_id: Uuid,
+) -> Result<PathBuf, String> {
+    let profile = state
+        .get_profile_by_id(profile_id)
+        .await
+        .ok_or("Profile not found")?;
+
+    if app
+        .get_webview_window(&format!("profile-{}", profile_id))
+        .is_some()
+    {
+        return Err("Close the profile window before changing its data".to_string());
+    }
+
+    Ok(profile.get_data_path(state.app_data_dir()))
+}
+
+/// Reports the disk space used by every profile's data directory
+#[tauri::command]
+pub async fn get_profile_disk_usage(
+    state: State<'_, AppState>,
+) -> Result<Vec<ProfileDiskUsage>, String> {
+    let mut usage = Vec::new();
+
+    for profile in state.get_profiles().await {
+        let path = profile.get_data_path(state.app_data_dir());
+        usage.push(profile_data::disk_usage(profile.profile_id, path).await?);
+    }
+
+    Ok(usage)
+}
+
+/// Clears a profile's webview caches while keeping its logins
+///
+/// # Returns
+/// The number of bytes freed
+#[tauri::command]
+pub async fn clear_profile_cache(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    profile_id: Uuid,
+) -> Result<u64, String> {
+    let path = closed_profile_dir(&app, &state, profile_id).await?;
+    let freed = profile_data::clear_cache(path)
+        .await
+        .map_err(log_error("Failed to clear profile cache"))?;
+
+    log::info!(
+        "Cleared {} bytes of cache from profile {}",
+        freed,
+        profile_id
+    );
+    Ok(freed)
+}
+
+/// Deletes all data of a profile, logging out every account on it
+///
+/// # Returns
+/// The number of bytes freed
+#[tauri::command]
+pub async fn wipe_profile_data(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    profile_id: Uuid,
+) -> Result<u64, String> {
+    let path = closed_profile_dir(&app, &state, profile_id).await?;
+    let freed = profile_data::wipe(path)
+        .await
+        .map_err(log_error("Failed to wipe profile data"))?;
+
+    log::warn!("Wiped {} bytes of data from profile {}", freed, profile_id);
+    Ok(freed)
+}
+
+/// Finds profile data directories whose profile no longer exists
+#[tauri::command]
+pub async fn find_orphan_profile_dirs(
+    state: State<'_, AppState>,
+) -> Result<Vec<OrphanProfileDir>, String> {
+    let known = state
+        .get_profiles()
+        .await
+        .iter()
+        .map(|p| p.profile_id)
+        .collect();
+
+    profile_data::find_orphans(profile_data::profiles_root(state.app_data_dir()), known).await
+}
+
+/// Deletes all profile data directories whose profile no longer exists
+///
+/// # Returns
+/// The number of bytes freed
+#[tauri::command]
+pub async fn delete_orphan_profile_dirs(state: State<'_, AppState>) -> Result<u64, String> {
+    let known = state
+        .get_profiles()
+        .await
+        .iter()
+        .map(|p| p.profile_id)
+        .collect();
+
+    profile_data::delete_orphans(profile_data::profiles_root(state.app_data_dir()), known)
+        .await
+        .map_err(log_error("Failed to delete orphan profile directories"))
 }
 
 /// ==================== BriefCase Commands ====================
@@ -115,19 +455,27 @@ pub async fn create_brief_case(
     app: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let brief_case = BriefCase::new(social_media, profile_id, user_name);
-
-    state.add_brief_case(brief_case).await;
-
-    let briefcases = state.get_brief_cases().await;
-    Storage::write_briefcases(&app, &briefcases)
-        .await
-        .map_err(|e| format!("Failed to save briefcases: {}", e))?;
-
-    app.emit("briefcases-changed", ())
-        .map_err(|e| format!("Failed to emit event: {}", e))?;
+    if state.get_platforms().await.get(&social_media).is_none() {
+        return Err(format!("Unknown platform {}", social_media));
+    }
 
-    Ok(())
+    let brief_case = BriefCase::new(social_media, profile_id, user_name);
+    log::info!(
+        "Created {} briefcase {} on profile {}",
+        brief_case.social_media,
+        brief_case.id,
+        profile_id
+    );
+
+    let mut briefcases = state.get_brief_cases().await;
+    briefcases.push(brief_case);
+    commit_briefcases(
+        &app,
+        &state,
+        briefcases,
+        AuditSource::command("create_brief_case"),
+    )
+    .await
 }
 
 /// Loads all BriefCases from the state
@@ -143,16 +491,142 @@ pub async fn save_briefcases(
     app: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    state.set_brief_cases(briefcases.clone()).await;
+    commit_briefcases(
+        &app,
+        &state,
+        briefcases,
+        AuditSource::command("save_briefcases"),
+    )
+    .await
+}
 
-    Storage::write_briefcases(&app, &briefcases)
-        .await
-        .map_err(|e| format!("Failed to save briefcases: {}", e))?;
+/// Enables, disables or pauses a BriefCase for automation
+///
+/// # Arguments
+/// * `briefcase_id` - The account to update
+/// * `is_active` - Whether the account takes part in automation
+/// * `paused_until` - Leave the account out until this time, None to resume it
+#[tauri::command]
+pub async fn set_briefcase_availability(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    briefcase_id: Uuid,
+    is_active: bool,
+    paused_until: Option<DateTime<Utc>>,
+) -> Result<(), String> {
+    let mut briefcases = state.get_brief_cases().await;
+    let briefcase = briefcases
+        .iter_mut()
+        .find(|b| b.id == briefcase_id)
+        .ok_or("Briefcase not found")?;
+
+    briefcase.is_active = is_active;
+    briefcase.paused_until = paused_until;
+
+    commit_briefcases(
+        &app,
+        &state,
+        briefcases,
+        AuditSource::command("set_briefcase_availability"),
+    )
+    .await
+}
 
-    app.emit("briefcases-changed", ())
-        .map_err(|e| format!("Failed to emit event: {}", e))?;
+/// ==================== Platform Commands ====================
+/// Lists the platforms tasks and accounts can be created for
+///
+/// Built-in platforms come first, followed by those added in `platforms.json`.
+#[tauri::command]
+pub async fn list_platforms(state: State<'_, AppState>) -> Result<Vec<Platform>, String> {
+    Ok(state.get_platforms().await.all().to_vec())
+}
 
-    Ok(())
+/// Finds the platform a link points to
+///
+/// # Returns
+/// The platform, None if the link belongs to no known platform
+#[tauri::command]
+pub async fn detect_platform(
+    state: State<'_, AppState>,
+    link: String,
+) -> Result<Option<Platform>, String> {
+    Ok(state.get_platforms().await.detect(&link).cloned())
+}
+
+/// ==================== Group Commands ====================
+/// Loads all briefcase groups from the state
+#[tauri::command]
+pub async fn load_groups(state: State<'_, AppState>) -> Result<Vec<BriefCaseGroup>, String> {
+    Ok(state.get_groups().await)
+}
+
+/// Creates a new briefcase group and saves it to disk
+///
+/// # Arguments
+/// * `name` - Display name of the group
+/// * `briefcase_ids` - The group's members, all must exist
+#[tauri::command]
+pub async fn create_group(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: String,
+    briefcase_ids: Vec<Uuid>,
+) -> Result<BriefCaseGroup, String> {
+    let group = BriefCaseGroup::new(name.trim().to_string(), briefcase_ids);
+    log::info!("Created group {} ({})", group.name, group.id);
+
+    let mut groups = state.get_groups().await;
+    groups.push(group.clone());
+    commit_groups(&app, &state, groups, AuditSource::command("create_group")).await?;
+
+    Ok(group)
+}
+
+/// Renames a briefcase group and replaces its members
+///
+/// # Arguments
+/// * `group_id` - The group to update
+/// * `name` - New display name
+/// * `briefcase_ids` - The group's new members, all must exist
+#[tauri::command]
+pub async fn update_group(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    group_id: Uuid,
+    name: String,
+    briefcase_ids: Vec<Uuid>,
+) -> Result<BriefCaseGroup, String> {
+    let mut groups = state.get_groups().await;
+    let group = groups
+        .iter_mut()
+        .find(|g| g.id == group_id)
+        .ok_or("Group not found")?;
+
+    group.name = name.trim().to_string();
+    group.briefcase_ids = briefcase_ids;
+    let group = group.clone();
+
+    commit_groups(&app, &state, groups, AuditSource::command("update_group")).await?;
+
+    Ok(group)
+}
+
+/// Deletes a briefcase group, leaving its briefcases untouched
+#[tauri::command]
+pub async fn delete_group(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    group_id: Uuid,
+) -> Result<(), String> {
+    let mut groups = state.get_groups().await;
+    let count = groups.len();
+    groups.retain(|g| g.id != group_id);
+
+    if groups.len() == count {
+        return Err("Group not found".to_string());
+    }
+
+    commit_groups(&app, &state, groups, AuditSource::command("delete_group")).await
 }
 
 /// ==================== Batch Data Commands ====================
@@ -164,24 +638,9 @@ pub async fn save_all_data(
     app: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    state.set_profiles(profiles.clone()).await;
-    state.set_brief_cases(briefcases.clone()).await;
-
-    Storage::write_profiles(&app, &profiles)
-        .await
-        .map_err(|e| format!("Failed to save profiles: {}", e))?;
-
-    Storage::write_briefcases(&app, &briefcases)
-        .await
-        .map_err(|e| format!("Failed to save briefcases: {}", e))?;
-
-    app.emit("profiles-changed", ())
-        .map_err(|e| format!("Failed to emit event: {}", e))?;
-
-    app.emit("briefcases-changed", ())
-        .map_err(|e| format!("Failed to emit event: {}", e))?;
-
-    Ok(())
+    let source = AuditSource::command("save_all_data");
+    commit_profiles(&app, &state, profiles, source).await?;
+    commit_briefcases(&app, &state, briefcases, source).await
 }
 
 /// ==================== Window Management Commands ====================
@@ -190,70 +649,48 @@ pub async fn save_all_data(
 #[tauri::command]
 pub async fn create_window_sized(
     app_handle: AppHandle,
+    state: State<'_, AppState>,
     profile: Profile,
     url: &str,
     size_type: WindowSize,
     close_previous_window: bool,
 ) -> Result<String, String> {
-    let data_dir = profile
-        .get_data_path(&app_handle)
-        .ok_or("Failed to get profile data path")?;
+    if state.is_locked().await {
+        return Err("Unlock the app before opening a profile window".to_string());
+    }
+
+    let data_dir = profile.get_data_path(state.app_data_dir());
 
     let window_label = format!("profile-{}", profile.profile_id);
 
     if close_previous_window {
-        // Close all profile windows first
-        for (_, window) in app_handle.webview_windows() {
-            let label = window.label();
+        // Close all profile windows first, without reporting them as lost
+        for (label, window) in app_handle.webview_windows() {
             if label.starts_with("profile-") {
+                state.mark_window_closing(&label).await;
                 let _ = window.close();
             }
         }
     }
 
-    // Get monitor dimensions for window sizing
-    let monitor = app_handle
-        .primary_monitor()
-        .map_err(|e| format!("Failed to get monitor: {}", e))?
-        .ok_or("No monitor available")?;
-
-    let screen_width = monitor.size().width as f64 / monitor.scale_factor();
-    let screen_height = monitor.size().height as f64 / monitor.scale_factor();
-
-    // Profile window takes up 2/3 of the screen
-    let webview_width = (screen_width * 0.66).floor();
-    let webview_height = screen_height;
-    let x = 0.0;
-    let y = 0.0;
-
-    let webview =
-        WebviewWindowBuilder::new(&app_handle, &window_label, WebviewUrl::App(url.into()))
-            .title(&format!("Browser - {}", profile.profile_name));
+    // Full windows cover the configured monitor, partial ones take the profile side of the layout
+    let layout = state.get_layout().await;
+    let area = layout::monitor_area(&app_handle, &layout)?;
+    let rect = match size_type {
+        WindowSize::Full => area,
+        WindowSize::Partial => layout.tile(area).0,
+    };
 
-    match size_type {
-        WindowSize::Full => {
-            webview
-                .inner_size(
-                    monitor.size().width as f64 / monitor.scale_factor(),
-                    monitor.size().height as f64 / monitor.scale_factor(),
-                )
-                .data_directory(data_dir)
-                .position(0.0, 0.0)
-                .resizable(true)
-                .build()
-                .map_err(|e| format!("Failed to build window: {}", e))?;
-        }
+    log::debug!("Opening {} ({:?})", window_label, size_type);
 
-        WindowSize::Partial => {
-            webview
-                .inner_size(webview_width, webview_height)
-                .position(x, y)
-                .data_directory(data_dir)
-                .resizable(true)
-                .build()
-                .map_err(|e| format!("Failed to build window: {}", e))?;
-        }
-    }
+    WebviewWindowBuilder::new(&app_handle, &window_label, WebviewUrl::App(url.into()))
+        .title(format!("Browser - {}", profile.profile_name))
+        .inner_size(rect.width, rect.height)
+        .position(rect.x, rect.y)
+        .data_directory(data_dir)
+        .resizable(true)
+        .build()
+        .map_err(log_error("Failed to build window"))?;
 
     Ok(window_label)
 }
@@ -261,15 +698,28 @@ pub async fn create_window_sized(
 #[tauri::command]
 pub async fn launch_profile_window(
     app_handle: AppHandle,
+    state: State<'_, AppState>,
     profile: Profile,
     url: String,
 ) -> Result<String, String> {
-    Ok(create_window_sized(app_handle, profile, url.as_str(), WindowSize::Partial, true).await?)
+    let close_previous_window = state.get_settings().await.close_previous_profile_window;
+    create_window_sized(
+        app_handle,
+        state,
+        profile,
+        url.as_str(),
+        WindowSize::Partial,
+        close_previous_window,
+    )
+    .await
 }
 
 /// Launches the control panel window
 #[tauri::command]
-pub async fn launch_panel_window(app_handle: AppHandle) -> Result<(), String> {
+pub async fn launch_panel_window(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
     // Return early if panel is already open
     if let Some(existing_panel) = app_handle.get_webview_window("panel") {
         let _ = existing_panel.show();
@@ -277,37 +727,57 @@ pub async fn launch_panel_window(app_handle: AppHandle) -> Result<(), String> {
         return Ok(());
     }
 
-    // Get monitor dimensions
-    let monitor = app_handle
-        .primary_monitor()
-        .map_err(|e| format!("Failed to get monitor: {}", e))?
-        .ok_or("No monitor available")?;
-
-    let screen_width = monitor.size().width as f64 / monitor.scale_factor();
-    let screen_height = monitor.size().height as f64 / monitor.scale_factor();
-
-    // Panel takes up remaining 1/3 of screen
-    let profile_width = (screen_width * 0.66).floor();
-    let panel_width = screen_width - profile_width;
-    let panel_height = screen_height;
-    let x = profile_width;
-    let y = 0.0;
+    // Panel takes the side of the screen left over by the profile window
+    let layout = state.get_layout().await;
+    let (_, rect) = layout.tile(layout::monitor_area(&app_handle, &layout)?);
 
     let panel = WebviewWindowBuilder::new(&app_handle, "panel", WebviewUrl::App("/panel".into()));
 
     panel
         .title("Control Panel")
-        .inner_size(panel_width, panel_height)
-        .position(x, y)
+        .inner_size(rect.width, rect.height)
+        .position(rect.x, rect.y)
         .resizable(true)
         .decorations(true)
-        .always_on_top(false)
+        .always_on_top(layout.panel_always_on_top)
         .build()
-        .map_err(|e| format!("Failed to build panel: {}", e))?;
+        .map_err(log_error("Failed to build panel"))?;
 
     Ok(())
 }
 
+/// Gets the persisted window layout
+#[tauri::command]
+pub async fn get_window_layout(state: State<'_, AppState>) -> Result<WindowLayout, String> {
+    Ok(state.get_layout().await)
+}
+
+/// Saves the window layout and re-tiles the open panel and profile window
+#[tauri::command]
+pub async fn set_window_layout(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    layout: WindowLayout,
+) -> Result<(), String> {
+    let settings = Settings {
+        layout,
+        ..state.get_settings().await
+    };
+    apply_settings(
+        &app,
+        &state,
+        settings,
+        AuditSource::command("set_window_layout"),
+    )
+    .await
+}
+
+/// Lists the connected monitors the layout can be placed on
+#[tauri::command]
+pub async fn list_monitors(app: AppHandle) -> Result<Vec<MonitorInfo>, String> {
+    layout::list_monitors(&app)
+}
+
 /// Changes the URL of the currently active profile window
 #[tauri::command]
 pub async fn change_webview_url(
@@ -325,15 +795,67 @@ pub async fn change_webview_url(
         .ok_or_else(|| format!("Webview with label '{}' not found", label))?;
 
     webview
-        .eval(&format!("window.location.href = '{}'", url))
-        .map_err(|e| format!("Failed to change URL: {}", e))?;
+        .eval(format!("window.location.href = '{}'", url))
+        .map_err(log_error("Failed to change URL"))?;
 
     Ok(())
 }
 
+/// Reopens the profile window for the current step after the user closed it
+#[tauri::command]
+pub async fn restore_profile_window(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let step = state
+        .current_step()
+        .await
+        .ok_or("No active execution step")?;
+
+    let profile = state
+        .get_profile_by_id(step.profile_id)
+        .await
+        .ok_or_else(|| format!("Profile {} not found", step.profile_id))?;
+
+    let label = launch_profile_window(app.clone(), state.clone(), profile, step.link).await?;
+    state.set_current_window_label(label.clone()).await;
+    log::info!("Reopened profile window {}", label);
+
+    Ok(label)
+}
+
+/// Tracks a destroyed profile window and notifies the panel if it was the active one
+///
+/// Called from the window event handler, so the state update runs on the async runtime.
+pub fn handle_profile_window_destroyed(app: &AppHandle, label: &str) {
+    let app = app.clone();
+    let label = label.to_string();
+
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        if !state.mark_window_lost(&label).await {
+            return;
+        }
+        log::warn!("Profile window {} was closed during automation", label);
+
+        let profile_id = label
+            .strip_prefix("profile-")
+            .and_then(|id| Uuid::parse_str(id).ok());
+
+        if let Err(e) = events::emit(&app, events::WINDOW_LOST, WindowLost { label, profile_id }) {
+            log::error!("{}", e);
+        }
+    });
+}
+
 /// Closes the workspace and all associated windows
 #[tauri::command]
 pub async fn close_workspace(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    log::info!("Closing workspace");
+
+    // Reset state first so closing the windows is not reported as a lost window
+    automation::close(&state).await?;
+
     // Close all profile windows
     for (_, window) in app.webview_windows() {
         let label = window.label();
@@ -347,14 +869,22 @@ pub async fn close_workspace(app: AppHandle, state: State<'_, AppState>) -> Resu
         let _ = panel.close();
     }
 
-    // Reset state
-    state.clear_current_window_label().await;
-    state.set_running(false).await;
-
     Ok(())
 }
 
 /// ==================== Automation Commands ====================
+/// Shows what automation would do with the given tasks without starting it
+///
+/// Reports the profiles in visiting order, the accounts left out because they
+/// are disabled or paused, and tasks no available account can post on.
+#[tauri::command]
+pub async fn preview_plan(
+    state: State<'_, AppState>,
+    tasks_json: String,
+) -> Result<PlanPreview, String> {
+    automation::preview(&state, &tasks_json).await
+}
+
 /// Starts the automation process with the given tasks
 #[tauri::command]
 pub async fn start_automation(
@@ -362,140 +892,624 @@ pub async fn start_automation(
     state: State<'_, AppState>,
     tasks_json: String,
 ) -> Result<ExecutionResult, String> {
-    let tasks: Vec<Task> =
-        serde_json::from_str(&tasks_json).map_err(|e| format!("Failed to parse tasks: {}", e))?;
+    let mut result = automation::start(
+        &state,
+        &tasks_json,
+        AuditSource::command("start_automation"),
+    )
+    .await?;
 
-    let briefcases = state.get_brief_cases().await;
-
-    if briefcases.is_empty() {
-        return Err(
-            "No briefcases found. Please add briefcases before starting automation.".to_string(),
-        );
+    if !result.completed {
+        // Launch panel and first profile window
+        launch_panel_window(app.clone(), state.clone()).await?;
+        present_step(&app, &state, &mut result, None).await?;
     }
 
-    // Create tasks with associated briefcases
-    let tasks_with_briefcases: Vec<Task> = tasks
-        .into_iter()
-        .map(|task| Task::new(task.link, task.comments, task.social_media, &briefcases))
-        .collect();
-
-    // Check if any tasks have matching briefcases
-    if tasks_with_briefcases
-        .iter()
-        .all(|t| t.related_brief_cases.is_empty())
-    {
-        return Err("No briefcases match the task platforms. Please add briefcases for the social media platforms you want to automate.".to_string());
-    }
+    Ok(result)
+}
 
-    // Set tasks and create execution plan
-    state.set_tasks(tasks_with_briefcases).await;
-    state.set_running(true).await;
+/// Executes the next step in the automation sequence
+#[tauri::command]
+pub async fn execute_next_step(
+    _app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ExecutionResult, String> {
+    automation::next_step(&state).await
+}
 
-    // Get first execution step
-    let result = execute_next_step(app.clone(), state.clone()).await?;
+/// Handles the "next" action from the user (arrow key or button)
+#[tauri::command]
+pub async fn next_execution(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ExecutionResult, String> {
+    automation::complete_current(&state).await?;
 
-    if !result.completed {
-        // Launch panel and first profile window
-        launch_panel_window(app.clone()).await?;
+    advance_execution(app, state).await
+}
 
-        let profile = state
-            .get_profile_by_id(result.profile_id)
-            .await
-            .ok_or_else(|| format!("Profile {} not found", result.profile_id))?;
+/// Moves to the next step without marking the current one as visited
+#[tauri::command]
+pub async fn skip_execution(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ExecutionResult, String> {
+    advance_execution(app, state).await
+}
 
-        let label = launch_profile_window(app.clone(), profile, result.link.clone()).await?;
-        state.set_current_window_label(label).await;
-    }
+/// Moves back to the previous step and loads it in the profile window
+#[tauri::command]
+pub async fn previous_execution(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ExecutionResult, String> {
+    let previous_profile_id = state.current_profile_id().await;
+
+    let mut result = automation::previous(&state).await?;
+
+    present_step(&app, &state, &mut result, previous_profile_id).await?;
 
     Ok(result)
 }
 
-/// Executes the next step in the automation sequence
+/// Jumps to any step of the plan and loads it in the profile window
+///
+/// # Arguments
+/// * `step_index` - Position of the step in the execution order
 #[tauri::command]
-pub async fn execute_next_step(
-    _app: AppHandle,
+pub async fn goto_step(
+    app: AppHandle,
     state: State<'_, AppState>,
+    step_index: usize,
 ) -> Result<ExecutionResult, String> {
-    if state.is_complete().await {
-        return Ok(ExecutionResult {
-            completed: true,
-            profile_id: Uuid::nil(),
-            link: String::new(),
-            should_change_profile: false,
-            task_index: 0,
-            comment: String::new(),
-        });
-    }
+    let previous_profile_id = state.current_profile_id().await;
 
-    // Check if we need to change profile
-    let should_change = state.should_change_profile().await;
+    let mut result = automation::goto_step(&state, step_index).await?;
 
-    // Get the next execution step (this advances the plan)
-    let step = state
-        .next_execution_step()
-        .await
-        .ok_or("No more execution steps")?;
+    present_step(&app, &state, &mut result, previous_profile_id).await?;
 
-    // Get profile info
-    let profile_id = step.profile_id;
-    let task_index = step.task_index;
-    let link = step.link;
-    let comment = state
-        .get_current_comment(task_index)
-        .await
-        .unwrap_or_default();
+    Ok(result)
+}
+
+/// Jumps to a task's step and loads it in the profile window
+///
+/// # Arguments
+/// * `task_index` - Index of the task
+/// * `briefcase_id` - The BriefCase to post with, None for the task's next unvisited step
+#[tauri::command]
+pub async fn goto_task(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_index: usize,
+    briefcase_id: Option<Uuid>,
+) -> Result<ExecutionResult, String> {
+    let previous_profile_id = state.current_profile_id().await;
 
-    Ok(ExecutionResult {
-        completed: false,
-        profile_id,
-        link,
-        should_change_profile: should_change,
+    let mut result = automation::goto_task(&state, task_index, briefcase_id).await?;
+
+    present_step(&app, &state, &mut result, previous_profile_id).await?;
+
+    Ok(result)
+}
+
+/// Adds a task to the running session, keeping completed steps and the current one
+///
+/// # Returns
+/// The index of the new task
+#[tauri::command]
+pub async fn add_task_to_session(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task: Task,
+) -> Result<usize, String> {
+    let task_index =
+        automation::add_task(&state, task, AuditSource::command("add_task_to_session")).await?;
+
+    events::emit_progress_updated(&app, &state).await?;
+    Ok(task_index)
+}
+
+/// Removes a task from the running session, keeping completed steps
+///
+/// If the current step belonged to the task, the next step is loaded in the
+/// profile window.
+///
+/// # Arguments
+/// * `task_index` - Index of the task to remove
+///
+/// # Returns
+/// The new current step if the plan moved, None otherwise
+#[tauri::command]
+pub async fn remove_task_from_session(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    task_index: usize,
+) -> Result<Option<ExecutionResult>, String> {
+    let previous_profile_id = state.current_profile_id().await;
+
+    let mut result = automation::remove_task(
+        &state,
         task_index,
-        comment,
-    })
+        AuditSource::command("remove_task_from_session"),
+    )
+    .await?;
+
+    match &mut result {
+        Some(result) if result.completed => events::emit_session_completed(&app, &state).await?,
+        Some(result) => present_step(&app, &state, result, previous_profile_id).await?,
+        // Task indices after the removed one have shifted
+        None => {
+            events::emit_progress_updated(&app, &state).await?;
+            events::emit_step_changed(&app, &state).await?;
+        }
+    }
+
+    Ok(result)
 }
 
-/// Handles the "next" action from the user (arrow key or button)
+/// Replaces the comments of a task in the running session
+///
+/// # Arguments
+/// * `task_index` - Index of the task
+/// * `comments` - The new comments
 #[tauri::command]
-pub async fn next_execution(
+pub async fn edit_task_comments(
     app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<ExecutionResult, String> {
-    // Get current step before advancing
-    let current_step = state.current_step().await;
-
-    // Mark current briefcase as visited and increment comment index
-    if let Some(step) = current_step {
-        if let Some(task) = state.get_task(step.task_index).await {
-            if let Some(briefcase) = task.related_brief_cases.get(step.briefcase_index) {
-                state.mark_briefcase_visited(briefcase.id).await;
+    task_index: usize,
+    comments: Vec<String>,
+) -> Result<(), String> {
+    automation::edit_task_comments(
+        &state,
+        task_index,
+        comments,
+        AuditSource::command("edit_task_comments"),
+    )
+    .await?;
+
+    // Keep the panel and clipboard in sync when the current task changed
+    if state.current_task_index().await == Some(task_index) {
+        events::emit_step_changed(&app, &state).await?;
+
+        if state.auto_copy_comment().await {
+            if let Some(comment) = state.current_comment().await {
+                copy_comment_to_clipboard(&app, task_index, &comment)?;
             }
-            state.increment_comment_index(step.task_index).await;
         }
     }
 
-    // Execute next step
-    let result = execute_next_step(app.clone(), state.clone()).await?;
+    Ok(())
+}
+
+/// Pauses automation, freezing the plan at its current step
+///
+/// Hides the profile window if `hide_window_on_pause` is set. Navigation
+/// commands are refused until automation is resumed.
+///
+/// # Returns
+/// The time spent on the session so far
+///
+/// # Errors
+/// Returns an error if automation is not running or already paused
+#[tauri::command]
+pub async fn pause_automation(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<SessionTime, String> {
+    let time = automation::pause(&state).await?;
+
+    if state.get_settings().await.hide_window_on_pause {
+        if let Some(window) = current_profile_window(&app, &state).await {
+            window.hide().map_err(log_error("Failed to hide window"))?;
+        }
+    }
+
+    events::emit(&app, events::AUTOMATION_PAUSED, time.clone())?;
+    Ok(time)
+}
+
+/// Resumes paused automation and brings back the profile window
+///
+/// # Returns
+/// The time spent on the session so far
+///
+/// # Errors
+/// Returns an error if automation is not running or not paused
+#[tauri::command]
+pub async fn resume_automation(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<SessionTime, String> {
+    let time = automation::resume(&state).await?;
+
+    if let Some(window) = current_profile_window(&app, &state).await {
+        window.show().map_err(log_error("Failed to show window"))?;
+        let _ = window.set_focus();
+    }
+
+    events::emit(&app, events::AUTOMATION_RESUMED, time.clone())?;
+    Ok(time)
+}
+
+/// Gets the active and paused time of the running session
+///
+/// # Returns
+/// The time spent on the session, None if automation is not running
+#[tauri::command]
+pub async fn get_session_time(state: State<'_, AppState>) -> Result<Option<SessionTime>, String> {
+    Ok(automation::session_time(&state).await)
+}
+
+/// Finds the open profile window of the current step
+async fn current_profile_window(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+) -> Option<tauri::WebviewWindow> {
+    let label = state.get_current_window_label().await?;
+    app.get_webview_window(&label)
+}
+
+/// Advances the plan and loads the new step in the profile window
+async fn advance_execution(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<ExecutionResult, String> {
+    let previous_profile_id = state.current_profile_id().await;
+
+    let mut result = automation::advance(&state).await?;
 
     if result.completed {
+        events::emit_session_completed(&app, &state).await?;
         return Ok(result);
     }
 
+    present_step(&app, &state, &mut result, previous_profile_id).await?;
+
+    Ok(result)
+}
+
+/// Loads a step in the profile window and notifies the frontend
+///
+/// Opens the step's profile window when the profile changes, otherwise
+/// navigates the current window to the step's link. The plan has already moved
+/// by then, so a comment that cannot be copied is reported as the result's
+/// warning instead of an error.
+async fn present_step(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+    result: &mut ExecutionResult,
+    previous_profile_id: Option<Uuid>,
+) -> Result<(), String> {
+    // Reopen the profile window if the user closed it
+    let window_open = match state.get_current_window_label().await {
+        Some(label) => app.get_webview_window(&label).is_some(),
+        None => false,
+    };
+
     // Either change profile or change URL
-    if result.should_change_profile {
+    if result.should_change_profile || !window_open {
         let profile = state
             .get_profile_by_id(result.profile_id)
             .await
             .ok_or_else(|| format!("Profile {} not found", result.profile_id))?;
 
-        let label = launch_profile_window(app.clone(), profile, result.link.clone()).await?;
+        let label =
+            launch_profile_window(app.clone(), state.clone(), profile, result.link.clone()).await?;
         state.set_current_window_label(label).await;
     } else {
-        let link = result.link.clone();
-        change_webview_url(app, state, link).await?;
+        change_webview_url(app.clone(), state.clone(), result.link.clone()).await?;
     }
 
-    Ok(result)
+    log::debug!(
+        "Presenting task {} on profile {}",
+        result.task_index,
+        result.profile_id
+    );
+    events::emit_plan_moved(app, state, previous_profile_id).await?;
+
+    if state.auto_copy_comment().await {
+        if let Err(error) = copy_comment_to_clipboard(app, result.task_index, &result.comment) {
+            result.warning = Some(error);
+        }
+    }
+
+    Ok(())
+}
+
+/// ==================== Login Check Commands ====================
+/// Starts checking logins and opens the first profile window at a platform home page
+///
+/// # Returns
+/// The first page to confirm the accounts on
+#[tauri::command]
+pub async fn start_login_check(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<LoginCheckProgress, String> {
+    let progress = login_check::start(&state).await?;
+    show_login_page(&app, &state, &progress).await?;
+    Ok(progress)
+}
+
+/// Gets the current page of the running login check
+///
+/// # Returns
+/// The page, None if no login check is running
+#[tauri::command]
+pub async fn get_login_check(
+    state: State<'_, AppState>,
+) -> Result<Option<LoginCheckProgress>, String> {
+    Ok(login_check::current(&state).await)
+}
+
+/// Moves the login check to the next platform home page
+///
+/// # Returns
+/// The next page, None once every page has been checked
+#[tauri::command]
+pub async fn next_login_check(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Option<LoginCheckProgress>, String> {
+    let progress = login_check::next(&state).await?;
+    if let Some(progress) = &progress {
+        show_login_page(&app, &state, progress).await?;
+    }
+    Ok(progress)
+}
+
+/// Ends the running login check, leaving the profile window open
+#[tauri::command]
+pub async fn end_login_check(state: State<'_, AppState>) -> Result<(), String> {
+    login_check::end(&state).await;
+    Ok(())
+}
+
+/// Records whether a BriefCase was found logged in
+///
+/// Only accounts on the page the login check currently shows can be recorded.
+///
+/// # Arguments
+/// * `briefcase_id` - The account that was checked
+/// * `status` - What the operator found
+#[tauri::command]
+pub async fn set_login_status(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    briefcase_id: Uuid,
+    status: LoginStatus,
+) -> Result<(), String> {
+    login_check::ensure_on_current_page(&state, briefcase_id).await?;
+
+    let mut briefcases = state.get_brief_cases().await;
+    let briefcase = briefcases
+        .iter_mut()
+        .find(|b| b.id == briefcase_id)
+        .ok_or("Briefcase not found")?;
+
+    briefcase.last_verified = Some(LoginVerification {
+        status,
+        verified_at: Utc::now(),
+    });
+    log::info!("Login of briefcase {} checked: {:?}", briefcase_id, status);
+
+    commit_briefcases(
+        &app,
+        &state,
+        briefcases,
+        AuditSource::command("set_login_status"),
+    )
+    .await
+}
+
+/// Opens a login check page in its profile window
+///
+/// Opens the profile's window when the profile changes, otherwise navigates
+/// the current window to the page.
+async fn show_login_page(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+    progress: &LoginCheckProgress,
+) -> Result<(), String> {
+    let window_open = current_profile_window(app, state).await.is_some();
+
+    if progress.should_change_profile || !window_open {
+        let profile = state
+            .get_profile_by_id(progress.stop.profile_id)
+            .await
+            .ok_or_else(|| format!("Profile {} not found", progress.stop.profile_id))?;
+
+        let label = launch_profile_window(
+            app.clone(),
+            state.clone(),
+            profile,
+            progress.stop.home_url.clone(),
+        )
+        .await?;
+        state.set_current_window_label(label).await;
+    } else {
+        change_webview_url(app.clone(), state.clone(), progress.stop.home_url.clone()).await?;
+    }
+
+    Ok(())
+}
+
+/// ==================== Shortcut Commands ====================
+/// Gets the global shortcut bindings
+#[tauri::command]
+pub async fn get_shortcuts(state: State<'_, AppState>) -> Result<ShortcutBindings, String> {
+    Ok(state.get_shortcuts().await)
+}
+
+/// Replaces the global shortcut bindings and re-registers them with the OS
+#[tauri::command]
+pub async fn set_shortcuts(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    shortcuts: ShortcutBindings,
+) -> Result<(), String> {
+    let settings = Settings {
+        shortcuts,
+        ..state.get_settings().await
+    };
+    apply_settings(
+        &app,
+        &state,
+        settings,
+        AuditSource::command("set_shortcuts"),
+    )
+    .await
+}
+
+/// ==================== Settings Commands ====================
+/// Gets the application settings
+#[tauri::command]
+pub async fn get_settings(state: State<'_, AppState>) -> Result<Settings, String> {
+    Ok(state.get_settings().await)
+}
+
+/// Validates, applies and saves the application settings
+#[tauri::command]
+pub async fn update_settings(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    settings: Settings,
+) -> Result<(), String> {
+    apply_settings(
+        &app,
+        &state,
+        settings,
+        AuditSource::command("update_settings"),
+    )
+    .await
+}
+
+/// Validates new settings, applies what changed and saves them to disk
+///
+/// Shortcuts are re-registered and the windows re-tiled only when their part of
+/// the settings changed. Records the change in the audit log and emits
+/// `settings-changed` with the new settings.
+async fn apply_settings(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+    settings: Settings,
+    source: AuditSource,
+) -> Result<(), String> {
+    settings.validate()?;
+
+    let previous = state.get_settings().await;
+
+    let shortcuts_changed = settings.shortcuts != previous.shortcuts;
+    if shortcuts_changed {
+        shortcuts::register(app, Some(&previous.shortcuts), &settings.shortcuts)?;
+    }
+
+    state
+        .storage()
+        .write_settings(&settings)
+        .await
+        .map_err(log_error("Failed to save settings"))
+        .inspect_err(|_| {
+            if shortcuts_changed {
+                let _ = shortcuts::register(app, Some(&settings.shortcuts), &previous.shortcuts);
+            }
+        })?;
+
+    state.set_settings(settings.clone()).await;
+    log::info!("Settings updated");
+
+    audit::record(
+        state,
+        audit::update(source, AuditEntity::Settings, None, &previous, &settings)
+            .into_iter()
+            .collect(),
+    )
+    .await?;
+
+    if settings.layout != previous.layout {
+        let profile_label = state.get_current_window_label().await;
+        layout::retile(app, &settings.layout, profile_label.as_deref())?;
+    }
+
+    app.emit("settings-changed", settings)
+        .map_err(log_error("Failed to emit event"))?;
+
+    Ok(())
+}
+
+/// ==================== Clipboard Commands ====================
+/// Places a comment on the system clipboard and notifies the frontend
+///
+/// Empty comments are not copied so the operator's clipboard is left untouched
+/// for tasks without comments.
+fn copy_comment_to_clipboard(
+    app: &AppHandle,
+    task_index: usize,
+    comment: &str,
+) -> Result<(), String> {
+    if comment.is_empty() {
+        return Ok(());
+    }
+
+    app.clipboard()
+        .write_text(comment)
+        .map_err(log_error("Failed to copy comment"))?;
+
+    events::emit(
+        app,
+        events::COMMENT_COPIED,
+        CommentCopied {
+            task_index,
+            comment: comment.to_string(),
+        },
+    )
+}
+
+/// Copies the comment of the current step to the clipboard
+#[tauri::command]
+pub async fn copy_current_comment(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let task_index = state
+        .current_task_index()
+        .await
+        .ok_or("No active execution step")?;
+
+    let comment = state
+        .current_comment()
+        .await
+        .ok_or("Current task has no comments")?;
+
+    copy_comment_to_clipboard(&app, task_index, &comment)?;
+
+    Ok(comment)
+}
+
+/// Enables or disables copying the comment to the clipboard on every step
+#[tauri::command]
+pub async fn set_auto_copy_comment(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<(), String> {
+    let settings = Settings {
+        auto_copy_comment: enabled,
+        ..state.get_settings().await
+    };
+    apply_settings(
+        &app,
+        &state,
+        settings,
+        AuditSource::command("set_auto_copy_comment"),
+    )
+    .await
+}
+
+/// Returns whether the comment is copied to the clipboard on every step
+#[tauri::command]
+pub async fn get_auto_copy_comment(state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.auto_copy_comment().await)
 }
 
 /// ==================== Data Query Commands ====================
@@ -524,11 +1538,8 @@ pub async fn get_panel_data(state: State<'_, AppState>) -> Result<PanelData, Str
         None
     };
 
-    let current_comment = if let Some(idx) = current_task_index {
-        state.get_current_comment(idx).await
-    } else {
-        None
-    };
+    let current_comment = state.current_comment().await;
+    let current_comment_index = state.current_comment_index().await;
 
     Ok(PanelData {
         current_task,
@@ -539,22 +1550,358 @@ pub async fn get_panel_data(state: State<'_, AppState>) -> Result<PanelData, Str
         overall_progress,
         task_progress,
         current_comment,
+        current_comment_index,
+        window_lost: state.get_lost_window_label().await.is_some(),
     })
 }
 
 /// Sets the current comment index for a task
 #[tauri::command]
 pub async fn set_comment_index(
+    app: AppHandle,
     state: State<'_, AppState>,
     task_index: usize,
     comment_index: usize,
 ) -> Result<(), String> {
+    update_comment_index(
+        &app,
+        &state,
+        task_index,
+        comment_index,
+        AuditSource::command("set_comment_index"),
+    )
+    .await
+}
+
+/// Changes a task's comment index, records the change and refreshes the panel
+async fn update_comment_index(
+    app: &AppHandle,
+    state: &State<'_, AppState>,
+    task_index: usize,
+    comment_index: usize,
+    source: AuditSource,
+) -> Result<(), String> {
+    let previous = state.get_task(task_index).await;
     state
         .set_task_comment_index(task_index, comment_index)
         .await;
+
+    if let (Some(previous), Some(task)) = (previous, state.get_task(task_index).await) {
+        audit::record(state, audit::diff_tasks(source, &[previous], &[task])).await?;
+    }
+
+    // Keep the panel and clipboard in sync when the operator picks a different comment
+    if state.current_task_index().await == Some(task_index) {
+        events::emit_step_changed(app, state).await?;
+
+        if state.auto_copy_comment().await {
+            if let Some(comment) = state.current_comment().await {
+                copy_comment_to_clipboard(app, task_index, &comment)?;
+            }
+        }
+    }
+
     Ok(())
 }
 
+/// ==================== Audit Commands ====================
+/// Number of audit entries returned when no limit is given
+const DEFAULT_AUDIT_LIMIT: usize = 200;
+
+/// Gets recorded data mutations, newest first
+///
+/// # Arguments
+/// * `entity` - Only entries about this kind of data
+/// * `entity_id` - Only entries about this record
+/// * `limit` - Maximum number of entries, defaults to 200
+#[tauri::command]
+pub async fn get_audit_log(
+    state: State<'_, AppState>,
+    entity: Option<AuditEntity>,
+    entity_id: Option<Uuid>,
+    limit: Option<usize>,
+) -> Result<Vec<AuditEntry>, String> {
+    let entries = state
+        .storage()
+        .read_audit()
+        .await
+        .map_err(log_error("Failed to read audit log"))?;
+
+    Ok(entries
+        .into_iter()
+        .rev()
+        .filter(|entry| entity.is_none_or(|entity| entry.entity == entity))
+        .filter(|entry| entity_id.is_none_or(|id| entry.entity_id == Some(id)))
+        .take(limit.unwrap_or(DEFAULT_AUDIT_LIMIT))
+        .collect())
+}
+
+/// Undoes a single recorded mutation
+///
+/// See `audit::revert` for what can be reverted. The revert is saved like any
+/// other change and recorded as new entries pointing back at the reverted one.
+///
+/// # Arguments
+/// * `entry_id` - ID of the audit entry to revert
+#[tauri::command]
+pub async fn revert_audit_entry(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    entry_id: Uuid,
+) -> Result<(), String> {
+    let source = AuditSource::revert(entry_id);
+
+    match audit::revert(&state, entry_id).await? {
+        Revert::Profiles(profiles) => commit_profiles(&app, &state, profiles, source).await,
+        Revert::BriefCases(briefcases) => commit_briefcases(&app, &state, briefcases, source).await,
+        Revert::Groups(groups) => commit_groups(&app, &state, groups, source).await,
+        Revert::Settings(settings) => apply_settings(&app, &state, settings, source).await,
+        Revert::CommentIndex {
+            task_index,
+            comment_index,
+        } => update_comment_index(&app, &state, task_index, comment_index, source).await,
+    }
+}
+
+/// ==================== Backup Commands ====================
+/// Writes all application data into a single backup archive
+///
+/// The data files are stored decrypted, so the archive stays readable after the
+/// app's passphrase changes.
+///
+/// # Arguments
+/// * `path` - Where to write the archive
+/// * `include_profile_data` - Whether the profile data directories (logins) are included
+/// * `passphrase` - Passphrase protecting the data files, None to store them in plaintext
+///
+/// # Returns
+/// The manifest describing the archive's contents
+#[tauri::command]
+pub async fn export_backup(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: PathBuf,
+    include_profile_data: bool,
+    passphrase: Option<String>,
+) -> Result<BackupManifest, String> {
+    if state.is_locked().await {
+        return Err("Unlock the app before exporting a backup".to_string());
+    }
+
+    let app_version = app.package_info().version.to_string();
+    backup::export(&state, path, include_profile_data, passphrase, app_version).await
+}
+
+/// Restores application data from a backup archive
+///
+/// See `backup::prepare_import` for how the modes combine the backup with the
+/// existing data. Shortcuts from the backup are registered before the data is
+/// written and put back if the import fails.
+///
+/// # Arguments
+/// * `path` - The archive to import
+/// * `mode` - How the backup is combined with the existing data
+/// * `passphrase` - The passphrase the archive was exported with, if any
+///
+/// # Returns
+/// The manifest of the imported archive
+#[tauri::command]
+pub async fn import_backup(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    path: PathBuf,
+    mode: ImportMode,
+    passphrase: Option<String>,
+) -> Result<BackupManifest, String> {
+    let import = backup::prepare_import(&state, path, mode, passphrase).await?;
+
+    let previous = state.get_settings().await;
+    let settings = import.data.settings.clone();
+    let shortcuts_changed = settings.shortcuts != previous.shortcuts;
+    if shortcuts_changed {
+        shortcuts::register(&app, Some(&previous.shortcuts), &settings.shortcuts)?;
+    }
+
+    let imported = backup::apply_import(&state, import).await;
+    if shortcuts_changed && state.get_settings().await.shortcuts != settings.shortcuts {
+        let _ = shortcuts::register(&app, Some(&settings.shortcuts), &previous.shortcuts);
+    }
+    let manifest = imported?;
+
+    if settings.layout != previous.layout {
+        let profile_label = state.get_current_window_label().await;
+        layout::retile(&app, &settings.layout, profile_label.as_deref())?;
+    }
+
+    for event in [
+        "profiles-changed",
+        "briefcases-changed",
+        "groups-changed",
+        "platforms-changed",
+    ] {
+        app.emit(event, ())
+            .map_err(log_error("Failed to emit event"))?;
+    }
+    app.emit("settings-changed", settings)
+        .map_err(log_error("Failed to emit event"))?;
+
+    Ok(manifest)
+}
+
+/// ==================== Security Commands ====================
+/// Gets whether a passphrase is set, the data is encrypted and the app is locked
+#[tauri::command]
+pub async fn get_security_status(state: State<'_, AppState>) -> Result<SecurityStatus, String> {
+    let security = state.get_security().await;
+    Ok(SecurityStatus {
+        passphrase_set: security.is_some(),
+        encrypted: security.is_some_and(|config| config.encrypt_data),
+        locked: state.is_locked().await,
+    })
+}
+
+/// Sets or changes the passphrase and re-encrypts the data files with it
+///
+/// # Arguments
+/// * `current` - The current passphrase, required if one is set
+/// * `passphrase` - The new passphrase
+/// * `encrypt_data` - Whether the data files are encrypted, or only the app lock is used
+///
+/// # Errors
+/// Returns an error if the app is locked or the current passphrase is wrong
+#[tauri::command]
+pub async fn set_passphrase(
+    state: State<'_, AppState>,
+    current: Option<String>,
+    passphrase: String,
+    encrypt_data: bool,
+) -> Result<SecurityStatus, String> {
+    data::set_passphrase(&state, current, passphrase, encrypt_data).await?;
+    get_security_status(state).await
+}
+
+/// Removes the passphrase and stores the data files in plaintext again
+///
+/// # Arguments
+/// * `current` - The current passphrase
+#[tauri::command]
+pub async fn remove_passphrase(
+    state: State<'_, AppState>,
+    current: String,
+) -> Result<SecurityStatus, String> {
+    data::remove_passphrase(&state, current).await?;
+    get_security_status(state).await
+}
+
+/// Unlocks the app with the passphrase and loads the encrypted data
+///
+/// # Arguments
+/// * `passphrase` - The configured passphrase
+///
+/// # Errors
+/// Returns an error if the passphrase is wrong
+#[tauri::command]
+pub async fn unlock_app(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    passphrase: String,
+) -> Result<SecurityStatus, String> {
+    if data::unlock(&state, passphrase).await? {
+        for event in ["profiles-changed", "briefcases-changed", "groups-changed"] {
+            app.emit(event, ())
+                .map_err(log_error("Failed to emit event"))?;
+        }
+    }
+
+    get_security_status(state).await
+}
+
+/// Locks the app, closing the workspace and unloading encrypted data
+#[tauri::command]
+pub async fn lock_app(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<SecurityStatus, String> {
+    if state.get_security().await.is_none() {
+        return Err("Set a passphrase first".to_string());
+    }
+
+    close_workspace(app.clone(), state.clone()).await?;
+
+    if data::lock(&state).await? {
+        for event in ["profiles-changed", "briefcases-changed", "groups-changed"] {
+            app.emit(event, ())
+                .map_err(log_error("Failed to emit event"))?;
+        }
+    }
+
+    get_security_status(state).await
+}
+
+/// ==================== Diagnostics Commands ====================
+/// Number of log entries returned when no limit is given
+const DEFAULT_LOG_LIMIT: usize = 200;
+/// Number of warnings and errors included in the diagnostics bundle
+const DIAGNOSTICS_LOG_LIMIT: usize = 100;
+
+/// Gets the most recent log entries, optionally filtered by level and module
+///
+/// # Arguments
+/// * `limit` - Maximum number of entries, defaults to 200
+/// * `level` - Minimum severity, e.g. `warn` returns warnings and errors
+/// * `module` - Only entries whose module contains this text, e.g. `storage`
+#[tauri::command]
+pub async fn get_recent_logs(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+    level: Option<String>,
+    module: Option<String>,
+) -> Result<Vec<LogEntry>, String> {
+    let log_dir = logging::log_dir(state.app_data_dir());
+    logging::read_recent(
+        &log_dir,
+        limit.unwrap_or(DEFAULT_LOG_LIMIT),
+        level.as_deref(),
+        module.as_deref(),
+    )
+    .await
+}
+
+/// Builds a diagnostics bundle, copies it to the clipboard and returns it
+#[tauri::command]
+pub async fn copy_diagnostics(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DiagnosticsBundle, String> {
+    let data_files = state
+        .storage()
+        .data_file_stats()
+        .await
+        .map_err(log_error("Failed to read data file stats"))?;
+
+    let log_dir = logging::log_dir(state.app_data_dir());
+    let recent_logs =
+        logging::read_recent(&log_dir, DIAGNOSTICS_LOG_LIMIT, Some("warn"), None).await?;
+
+    let bundle = DiagnosticsBundle {
+        app_version: app.package_info().version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        storage_backend: state.storage().name().to_string(),
+        data_files,
+        recent_logs,
+    };
+
+    let text = serde_json::to_string_pretty(&bundle)
+        .map_err(log_error("Failed to serialize diagnostics"))?;
+
+    app.clipboard()
+        .write_text(text)
+        .map_err(log_error("Failed to copy diagnostics"))?;
+
+    Ok(bundle)
+}
+
 /// Test command for debugging
 #[tauri::command]
 pub fn test_command() -> String {
@@ -0,0 +1,548 @@
+use crate::audit::{self, AuditEntity, AuditEntry, AuditSource};
+use crate::logging::log_error;
+use crate::models::profile::normalize_sort_order;
+use crate::models::{BriefCase, BriefCaseGroup, Profile, Session, SessionStep};
+use crate::platforms::PlatformRegistry;
+use crate::profile_data;
+use crate::security::{self, SecurityConfig};
+use crate::state::AppState;
+use crate::storage::DataSnapshot;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// Validates the profiles that are new or whose metadata changed
+///
+/// Profiles that only moved are not validated again, so a profile saved before
+/// a rule existed does not keep the others from being saved.
+///
+/// # Arguments
+/// * `previous` - The profiles as they were saved before
+/// * `profiles` - The profiles about to be saved
+fn validate_changed_profiles(previous: &[Profile], profiles: &[Profile]) -> Result<(), String> {
+    for profile in profiles {
+        let unchanged = previous.iter().any(|old| {
+            *old == Profile {
+                sort_order: old.sort_order,
+                ..profile.clone()
+            }
+        });
+        if !unchanged {
+            profile.validate()?;
+        }
+    }
+    Ok(())
+}
+
+/// Replaces all profiles, saves them and records the changes
+///
+/// New and changed profiles are validated, then all are renumbered in their
+/// user-defined order.
+///
+/// # Arguments
+/// * `profiles` - The new list of profiles
+/// * `source` - What caused the change, recorded in the audit log
+pub async fn save_profiles(
+    state: &AppState,
+    mut profiles: Vec<Profile>,
+    source: AuditSource,
+) -> Result<(), String> {
+    let previous = state.get_profiles().await;
+    validate_changed_profiles(&previous, &profiles)?;
+    normalize_sort_order(&mut profiles);
+
+    state.set_profiles(profiles.clone()).await;
+    log::info!("Saving {} profiles", profiles.len());
+
+    state
+        .storage()
+        .write_profiles(&profiles)
+        .await
+        .map_err(log_error("Failed to save profiles"))?;
+
+    audit::record(
+        state,
+        audit::diff(source, AuditEntity::Profile, &previous, &profiles, |p| {
+            p.profile_id
+        }),
+    )
+    .await
+}
+
+/// Replaces all BriefCases, saves them and records the changes
+///
+/// Removed BriefCases are dropped from the groups they belonged to.
+///
+/// # Arguments
+/// * `briefcases` - The new list of BriefCases
+/// * `source` - What caused the change, recorded in the audit log
+///
+/// # Returns
+/// true if groups were changed because they lost members
+pub async fn save_briefcases(
+    state: &AppState,
+    briefcases: Vec<BriefCase>,
+    source: AuditSource,
+) -> Result<bool, String> {
+    let previous = state.get_brief_cases().await;
+    state.set_brief_cases(briefcases.clone()).await;
+    log::info!("Saving {} briefcases", briefcases.len());
+
+    state
+        .storage()
+        .write_briefcases(&briefcases)
+        .await
+        .map_err(log_error("Failed to save briefcases"))?;
+
+    audit::record(
+        state,
+        audit::diff(
+            source,
+            AuditEntity::BriefCase,
+            &previous,
+            &briefcases,
+            |b| b.id,
+        ),
+    )
+    .await?;
+
+    // Drop removed briefcases from the groups they belonged to
+    let mut groups = state.get_groups().await;
+    let mut pruned = false;
+    for group in &mut groups {
+        let member_count = group.briefcase_ids.len();
+        group
+            .briefcase_ids
+            .retain(|id| briefcases.iter().any(|b| b.id == *id));
+        pruned |= group.briefcase_ids.len() != member_count;
+    }
+    if pruned {
+        save_groups(state, groups, source).await?;
+    }
+
+    Ok(pruned)
+}
+
+/// Replaces all groups, saves them and records the changes
+///
+/// Every group is validated against the current briefcases first.
+///
+/// # Arguments
+/// * `groups` - The new list of groups
+/// * `source` - What caused the change, recorded in the audit log
+pub async fn save_groups(
+    state: &AppState,
+    groups: Vec<BriefCaseGroup>,
+    source: AuditSource,
+) -> Result<(), String> {
+    let briefcases = state.get_brief_cases().await;
+    for group in &groups {
+        group.validate(&briefcases)?;
+    }
+
+    let previous = state.get_groups().await;
+    state.set_groups(groups.clone()).await;
+    log::info!("Saving {} groups", groups.len());
+
+    state
+        .storage()
+        .write_groups(&groups)
+        .await
+        .map_err(log_error("Failed to save groups"))?;
+
+    audit::record(
+        state,
+        audit::diff(source, AuditEntity::Group, &previous, &groups, |g| g.id),
+    )
+    .await
+}
+
+/// Replaces all data with a restored data set and records the changes
+///
+/// Everything new or changed is validated before the backend writes the whole set
+/// at once, so a failure leaves the stored data untouched. The audit log is only appended to:
+/// first `history`, then one entry per changed record.
+///
+/// # Arguments
+/// * `data` - The data set to restore
+/// * `history` - Audit entries to carry over, e.g. those of a backup
+/// * `source` - What caused the change, recorded in the audit log
+pub async fn restore(
+    state: &AppState,
+    mut data: DataSnapshot,
+    mut history: Vec<AuditEntry>,
+    source: AuditSource,
+) -> Result<(), String> {
+    validate_changed_profiles(&state.get_profiles().await, &data.profiles)?;
+    normalize_sort_order(&mut data.profiles);
+    for group in &data.groups {
+        group.validate(&data.briefcases)?;
+    }
+    data.settings.validate()?;
+    for platform in &data.platforms {
+        platform.validate()?;
+    }
+
+    state
+        .storage()
+        .restore(&data)
+        .await
+        .map_err(log_error("Failed to restore data"))?;
+
+    let previous_profiles = state.get_profiles().await;
+    let previous_briefcases = state.get_brief_cases().await;
+    let previous_groups = state.get_groups().await;
+    let previous_settings = state.get_settings().await;
+    state.set_profiles(data.profiles.clone()).await;
+    state.set_brief_cases(data.briefcases.clone()).await;
+    state.set_groups(data.groups.clone()).await;
+    state.set_settings(data.settings.clone()).await;
+    state
+        .set_platforms(PlatformRegistry::with_custom(data.platforms.clone()))
+        .await;
+    log::info!(
+        "Restored {} profiles, {} briefcases and {} sessions",
+        data.profiles.len(),
+        data.briefcases.len(),
+        data.sessions.len()
+    );
+
+    history.extend(audit::diff(
+        source,
+        AuditEntity::Profile,
+        &previous_profiles,
+        &data.profiles,
+        |p| p.profile_id,
+    ));
+    history.extend(audit::diff(
+        source,
+        AuditEntity::BriefCase,
+        &previous_briefcases,
+        &data.briefcases,
+        |b| b.id,
+    ));
+    history.extend(audit::diff(
+        source,
+        AuditEntity::Group,
+        &previous_groups,
+        &data.groups,
+        |g| g.id,
+    ));
+    history.extend(audit::update(
+        source,
+        AuditEntity::Settings,
+        None,
+        &previous_settings,
+        &data.settings,
+    ));
+
+    audit::record(state, history).await
+}
+
+/// Reads all recorded sessions together with their steps
+///
+/// # Returns
+/// The sessions, oldest first, and the steps of all of them
+pub async fn read_history(state: &AppState) -> Result<(Vec<Session>, Vec<SessionStep>), String> {
+    let sessions = state
+        .storage()
+        .read_sessions()
+        .await
+        .map_err(log_error("Failed to read session history"))?;
+
+    let mut steps = Vec::new();
+    for session in &sessions {
+        steps.extend(
+            state
+                .storage()
+                .read_session_steps(session.id)
+                .await
+                .map_err(log_error("Failed to read session history"))?,
+        );
+    }
+
+    Ok((sessions, steps))
+}
+
+/// Derives the key for a passphrase off the async runtime and checks it
+pub async fn derive_key(config: SecurityConfig, passphrase: String) -> Result<[u8; 32], String> {
+    tokio::task::spawn_blocking(move || config.unlock(&passphrase))
+        .await
+        .map_err(log_error("Failed to check passphrase"))?
+        .map_err(|e| e.to_string())
+}
+
+/// Checks the passphrase and unlocks the app, loading the data if it is encrypted
+///
+/// # Returns
+/// true if encrypted data was loaded into the state
+///
+/// # Errors
+/// Returns an error if no passphrase is set or the passphrase is wrong
+pub async fn unlock(state: &AppState, passphrase: String) -> Result<bool, String> {
+    let config = state.get_security().await.ok_or("No passphrase is set")?;
+    if !state.is_locked().await {
+        return Ok(false);
+    }
+
+    let encrypt_data = config.encrypt_data;
+    let key = derive_key(config, passphrase).await.map_err(|e| {
+        log::warn!("Failed unlock attempt: {}", e);
+        e
+    })?;
+
+    if encrypt_data {
+        state.storage().data_key().activate(Some(key), true);
+
+        let profiles = state
+            .storage()
+            .read_profiles()
+            .await
+            .map_err(log_error("Failed to read profiles"))?;
+        let briefcases = state
+            .storage()
+            .read_briefcases()
+            .await
+            .map_err(log_error("Failed to read briefcases"))?;
+        let groups = state
+            .storage()
+            .read_groups()
+            .await
+            .map_err(log_error("Failed to read groups"))?;
+
+        state.set_profiles(profiles).await;
+        state.set_brief_cases(briefcases).await;
+        state.set_groups(groups).await;
+    }
+    state.set_locked(false).await;
+
+    log::info!("App unlocked");
+    Ok(encrypt_data)
+}
+
+/// Rewrites all data with a new key and stores the new passphrase configuration
+///
+/// Everything is read with the current key first. If writing fails, the current
+/// key is put back into effect and the stored data is left as it was.
+///
+/// # Arguments
+/// * `key` - The key to encrypt with, None to store plaintext
+/// * `config` - The new passphrase configuration, None to remove it
+async fn rekey(
+    state: &AppState,
+    key: Option<[u8; 32]>,
+    config: Option<SecurityConfig>,
+) -> Result<(), String> {
+    let audit = state
+        .storage()
+        .read_audit()
+        .await
+        .map_err(log_error("Failed to read audit log"))?;
+    let platforms = state
+        .storage()
+        .read_platforms()
+        .await
+        .map_err(log_error("Failed to read platforms"))?;
+    let (sessions, steps) = read_history(state).await?;
+    let data = DataSnapshot {
+        profiles: state.get_profiles().await,
+        briefcases: state.get_brief_cases().await,
+        groups: state.get_groups().await,
+        settings: state.get_settings().await,
+        platforms,
+        sessions,
+        steps,
+    };
+
+    let data_key = state.storage().data_key();
+    let previous = data_key.active_key();
+    let encrypt_data = config.as_ref().is_some_and(|config| config.encrypt_data);
+    data_key.activate(key.filter(|_| encrypt_data), encrypt_data);
+    let rekeyed = state
+        .storage()
+        .rekey(&data, &audit, config.as_ref())
+        .await
+        .map_err(log_error("Failed to re-encrypt data"));
+    if rekeyed.is_err() {
+        data_key.reactivate(previous);
+    }
+    rekeyed?;
+
+    state.set_security(config).await;
+    Ok(())
+}
+
+/// Sets or changes the passphrase and rewrites the data with the new key
+///
+/// # Arguments
+/// * `current` - The current passphrase, required if one is set
+/// * `passphrase` - The new passphrase
+/// * `encrypt_data` - Whether the data is encrypted, or only the app lock is used
+///
+/// # Errors
+/// Returns an error if the app is locked, the current passphrase is wrong or
+/// the new one is too short
+pub async fn set_passphrase(
+    state: &AppState,
+    current: Option<String>,
+    passphrase: String,
+    encrypt_data: bool,
+) -> Result<(), String> {
+    if state.is_locked().await {
+        return Err("Unlock the app before changing the passphrase".to_string());
+    }
+    if let Some(config) = state.get_security().await {
+        derive_key(config, current.unwrap_or_default()).await?;
+    }
+    security::validate_passphrase(&passphrase)?;
+
+    let (config, key) =
+        tokio::task::spawn_blocking(move || SecurityConfig::create(&passphrase, encrypt_data))
+            .await
+            .map_err(log_error("Failed to set passphrase"))?
+            .map_err(log_error("Failed to set passphrase"))?;
+
+    rekey(state, Some(key), Some(config)).await?;
+
+    log::info!(
+        "Passphrase set, data encryption {}",
+        if encrypt_data { "on" } else { "off" }
+    );
+    Ok(())
+}
+
+/// Removes the passphrase and stores the data in plaintext again
+///
+/// # Arguments
+/// * `current` - The current passphrase
+///
+/// # Errors
+/// Returns an error if the app is locked, no passphrase is set or it is wrong
+pub async fn remove_passphrase(state: &AppState, current: String) -> Result<(), String> {
+    if state.is_locked().await {
+        return Err("Unlock the app before removing the passphrase".to_string());
+    }
+    let config = state.get_security().await.ok_or("No passphrase is set")?;
+    derive_key(config, current).await?;
+
+    rekey(state, None, None).await?;
+
+    log::info!("Passphrase removed");
+    Ok(())
+}
+
+/// Locks the app, unloading the data if it is encrypted
+///
+/// # Returns
+/// true if encrypted data was unloaded from the state
+///
+/// # Errors
+/// Returns an error if no passphrase is set
+pub async fn lock(state: &AppState) -> Result<bool, String> {
+    let config = state.get_security().await.ok_or("Set a passphrase first")?;
+    state.set_locked(true).await;
+
+    if config.encrypt_data {
+        state.storage().data_key().activate(None, true);
+
+        state.set_profiles(Vec::new()).await;
+        state.set_brief_cases(Vec::new()).await;
+        state.set_groups(Vec::new()).await;
+    }
+
+    log::info!("App locked");
+    Ok(config.encrypt_data)
+}
+
+/// Checks the persisted data for problems the app would silently work around
+///
+/// Reads every data set straight from storage, so unreadable data is reported
+/// instead of being replaced by an empty set as on startup.
+///
+/// # Returns
+/// A description of every problem found, empty if the data is consistent
+pub async fn validate(state: &AppState) -> Vec<String> {
+    let mut problems = Vec::new();
+    let storage = state.storage();
+
+    let profiles = storage.read_profiles().await.unwrap_or_else(|e| {
+        problems.push(format!("Profiles cannot be read: {}", e));
+        Vec::new()
+    });
+    let briefcases = storage.read_briefcases().await.unwrap_or_else(|e| {
+        problems.push(format!("Briefcases cannot be read: {}", e));
+        Vec::new()
+    });
+    let groups = storage.read_groups().await.unwrap_or_else(|e| {
+        problems.push(format!("Groups cannot be read: {}", e));
+        Vec::new()
+    });
+
+    match storage.read_settings().await {
+        Ok(settings) => {
+            if let Err(e) = settings.validate() {
+                problems.push(format!("Settings are invalid: {}", e));
+            }
+        }
+        Err(e) => problems.push(format!("Settings cannot be read: {}", e)),
+    }
+    if let Err(e) = storage.read_audit().await {
+        problems.push(format!("Audit log cannot be read: {}", e));
+    }
+    if let Err(e) = read_history(state).await {
+        problems.push(format!("Session history cannot be read: {}", e));
+    }
+
+    let mut profile_ids = HashSet::new();
+    for profile in &profiles {
+        if !profile_ids.insert(profile.profile_id) {
+            problems.push(format!("Profile {} appears twice", profile.profile_id));
+        }
+        if let Err(e) = profile.validate() {
+            problems.push(format!("Profile {}: {}", profile.profile_id, e));
+        }
+        if !profile.get_data_path(state.app_data_dir()).is_dir() {
+            problems.push(format!(
+                "Profile {} ({}) has no data directory",
+                profile.profile_name, profile.profile_id
+            ));
+        }
+    }
+
+    let mut briefcase_ids = HashSet::new();
+    for briefcase in &briefcases {
+        if !briefcase_ids.insert(briefcase.id) {
+            problems.push(format!("Briefcase {} appears twice", briefcase.id));
+        }
+        if !profile_ids.contains(&briefcase.profile_id) {
+            problems.push(format!(
+                "Briefcase {} ({}) belongs to unknown profile {}",
+                briefcase.user_name, briefcase.id, briefcase.profile_id
+            ));
+        }
+        if briefcase.user_name.trim().is_empty() {
+            problems.push(format!("Briefcase {} has no user name", briefcase.id));
+        }
+    }
+
+    for group in &groups {
+        if let Err(e) = group.validate(&briefcases) {
+            problems.push(e);
+        }
+    }
+
+    let known: HashSet<Uuid> = profile_ids;
+    let root = profile_data::profiles_root(state.app_data_dir());
+    if root.is_dir() {
+        match profile_data::find_orphans(root, known).await {
+            Ok(orphans) => problems.extend(orphans.into_iter().map(|orphan| {
+                format!(
+                    "Data directory {} belongs to no profile",
+                    orphan.path.display()
+                )
+            })),
+            Err(e) => problems.push(format!("Profile directories cannot be read: {}", e)),
+        }
+    }
+
+    problems
+}
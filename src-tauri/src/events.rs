@@ -0,0 +1,177 @@
+use crate::models::{BriefCase, Profile};
+use crate::state::AppState;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+/// Emitted whenever the execution plan lands on a new step
+pub const STEP_CHANGED: &str = "step-changed";
+/// Emitted when the new step belongs to a different profile than the previous one
+pub const PROFILE_SWITCHED: &str = "profile-switched";
+/// Emitted after every move of the execution plan
+pub const PROGRESS_UPDATED: &str = "progress-updated";
+/// Emitted once the last step has been passed
+pub const SESSION_COMPLETED: &str = "session-completed";
+/// Emitted when a comment has been placed on the clipboard
+pub const COMMENT_COPIED: &str = "comment-copied";
+/// Emitted when the user closes the active profile window during automation
+pub const WINDOW_LOST: &str = "window-lost";
+/// Emitted when automation is paused, with the session's `SessionTime`
+pub const AUTOMATION_PAUSED: &str = "automation-paused";
+/// Emitted when automation is resumed, with the session's `SessionTime`
+pub const AUTOMATION_RESUMED: &str = "automation-resumed";
+
+/// Payload of the `step-changed` event
+#[derive(Debug, Clone, Serialize)]
+pub struct StepChanged {
+    /// Position of the step in the execution order
+    pub step_index: usize,
+    /// Total number of steps in the execution plan
+    pub total_steps: usize,
+    /// Index of the task this step belongs to
+    pub task_index: usize,
+    /// The BriefCase that should post on this step
+    pub briefcase: Option<BriefCase>,
+    /// The profile whose window displays this step
+    pub profile_id: Uuid,
+    /// The URL of the social media post
+    pub link: String,
+    /// The comment to post on this step
+    pub comment: Option<String>,
+}
+
+/// Payload of the `profile-switched` event
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileSwitched {
+    /// The profile that was active before the switch, if any
+    pub previous_profile_id: Option<Uuid>,
+    /// The profile that is now active
+    pub profile: Option<Profile>,
+}
+
+/// Payload of the `progress-updated` event
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressUpdated {
+    /// Overall progress: (visited_count, total_count)
+    pub overall_progress: (usize, usize),
+    /// Progress for each task: (task_index, visited, total)
+    pub task_progress: Vec<(usize, usize, usize)>,
+    /// Index of the current task
+    pub current_task_index: Option<usize>,
+}
+
+/// Payload of the `session-completed` event
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionCompleted {
+    /// Number of tasks in the finished session
+    pub total_tasks: usize,
+    /// Number of steps in the finished session
+    pub total_steps: usize,
+    /// Overall progress: (visited_count, total_count)
+    pub overall_progress: (usize, usize),
+}
+
+/// Payload of the `comment-copied` event
+#[derive(Debug, Clone, Serialize)]
+pub struct CommentCopied {
+    /// Index of the task the comment belongs to
+    pub task_index: usize,
+    /// The comment that was placed on the clipboard
+    pub comment: String,
+}
+
+/// Payload of the `window-lost` event
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowLost {
+    /// Label of the closed window
+    pub label: String,
+    /// The profile the closed window belonged to
+    pub profile_id: Option<Uuid>,
+}
+
+/// Emits an event to all windows, converting the error for command results
+pub fn emit<S: Serialize + Clone>(app: &AppHandle, event: &str, payload: S) -> Result<(), String> {
+    app.emit(event, payload)
+        .map_err(|e| format!("Failed to emit event: {}", e))
+}
+
+/// Emits the events describing the step the execution plan just landed on
+///
+/// # Arguments
+/// * `app` - The app handle used to emit events
+/// * `state` - The application state to build the payloads from
+/// * `previous_profile_id` - The profile that was active before the move
+pub async fn emit_plan_moved(
+    app: &AppHandle,
+    state: &AppState,
+    previous_profile_id: Option<Uuid>,
+) -> Result<(), String> {
+    emit_step_changed(app, state).await?;
+
+    let current_profile_id = state.current_profile_id().await;
+    if let Some(profile_id) = current_profile_id.filter(|id| Some(*id) != previous_profile_id) {
+        let profile = state.get_profile_by_id(profile_id).await;
+
+        emit(
+            app,
+            PROFILE_SWITCHED,
+            ProfileSwitched {
+                previous_profile_id,
+                profile,
+            },
+        )?;
+    }
+
+    emit_progress_updated(app, state).await
+}
+
+/// Emits `step-changed` for the current step, if there is one
+pub async fn emit_step_changed(app: &AppHandle, state: &AppState) -> Result<(), String> {
+    let (Some(step), Some(step_index)) =
+        (state.current_step().await, state.current_position().await)
+    else {
+        return Ok(());
+    };
+
+    let briefcase = state
+        .get_task(step.task_index)
+        .await
+        .and_then(|task| task.related_brief_cases.get(step.briefcase_index).cloned());
+
+    let payload = StepChanged {
+        step_index,
+        total_steps: state.get_progress().await.1,
+        task_index: step.task_index,
+        briefcase,
+        profile_id: step.profile_id,
+        link: step.link,
+        comment: state.current_comment().await,
+    };
+
+    emit(app, STEP_CHANGED, payload)
+}
+
+/// Emits `progress-updated` with the current progress of the plan
+pub async fn emit_progress_updated(app: &AppHandle, state: &AppState) -> Result<(), String> {
+    let payload = ProgressUpdated {
+        overall_progress: state.get_progress().await,
+        task_progress: state.get_task_progress().await,
+        current_task_index: state.current_task_index().await,
+    };
+
+    emit(app, PROGRESS_UPDATED, payload)
+}
+
+/// Emits the final progress followed by `session-completed`
+pub async fn emit_session_completed(app: &AppHandle, state: &AppState) -> Result<(), String> {
+    emit_progress_updated(app, state).await?;
+
+    let overall_progress = state.get_progress().await;
+    let payload = SessionCompleted {
+        total_tasks: state.task_count().await,
+        total_steps: overall_progress.1,
+        overall_progress,
+    };
+
+    emit(app, SESSION_COMPLETED, payload)
+}
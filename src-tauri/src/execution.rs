@@ -1,4 +1,5 @@
-use crate::models::Task;
+use crate::models::{BriefCase, BriefCaseGroup, Profile, Task, Unavailability};
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use uuid::Uuid;
@@ -14,6 +15,12 @@ pub struct ExecutionStep {
     pub profile_id: Uuid,
     /// The URL of the social media post
     pub link: String,
+    /// Whether the step has been completed
+    #[serde(default)]
+    pub completed: bool,
+    /// Index of the task comment posted on the step, None until it is completed
+    #[serde(default)]
+    pub comment_index: Option<usize>,
 }
 
 /// Represents execution steps grouped by profile for optimized profile switching
@@ -25,6 +32,56 @@ pub struct ProfileExecution {
     pub steps: Vec<ExecutionStep>,
 }
 
+/// A BriefCase matching a task platform that was left out of the plan
+#[derive(Debug, Clone, Serialize)]
+pub struct ExcludedBriefCase {
+    /// The account that was left out
+    pub briefcase: BriefCase,
+    /// Why the account was left out
+    pub reason: Unavailability,
+}
+
+/// Number of steps a profile contributes to a plan
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfilePreview {
+    /// The profile the steps run on
+    pub profile_id: Uuid,
+    /// Display name of the profile, None if it no longer exists
+    pub profile_name: Option<String>,
+    /// Number of steps run on this profile
+    pub step_count: usize,
+}
+
+/// The group a task targets and the members it resolved to
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskGroupPreview {
+    /// Index of the task in the task list
+    pub task_index: usize,
+    /// The targeted group
+    pub group_id: Uuid,
+    /// Display name of the group, None if it no longer exists
+    pub group_name: Option<String>,
+    /// IDs of the group members posting on the task
+    pub briefcase_ids: Vec<Uuid>,
+}
+
+/// Summary of what an execution plan would do, shown before automation starts
+#[derive(Debug, Clone, Serialize)]
+pub struct PlanPreview {
+    /// Total number of steps in the plan
+    pub total_steps: usize,
+    /// Profiles in the order they are visited
+    pub profiles: Vec<ProfilePreview>,
+    /// Accounts matching a task platform that are disabled or paused
+    pub excluded: Vec<ExcludedBriefCase>,
+    /// Indices of tasks no available account can post on
+    pub tasks_without_accounts: Vec<usize>,
+    /// Tasks targeting a group, with the members they resolved to
+    pub task_groups: Vec<TaskGroupPreview>,
+    /// Accounts in the plan whose login expired or was never checked
+    pub unverified_logins: Vec<BriefCase>,
+}
+
 /// Optimized execution plan that groups BriefCases by profile to minimize profile switches
 ///
 /// The execution plan creates an optimized sequence where:
@@ -41,8 +98,6 @@ pub struct ExecutionPlan {
     execution_order: Vec<ExecutionStep>,
     /// Current position in the execution order
     current_step_index: usize,
-    /// Set of visited BriefCase IDs to track progress
-    visited_briefcase_ids: Vec<Uuid>,
 }
 
 impl ExecutionPlan {
@@ -51,24 +106,33 @@ impl ExecutionPlan {
     /// # Algorithm:
     /// 1. For each task, iterate through all its BriefCases
     /// 2. Group executions by profile_id to minimize profile switches
-    /// 3. Create a flattened execution order that respects profile grouping
+    /// 3. Create a flattened execution order that visits profiles in their user-defined order
     ///
     /// # Arguments
     /// * `tasks` - The list of tasks to create a plan for
+    /// * `profiles` - All profiles, used for ordering; unknown profiles run last
     ///
     /// # Returns
     /// A new ExecutionPlan optimized for minimal profile switching
-    pub fn new(tasks: &[Task]) -> Self {
+    pub fn new(tasks: &[Task], profiles: &[Profile]) -> Self {
         let mut profile_executions: BTreeMap<Uuid, ProfileExecution> = BTreeMap::new();
 
-        // Group execution steps by profile ID
+        let now = Utc::now();
+
+        // Group execution steps by profile ID, leaving out disabled and paused accounts
         for (task_index, task) in tasks.iter().enumerate() {
             for (briefcase_index, briefcase) in task.related_brief_cases.iter().enumerate() {
+                if briefcase.unavailability(now).is_some() {
+                    continue;
+                }
+
                 let step = ExecutionStep {
                     task_index,
                     briefcase_index,
                     profile_id: briefcase.profile_id,
                     link: task.link.clone(),
+                    completed: false,
+                    comment_index: None,
                 };
 
                 profile_executions
@@ -82,25 +146,135 @@ impl ExecutionPlan {
             }
         }
 
-        // Create flattened execution order by iterating through profiles
-        let execution_order: Vec<ExecutionStep> = profile_executions
-            .values()
+        // Create flattened execution order by iterating through profiles in the user's order
+        let position = |profile_id: &Uuid| {
+            profiles
+                .iter()
+                .find(|p| p.profile_id == *profile_id)
+                .map_or(u32::MAX, |p| p.sort_order)
+        };
+        let mut ordered: Vec<&ProfileExecution> = profile_executions.values().collect();
+        ordered.sort_by_key(|pe| position(&pe.profile_id));
+
+        let execution_order: Vec<ExecutionStep> = ordered
+            .into_iter()
             .flat_map(|pe| pe.steps.clone())
             .collect();
 
+        log::debug!(
+            "Planned {} steps across {} profiles",
+            execution_order.len(),
+            profile_executions.len()
+        );
+
         Self {
             profile_executions,
             execution_order,
             current_step_index: 0,
-            visited_briefcase_ids: Vec::new(),
         }
     }
 
-    /// Gets the next execution step in the sequence
+    /// Summarizes the plan for review before it runs
+    ///
+    /// # Arguments
+    /// * `tasks` - The tasks the plan was created from
+    /// * `all_brief_cases` - All BriefCases, used to report excluded accounts
+    /// * `profiles` - All profiles, used to name the visited profiles
+    /// * `groups` - All groups, used to name the targeted groups
+    ///
+    /// # Returns
+    /// A PlanPreview listing profiles in visiting order and every excluded account
+    pub fn preview(
+        &self,
+        tasks: &[Task],
+        all_brief_cases: &[BriefCase],
+        profiles: &[Profile],
+        groups: &[BriefCaseGroup],
+    ) -> PlanPreview {
+        let mut profile_previews: Vec<ProfilePreview> = Vec::new();
+        for step in &self.execution_order {
+            match profile_previews.last_mut() {
+                Some(preview) if preview.profile_id == step.profile_id => preview.step_count += 1,
+                _ => profile_previews.push(ProfilePreview {
+                    profile_id: step.profile_id,
+                    profile_name: profiles
+                        .iter()
+                        .find(|p| p.profile_id == step.profile_id)
+                        .map(|p| p.profile_name.clone()),
+                    step_count: 1,
+                }),
+            }
+        }
+
+        let now = Utc::now();
+        let excluded = all_brief_cases
+            .iter()
+            .filter(|bc| {
+                tasks.iter().any(|t| {
+                    t.social_media == bc.social_media
+                        && t.target_group_id
+                            .is_none_or(|id| groups.iter().any(|g| g.id == id && g.contains(bc.id)))
+                })
+            })
+            .filter_map(|bc| {
+                bc.unavailability(now).map(|reason| ExcludedBriefCase {
+                    briefcase: bc.clone(),
+                    reason,
+                })
+            })
+            .collect();
+
+        let tasks_without_accounts = (0..tasks.len())
+            .filter(|index| !self.execution_order.iter().any(|s| s.task_index == *index))
+            .collect();
+
+        let task_groups = tasks
+            .iter()
+            .enumerate()
+            .filter_map(|(task_index, task)| {
+                let group_id = task.target_group_id?;
+                Some(TaskGroupPreview {
+                    task_index,
+                    group_id,
+                    group_name: groups
+                        .iter()
+                        .find(|g| g.id == group_id)
+                        .map(|g| g.name.clone()),
+                    briefcase_ids: task.related_brief_cases.iter().map(|bc| bc.id).collect(),
+                })
+            })
+            .collect();
+
+        let mut unverified_logins: Vec<BriefCase> = Vec::new();
+        for step in &self.execution_order {
+            let Some(briefcase) = tasks
+                .get(step.task_index)
+                .and_then(|task| task.related_brief_cases.get(step.briefcase_index))
+            else {
+                continue;
+            };
+            if !briefcase.is_login_verified()
+                && !unverified_logins.iter().any(|bc| bc.id == briefcase.id)
+            {
+                unverified_logins.push(briefcase.clone());
+            }
+        }
+
+        PlanPreview {
+            total_steps: self.execution_order.len(),
+            profiles: profile_previews,
+            excluded,
+            tasks_without_accounts,
+            task_groups,
+            unverified_logins,
+        }
+    }
+
+    /// Advances to the next execution step in the sequence
     ///
     /// # Returns
     /// Some(ExecutionStep) if there are more steps, None if complete
-    pub fn next(&mut self) -> Option<ExecutionStep> {
+    pub fn advance(&mut self) -> Option<ExecutionStep> {
         if self.current_step_index >= self.execution_order.len() {
             return None;
         }
@@ -110,6 +284,185 @@ impl ExecutionPlan {
         Some(step)
     }
 
+    /// Moves back to the previous execution step
+    ///
+    /// # Returns
+    /// Some(ExecutionStep) that is now current, None if already at the first step
+    pub fn previous(&mut self) -> Option<ExecutionStep> {
+        if self.current_step_index <= 1 {
+            return None;
+        }
+
+        self.current_step_index -= 1;
+        self.current_step().cloned()
+    }
+
+    /// Moves the cursor to any step of the plan
+    ///
+    /// Completed steps stay completed and keep the comment posted on them, so
+    /// jumping back to redo a step does not lower the progress.
+    ///
+    /// # Arguments
+    /// * `position` - Position of the step in the execution order
+    ///
+    /// # Returns
+    /// Some(ExecutionStep) that is now current, None if the position is out of range
+    pub fn goto(&mut self, position: usize) -> Option<ExecutionStep> {
+        let step = self.execution_order.get(position)?.clone();
+        self.current_step_index = position + 1;
+        Some(step)
+    }
+
+    /// Finds the position of a task's step in the execution order
+    ///
+    /// # Arguments
+    /// * `tasks` - The task list the plan was created from
+    /// * `task_index` - Index of the task
+    /// * `briefcase_id` - The BriefCase whose step to find; None for the task's
+    ///   first step not yet completed, or its first step if all were completed
+    ///
+    /// # Returns
+    /// Some(position) if the task has a matching step, None otherwise
+    pub fn task_position(
+        &self,
+        tasks: &[Task],
+        task_index: usize,
+        briefcase_id: Option<Uuid>,
+    ) -> Option<usize> {
+        let task = tasks.get(task_index)?;
+        let steps = || {
+            self.execution_order
+                .iter()
+                .enumerate()
+                .filter(|(_, step)| step.task_index == task_index)
+                .filter_map(|(position, step)| {
+                    let briefcase = task.related_brief_cases.get(step.briefcase_index)?;
+                    Some((position, briefcase.id, step.completed))
+                })
+        };
+
+        match briefcase_id {
+            Some(id) => steps().find(|(_, step_id, _)| *step_id == id),
+            None => steps()
+                .find(|(_, _, completed)| !completed)
+                .or_else(|| steps().next()),
+        }
+        .map(|(position, _, _)| position)
+    }
+
+    /// Adds the steps of a task that was appended to a running plan
+    ///
+    /// Each profile's new steps join its block if that block has not been
+    /// passed yet, so the profile is still opened only once. Steps for profiles
+    /// that are already done run at the end. Steps before the cursor never move.
+    ///
+    /// # Arguments
+    /// * `tasks` - The task list including the new task
+    /// * `task_index` - Index of the new task
+    /// * `profiles` - All profiles, used for ordering; unknown profiles run last
+    ///
+    /// # Returns
+    /// The number of steps added
+    pub fn add_task(&mut self, tasks: &[Task], task_index: usize, profiles: &[Profile]) -> usize {
+        let Some(task) = tasks.get(task_index) else {
+            return 0;
+        };
+
+        let now = Utc::now();
+        let mut new_steps: BTreeMap<Uuid, Vec<ExecutionStep>> = BTreeMap::new();
+        for (briefcase_index, briefcase) in task.related_brief_cases.iter().enumerate() {
+            if briefcase.unavailability(now).is_some() {
+                continue;
+            }
+            new_steps
+                .entry(briefcase.profile_id)
+                .or_default()
+                .push(ExecutionStep {
+                    task_index,
+                    briefcase_index,
+                    profile_id: briefcase.profile_id,
+                    link: task.link.clone(),
+                    completed: false,
+                    comment_index: None,
+                });
+        }
+
+        let position = |profile_id: &Uuid| {
+            profiles
+                .iter()
+                .find(|p| p.profile_id == *profile_id)
+                .map_or(u32::MAX, |p| p.sort_order)
+        };
+        let mut ordered: Vec<(Uuid, Vec<ExecutionStep>)> = new_steps.into_iter().collect();
+        ordered.sort_by_key(|(profile_id, _)| position(profile_id));
+
+        let mut added = 0;
+        for (profile_id, steps) in ordered {
+            // The current step counts as not passed, so new steps can follow it directly
+            let insert_at = self
+                .execution_order
+                .iter()
+                .rposition(|step| step.profile_id == profile_id)
+                .map(|last| last + 1)
+                .filter(|index| *index >= self.current_step_index)
+                .unwrap_or(self.execution_order.len());
+
+            added += steps.len();
+            self.profile_executions
+                .entry(profile_id)
+                .or_insert_with(|| ProfileExecution {
+                    profile_id,
+                    steps: Vec::new(),
+                })
+                .steps
+                .extend(steps.iter().cloned());
+            self.execution_order.splice(insert_at..insert_at, steps);
+        }
+
+        added
+    }
+
+    /// Removes the steps of a task from a running plan
+    ///
+    /// Following tasks move down one index, the cursor keeps pointing at the
+    /// same step, and the removed steps no longer count towards the progress.
+    ///
+    /// # Arguments
+    /// * `task_index` - Index the removed task had
+    ///
+    /// # Returns
+    /// true if the current step belonged to the removed task; the cursor then
+    /// points at the step before it, so `advance` continues with the step after it
+    pub fn remove_task(&mut self, task_index: usize) -> bool {
+        let current_removed = self.current_step_index > 0
+            && self
+                .execution_order
+                .get(self.current_step_index - 1)
+                .is_some_and(|step| step.task_index == task_index);
+        let removed_before = self.execution_order[..self.current_step_index]
+            .iter()
+            .filter(|step| step.task_index == task_index)
+            .count();
+
+        let renumber = |steps: &mut Vec<ExecutionStep>| {
+            steps.retain(|step| step.task_index != task_index);
+            for step in steps.iter_mut() {
+                if step.task_index > task_index {
+                    step.task_index -= 1;
+                }
+            }
+        };
+        renumber(&mut self.execution_order);
+        for execution in self.profile_executions.values_mut() {
+            renumber(&mut execution.steps);
+        }
+        self.profile_executions
+            .retain(|_, execution| !execution.steps.is_empty());
+        self.current_step_index -= removed_before;
+
+        current_removed
+    }
+
     /// Checks if the next step requires a profile change
     ///
     /// # Returns
@@ -130,13 +483,87 @@ impl ExecutionPlan {
         current_profile_id != next_profile_id
     }
 
-    /// Marks a BriefCase as visited for progress tracking
+    /// Marks the current step as completed
     ///
     /// # Arguments
-    /// * `briefcase_id` - The ID of the BriefCase to mark as visited
-    pub fn mark_visited(&mut self, briefcase_id: Uuid) {
-        if !self.visited_briefcase_ids.contains(&briefcase_id) {
-            self.visited_briefcase_ids.push(briefcase_id);
+    /// * `comment_index` - Index of the comment posted, None if the task has no comments
+    ///
+    /// # Returns
+    /// true if the step was completed for the first time, false if it was
+    /// completed again or there is no current step
+    pub fn complete_current(&mut self, comment_index: Option<usize>) -> bool {
+        let Some(position) = self.current_position() else {
+            return false;
+        };
+
+        let step = &mut self.execution_order[position];
+        let first_time = !step.completed;
+        step.completed = true;
+        step.comment_index = comment_index;
+        self.sync_grouped(position);
+        first_time
+    }
+
+    /// Gets the index of the comment shown for the current step
+    ///
+    /// A completed step shows the comment posted on it, any other step the
+    /// task's next comment in the rotation.
+    ///
+    /// # Arguments
+    /// * `tasks` - The task list the plan was created from
+    ///
+    /// # Returns
+    /// Some(index) into the task's comments, None if there is no current step
+    /// or its task has no comments
+    pub fn current_comment_index(&self, tasks: &[Task]) -> Option<usize> {
+        let step = self.current_step()?;
+        let task = tasks.get(step.task_index)?;
+        if task.comments.is_empty() {
+            return None;
+        }
+
+        Some(
+            step.comment_index
+                .filter(|index| *index < task.comments.len())
+                .unwrap_or(task.comment_index % task.comments.len()),
+        )
+    }
+
+    /// Changes the comment posted on the current step if it is a completed step of a task
+    ///
+    /// # Returns
+    /// true if the current step took the comment, false if the task's rotation
+    /// should change instead
+    pub fn assign_comment(&mut self, task_index: usize, comment_index: usize) -> bool {
+        let Some(position) = self.current_position() else {
+            return false;
+        };
+
+        match self.execution_order.get_mut(position) {
+            Some(step) if step.completed && step.task_index == task_index => {
+                step.comment_index = Some(comment_index);
+                self.sync_grouped(position);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Copies a step of the execution order over its copy in `profile_executions`
+    fn sync_grouped(&mut self, position: usize) {
+        let Some(step) = self.execution_order.get(position) else {
+            return;
+        };
+
+        let Some(execution) = self.profile_executions.get_mut(&step.profile_id) else {
+            return;
+        };
+        let grouped = execution
+            .steps
+            .iter_mut()
+            .find(|s| s.task_index == step.task_index && s.briefcase_index == step.briefcase_index);
+        if let Some(grouped) = grouped {
+            *grouped = step.clone();
         }
     }
 
@@ -168,10 +595,33 @@ impl ExecutionPlan {
         }
     }
 
-    /// Resets the execution plan to the beginning
+    /// Gets the position of the current step in the execution order
+    ///
+    /// # Returns
+    /// Some(index) if the plan has any steps, None otherwise
+    pub fn current_position(&self) -> Option<usize> {
+        if self.execution_order.is_empty() {
+            None
+        } else {
+            Some(
+                self.current_step_index
+                    .saturating_sub(1)
+                    .min(self.execution_order.len() - 1),
+            )
+        }
+    }
+
+    /// Resets the execution plan to the beginning, with no step completed
     pub fn reset(&mut self) {
         self.current_step_index = 0;
-        self.visited_briefcase_ids.clear();
+        let grouped = self
+            .profile_executions
+            .values_mut()
+            .flat_map(|pe| pe.steps.iter_mut());
+        for step in self.execution_order.iter_mut().chain(grouped) {
+            step.completed = false;
+            step.comment_index = None;
+        }
     }
 
     /// Gets total number of execution steps
@@ -179,50 +629,39 @@ impl ExecutionPlan {
         self.execution_order.len()
     }
 
-    /// Gets number of completed steps
-    #[allow(dead_code)]
-    pub fn completed_steps(&self) -> usize {
-        self.current_step_index
-    }
-
     /// Checks if all steps are completed
     pub fn is_complete(&self) -> bool {
         self.current_step_index >= self.execution_order.len()
     }
 
-    /// Gets the number of visited BriefCases
+    /// Gets the number of completed steps
+    ///
+    /// Steps are counted rather than BriefCases, so an account taking part in
+    /// several tasks counts once per task, matching `total_steps`.
     pub fn visited_count(&self) -> usize {
-        self.visited_briefcase_ids.len()
-    }
-
-    /// Calculates progress as a percentage (0.0 to 1.0)
-    #[allow(dead_code)]
-    pub fn progress(&self) -> f64 {
-        if self.execution_order.is_empty() {
-            1.0
-        } else {
-            self.current_step_index as f64 / self.execution_order.len() as f64
-        }
+        self.execution_order
+            .iter()
+            .filter(|step| step.completed)
+            .count()
     }
 
-    /// Gets task-specific progress (visited count / total count for each task)
+    /// Gets task-specific progress (completed steps / planned steps for each task)
     ///
     /// # Arguments
-    /// * `tasks` - The task list to calculate progress for
+    /// * `tasks` - The task list the plan was created from
     ///
     /// # Returns
     /// A vector of (task_index, visited, total) tuples for each task
     pub fn task_progress(&self, tasks: &[Task]) -> Vec<(usize, usize, usize)> {
-        tasks
-            .iter()
-            .enumerate()
-            .map(|(index, task)| {
-                let visited_count = task
-                    .related_brief_cases
+        (0..tasks.len())
+            .map(|index| {
+                let steps: Vec<&ExecutionStep> = self
+                    .execution_order
                     .iter()
-                    .filter(|bc| self.visited_briefcase_ids.contains(&bc.id))
-                    .count();
-                (index, visited_count, task.briefcase_count())
+                    .filter(|step| step.task_index == index)
+                    .collect();
+                let completed = steps.iter().filter(|step| step.completed).count();
+                (index, completed, steps.len())
             })
             .collect()
     }
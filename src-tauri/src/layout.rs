@@ -0,0 +1,81 @@
+use crate::window_layout::{MonitorInfo, Rect, WindowLayout};
+use tauri::{AppHandle, LogicalPosition, LogicalSize, Manager, Monitor, WebviewWindow};
+
+/// Converts a monitor's physical geometry into a logical rectangle
+fn logical_area(monitor: &Monitor) -> Rect {
+    let scale = monitor.scale_factor();
+    Rect {
+        x: monitor.position().x as f64 / scale,
+        y: monitor.position().y as f64 / scale,
+        width: monitor.size().width as f64 / scale,
+        height: monitor.size().height as f64 / scale,
+    }
+}
+
+/// Lists all connected monitors
+pub fn list_monitors(app: &AppHandle) -> Result<Vec<MonitorInfo>, String> {
+    let primary_name = app
+        .primary_monitor()
+        .map_err(|e| format!("Failed to get monitor: {}", e))?
+        .and_then(|m| m.name().cloned());
+
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| format!("Failed to get monitors: {}", e))?;
+
+    Ok(monitors
+        .iter()
+        .map(|monitor| MonitorInfo {
+            name: monitor.name().cloned(),
+            area: logical_area(monitor),
+            is_primary: monitor.name().cloned() == primary_name,
+        })
+        .collect())
+}
+
+/// Resolves the logical area of the monitor selected by the layout
+///
+/// Falls back to the primary monitor when the configured one is disconnected.
+pub fn monitor_area(app: &AppHandle, layout: &WindowLayout) -> Result<Rect, String> {
+    layout
+        .monitor_area(&list_monitors(app)?)
+        .ok_or_else(|| "No monitor available".to_string())
+}
+
+/// Moves and resizes a window to the given rectangle
+fn place(window: &WebviewWindow, rect: Rect) -> Result<(), String> {
+    window
+        .set_size(LogicalSize::new(rect.width, rect.height))
+        .map_err(|e| format!("Failed to resize window: {}", e))?;
+    window
+        .set_position(LogicalPosition::new(rect.x, rect.y))
+        .map_err(|e| format!("Failed to move window: {}", e))?;
+    Ok(())
+}
+
+/// Re-tiles the open panel and active profile window according to the layout
+///
+/// # Arguments
+/// * `app` - The app handle to look the windows up with
+/// * `layout` - The layout to apply
+/// * `profile_label` - Label of the active profile window, if any
+pub fn retile(
+    app: &AppHandle,
+    layout: &WindowLayout,
+    profile_label: Option<&str>,
+) -> Result<(), String> {
+    let (profile_rect, panel_rect) = layout.tile(monitor_area(app, layout)?);
+
+    if let Some(panel) = app.get_webview_window("panel") {
+        place(&panel, panel_rect)?;
+        panel
+            .set_always_on_top(layout.panel_always_on_top)
+            .map_err(|e| format!("Failed to update panel: {}", e))?;
+    }
+
+    if let Some(window) = profile_label.and_then(|label| app.get_webview_window(label)) {
+        place(&window, profile_rect)?;
+    }
+
+    Ok(())
+}
@@ -17,18 +17,36 @@ use crate::state::AppState;
 /// 4. Start automation - the system optimizes execution to minimize profile switches
 /// 5. Navigate through tasks using arrow keys or next button
 /// 6. Progress is tracked per-task and overall
-use crate::storage::Storage;
-use tauri::Manager;
+use crate::storage::{StorageKind, StoreLock};
+use tauri::{Manager, WindowEvent};
+use tauri_plugin_global_shortcut::ShortcutState;
 
 // Re-export commands for use in invoke_handler
 pub use crate::commands::*;
 
-// Module declarations
+// Core modules, usable without a window system
+pub mod audit;
+pub mod automation;
+pub mod backup;
+pub mod data;
+pub mod execution;
+pub mod logging;
+pub mod login_check;
+pub mod models;
+pub mod platforms;
+pub mod profile_data;
+pub mod security;
+pub mod settings;
+pub mod shortcut_bindings;
+pub mod state;
+pub mod storage;
+pub mod window_layout;
+
+// Tauri integration
 mod commands;
-mod execution;
-mod models;
-mod state;
-mod storage;
+mod events;
+mod layout;
+mod shortcuts;
 
 /// Initializes and runs the Tauri application
 ///
@@ -37,57 +55,144 @@ mod storage;
 pub fn run() {
     tauri::Builder::default()
         .setup(|app| {
-            // Initialize storage and load data
-            let storage_init =
-                tauri::async_runtime::block_on(async { Storage::initialize(app).await });
-
-            if let Err(e) = storage_init {
-                eprintln!("Failed to initialize storage: {}", e);
-                return Err(Box::new(e) as Box<dyn std::error::Error>);
+            // Start logging before anything else can fail
+            let app_data_dir = app.path().app_data_dir()?;
+            if let Err(e) = logging::init(logging::log_dir(&app_data_dir)) {
+                eprintln!("Failed to initialize logging: {}", e);
             }
+            log::info!("Starting attache {}", app.package_info().version);
 
-            // Load profiles and briefcases from disk
-            let (profiles, briefcases) = tauri::async_runtime::block_on(async {
-                let profiles: Vec<crate::models::Profile> =
-                    Storage::read_profiles(app).await.unwrap_or_default();
-                let briefcases: Vec<crate::models::BriefCase> =
-                    Storage::read_briefcases(app).await.unwrap_or_default();
-                (profiles, briefcases)
-            });
+            // Keep other processes from changing the data while the app runs
+            let lock = StoreLock::acquire(&app_data_dir).map_err(|e| {
+                log::error!("Failed to lock the data directory: {}", e);
+                Box::new(e) as Box<dyn std::error::Error>
+            })?;
+            app.manage(lock);
 
-            // Create and manage application state
-            let state = AppState::new(profiles, briefcases);
+            // Open the selected storage backend and load the state from it
+            let storage = StorageKind::from_env().open(&app_data_dir);
+            let state = tauri::async_runtime::block_on(AppState::load(storage, app_data_dir))
+                .map_err(|e| {
+                    log::error!("Failed to initialize storage: {}", e);
+                    Box::new(e) as Box<dyn std::error::Error>
+                })?;
+            let shortcut_bindings = tauri::async_runtime::block_on(state.get_shortcuts());
             app.manage(state);
 
+            // Register global shortcuts so navigation works while the profile window has focus
+            if let Err(e) = shortcuts::register(app.handle(), None, &shortcut_bindings) {
+                log::error!("Failed to register global shortcuts: {}", e);
+            }
+
             Ok(())
         })
+        .on_window_event(|window, event| {
+            if let WindowEvent::Destroyed = event {
+                if window.label().starts_with("profile-") {
+                    handle_profile_window_destroyed(window.app_handle(), window.label());
+                }
+            }
+        })
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, shortcut, event| {
+                    if event.state() == ShortcutState::Pressed {
+                        shortcuts::handle(app, shortcut);
+                    }
+                })
+                .build(),
+        )
         .invoke_handler(tauri::generate_handler![
             // Profile management
             create_profile,
             load_profiles,
             save_profiles,
+            clone_profile,
+            update_profile_details,
+            reorder_profiles,
+            // Profile data directories
+            get_profile_disk_usage,
+            clear_profile_cache,
+            wipe_profile_data,
+            find_orphan_profile_dirs,
+            delete_orphan_profile_dirs,
             // BriefCase management
             create_brief_case,
             load_briefcases,
             save_briefcases,
+            set_briefcase_availability,
+            // Platforms
+            list_platforms,
+            detect_platform,
+            // Group management
+            load_groups,
+            create_group,
+            update_group,
+            delete_group,
             // Batch operations
             save_all_data,
             // Window management
             launch_profile_window,
             launch_panel_window,
             change_webview_url,
+            restore_profile_window,
             close_workspace,
             create_window_sized,
+            get_window_layout,
+            set_window_layout,
+            list_monitors,
             // Automation
+            preview_plan,
             start_automation,
             execute_next_step,
             next_execution,
+            previous_execution,
+            skip_execution,
+            goto_step,
+            goto_task,
+            add_task_to_session,
+            remove_task_from_session,
+            edit_task_comments,
+            pause_automation,
+            resume_automation,
+            get_session_time,
+            // Login check
+            start_login_check,
+            get_login_check,
+            next_login_check,
+            end_login_check,
+            set_login_status,
+            // Settings
+            get_settings,
+            update_settings,
+            // Shortcuts
+            get_shortcuts,
+            set_shortcuts,
+            // Clipboard
+            copy_current_comment,
+            set_auto_copy_comment,
+            get_auto_copy_comment,
             // Data queries
             get_panel_data,
             set_comment_index,
+            // Audit
+            get_audit_log,
+            revert_audit_entry,
+            // Backup
+            export_backup,
+            import_backup,
+            // Security
+            get_security_status,
+            set_passphrase,
+            remove_passphrase,
+            unlock_app,
+            lock_app,
+            // Diagnostics
+            get_recent_logs,
+            copy_diagnostics,
             // Test
             test_command,
         ])
@@ -0,0 +1,242 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// Directory inside the app data directory that holds the log files
+pub const LOG_DIR: &str = "logs";
+/// Name of the log file currently written to
+const LOG_FILE: &str = "attache.log";
+/// Size after which the current log file is rotated
+const MAX_LOG_FILE_SIZE: u64 = 1024 * 1024;
+/// Number of rotated log files kept next to the current one
+const MAX_ROTATED_FILES: usize = 4;
+/// Prefix of log targets coming from this crate
+const CRATE_TARGET: &str = "attache_lib";
+
+/// A single structured log line
+///
+/// Log files contain one JSON-encoded entry per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// RFC 3339 timestamp of when the entry was written
+    pub timestamp: String,
+    /// Severity, e.g. `INFO` or `ERROR`
+    pub level: String,
+    /// Module that wrote the entry, e.g. `commands` or `storage`
+    pub module: String,
+    /// The log message
+    pub message: String,
+}
+
+/// Logger writing structured entries to rotating files
+///
+/// Entries from this crate are kept from `Info` up (`Debug` in debug builds),
+/// entries from dependencies only from `Warn` up.
+struct FileLogger {
+    /// Directory containing the log files
+    dir: PathBuf,
+    /// Currently open log file, opened lazily
+    file: Mutex<Option<File>>,
+    /// Most verbose level kept for this crate
+    level: LevelFilter,
+}
+
+impl FileLogger {
+    /// Returns the path of the current log file or of a rotated one
+    fn file_path(dir: &Path, rotation: usize) -> PathBuf {
+        if rotation == 0 {
+            dir.join(LOG_FILE)
+        } else {
+            dir.join(format!("{}.{}", LOG_FILE, rotation))
+        }
+    }
+
+    /// Shifts every log file one rotation back, dropping the oldest one
+    fn rotate(&self) -> std::io::Result<()> {
+        for rotation in (0..MAX_ROTATED_FILES).rev() {
+            let from = Self::file_path(&self.dir, rotation);
+            if from.exists() {
+                fs::rename(&from, Self::file_path(&self.dir, rotation + 1))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends a line to the current log file, rotating it first if it is full
+    fn write_line(&self, file: &mut Option<File>, line: &str) -> std::io::Result<()> {
+        let current_path = Self::file_path(&self.dir, 0);
+
+        let size = fs::metadata(&current_path).map(|m| m.len()).unwrap_or(0);
+        if size + line.len() as u64 > MAX_LOG_FILE_SIZE {
+            *file = None;
+            self.rotate()?;
+        }
+
+        if file.is_none() {
+            *file = Some(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&current_path)?,
+            );
+        }
+
+        if let Some(file) = file.as_mut() {
+            writeln!(file, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        if metadata.target().starts_with(CRATE_TARGET) {
+            metadata.level() <= self.level
+        } else {
+            metadata.level() <= Level::Warn
+        }
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let module = record
+            .target()
+            .strip_prefix(CRATE_TARGET)
+            .map(|m| m.trim_start_matches("::"))
+            .filter(|m| !m.is_empty())
+            .unwrap_or(record.target());
+
+        let entry = LogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            level: record.level().to_string(),
+            module: module.to_string(),
+            message: record.args().to_string(),
+        };
+
+        if cfg!(debug_assertions) {
+            eprintln!("[{}] {}: {}", entry.level, entry.module, entry.message);
+        }
+
+        let Ok(line) = serde_json::to_string(&entry) else {
+            return;
+        };
+
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = self.write_line(&mut file, &line) {
+            eprintln!("Failed to write log file: {}", e);
+        }
+    }
+
+    fn flush(&self) {
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(file) = file.as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
+
+/// Formats an error for a command result and records it in the log
+///
+/// # Arguments
+/// * `context` - What was being attempted, e.g. "Failed to save profiles"
+pub fn log_error<E: std::fmt::Display>(context: &'static str) -> impl FnOnce(E) -> String {
+    move |e| {
+        let message = format!("{}: {}", context, e);
+        log::error!("{}", message);
+        message
+    }
+}
+
+/// Gets the directory the log files are written to
+///
+/// # Arguments
+/// * `app_data_dir` - The app data directory holding the log directory
+pub fn log_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(LOG_DIR)
+}
+
+/// Installs the file logger as the global logger
+///
+/// # Arguments
+/// * `log_dir` - Directory the log files are written to, created if missing
+///
+/// # Errors
+/// Returns an error if the directory cannot be created or a logger is already installed
+pub fn init(log_dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(&log_dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+
+    let level = if cfg!(debug_assertions) {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Info
+    };
+
+    let logger = FileLogger {
+        dir: log_dir,
+        file: Mutex::new(None),
+        level,
+    };
+
+    log::set_boxed_logger(Box::new(logger)).map_err(|e| format!("Failed to set logger: {}", e))?;
+    log::set_max_level(LevelFilter::Debug);
+
+    Ok(())
+}
+
+/// Reads the most recent log entries, newest last
+///
+/// # Arguments
+/// * `log_dir` - Directory containing the log files
+/// * `limit` - Maximum number of entries to return
+/// * `min_level` - Only return entries at least this severe, e.g. `warn`
+/// * `module` - Only return entries whose module contains this text
+///
+/// # Errors
+/// Returns an error if `min_level` is not a valid log level
+pub async fn read_recent(
+    log_dir: &Path,
+    limit: usize,
+    min_level: Option<&str>,
+    module: Option<&str>,
+) -> Result<Vec<LogEntry>, String> {
+    let min_level = min_level
+        .map(Level::from_str)
+        .transpose()
+        .map_err(|_| "Invalid log level".to_string())?;
+
+    let mut entries: Vec<LogEntry> = Vec::new();
+
+    // Oldest rotated file first so entries end up in chronological order
+    for rotation in (0..=MAX_ROTATED_FILES).rev() {
+        let path = FileLogger::file_path(log_dir, rotation);
+        let Ok(data) = tokio::fs::read_to_string(&path).await else {
+            continue;
+        };
+
+        entries.extend(
+            data.lines()
+                .filter_map(|line| serde_json::from_str::<LogEntry>(line).ok())
+                .filter(|entry| match min_level {
+                    Some(min_level) => Level::from_str(&entry.level)
+                        .map(|level| level <= min_level)
+                        .unwrap_or(false),
+                    None => true,
+                })
+                .filter(|entry| match module {
+                    Some(module) => entry.module.contains(module),
+                    None => true,
+                }),
+        );
+    }
+
+    let skip = entries.len().saturating_sub(limit);
+    Ok(entries.split_off(skip))
+}
@@ -0,0 +1,208 @@
+use crate::models::{BriefCase, Profile, SocialMedia};
+use crate::platforms::PlatformRegistry;
+use crate::state::AppState;
+use chrono::Utc;
+use serde::Serialize;
+use uuid::Uuid;
+
+/// One page of a login check: a platform's home page in one profile window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoginCheckStop {
+    /// The profile whose window shows the page
+    pub profile_id: Uuid,
+    /// The platform whose login is checked
+    pub social_media: SocialMedia,
+    /// Page the profile window opens
+    pub home_url: String,
+    /// Accounts on this platform and profile the operator confirms
+    pub briefcase_ids: Vec<Uuid>,
+}
+
+/// A running login check walking through every profile and platform in turn
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginCheck {
+    /// All pages to check, grouped by profile in the user-defined order
+    stops: Vec<LoginCheckStop>,
+    /// Index of the page currently shown
+    position: usize,
+}
+
+/// Where a running login check is, as sent to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct LoginCheckProgress {
+    /// Index of the current page
+    pub position: usize,
+    /// Number of pages in the check
+    pub total: usize,
+    /// The current page
+    pub stop: LoginCheckStop,
+    /// Whether the page is in a different profile than the previous one
+    pub should_change_profile: bool,
+}
+
+impl LoginCheck {
+    /// Plans a login check over the available accounts
+    ///
+    /// Each profile is opened once and shows the platforms it has accounts on in
+    /// registry order. Disabled and paused accounts are left out like in the
+    /// execution plan, as are accounts on platforms missing from the registry,
+    /// which have no home page.
+    ///
+    /// # Arguments
+    /// * `profiles` - All profiles, used for ordering; accounts of unknown profiles are left out
+    /// * `briefcases` - All accounts
+    /// * `platforms` - The platforms providing the home pages
+    pub fn new(
+        profiles: &[Profile],
+        briefcases: &[BriefCase],
+        platforms: &PlatformRegistry,
+    ) -> Self {
+        let mut ordered: Vec<&Profile> = profiles.iter().collect();
+        ordered.sort_by_key(|p| p.sort_order);
+
+        let now = Utc::now();
+        let stops = ordered
+            .into_iter()
+            .flat_map(|profile| {
+                platforms.all().iter().filter_map(|platform| {
+                    let briefcase_ids: Vec<Uuid> = briefcases
+                        .iter()
+                        .filter(|b| {
+                            b.unavailability(now).is_none()
+                                && b.profile_id == profile.profile_id
+                                && b.social_media == platform.id
+                        })
+                        .map(|b| b.id)
+                        .collect();
+
+                    (!briefcase_ids.is_empty()).then(|| LoginCheckStop {
+                        profile_id: profile.profile_id,
+                        social_media: platform.id.clone(),
+                        home_url: platform.home_url.clone(),
+                        briefcase_ids,
+                    })
+                })
+            })
+            .collect();
+
+        Self { stops, position: 0 }
+    }
+
+    /// Gets all pages of the check in order
+    pub fn stops(&self) -> &[LoginCheckStop] {
+        &self.stops
+    }
+
+    /// Describes the current page, None if the check has no pages
+    pub fn progress(&self) -> Option<LoginCheckProgress> {
+        let stop = self.stops.get(self.position)?;
+        let should_change_profile = self
+            .position
+            .checked_sub(1)
+            .and_then(|previous| self.stops.get(previous))
+            .is_none_or(|previous| previous.profile_id != stop.profile_id);
+
+        Some(LoginCheckProgress {
+            position: self.position,
+            total: self.stops.len(),
+            stop: stop.clone(),
+            should_change_profile,
+        })
+    }
+
+    /// Moves to the next page
+    ///
+    /// # Returns
+    /// false if the current page was the last one
+    pub fn advance(&mut self) -> bool {
+        if self.position + 1 >= self.stops.len() {
+            return false;
+        }
+        self.position += 1;
+        true
+    }
+}
+
+/// Starts a login check over all available accounts
+///
+/// # Returns
+/// The first page to show
+///
+/// # Errors
+/// Returns an error if the app is locked, automation is running or no
+/// available account can be checked
+pub async fn start(state: &AppState) -> Result<LoginCheckProgress, String> {
+    if state.is_locked().await {
+        return Err("Unlock the app before checking logins".to_string());
+    }
+    if state.is_running().await {
+        return Err("Stop automation before checking logins".to_string());
+    }
+
+    let check = LoginCheck::new(
+        &state.get_profiles().await,
+        &state.get_brief_cases().await,
+        &state.get_platforms().await,
+    );
+    let progress = check
+        .progress()
+        .ok_or("No available briefcases to check logins for")?;
+
+    log::info!("Checking logins on {} pages", check.stops().len());
+    state.set_login_check(Some(check)).await;
+    Ok(progress)
+}
+
+/// Gets the current page of the running login check
+pub async fn current(state: &AppState) -> Option<LoginCheckProgress> {
+    state.get_login_check().await?.progress()
+}
+
+/// Moves the running login check to its next page
+///
+/// The check ends after its last page.
+///
+/// # Returns
+/// The next page to show, None if the check is done
+///
+/// # Errors
+/// Returns an error if no login check is running
+pub async fn next(state: &AppState) -> Result<Option<LoginCheckProgress>, String> {
+    let mut check = state
+        .get_login_check()
+        .await
+        .ok_or("No login check is running")?;
+
+    if !check.advance() {
+        end(state).await;
+        return Ok(None);
+    }
+
+    let progress = check.progress();
+    state.set_login_check(Some(check)).await;
+    Ok(progress)
+}
+
+/// Checks that an account is on the page the running login check shows
+///
+/// # Errors
+/// Returns an error if no login check is running or the account is not
+/// confirmed on its current page
+pub async fn ensure_on_current_page(state: &AppState, briefcase_id: Uuid) -> Result<(), String> {
+    let progress = current(state).await.ok_or("No login check is running")?;
+    if !progress.stop.briefcase_ids.contains(&briefcase_id) {
+        return Err(format!(
+            "Briefcase {} is not checked on the current page",
+            briefcase_id
+        ));
+    }
+    Ok(())
+}
+
+/// Ends the running login check, if any
+pub async fn end(state: &AppState) {
+    if state.get_login_check().await.is_some() {
+        log::info!("Login check ended");
+    }
+    state.set_login_check(None).await;
+}
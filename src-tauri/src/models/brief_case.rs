@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -17,6 +18,47 @@ pub struct BriefCase {
     pub profile_id: Uuid,
     /// The username/display name for this account
     pub user_name: String,
+    /// Whether this account takes part in automation
+    #[serde(default = "default_active")]
+    pub is_active: bool,
+    /// The account is left out of automation until this time, e.g. while rate-limited
+    #[serde(default)]
+    pub paused_until: Option<DateTime<Utc>>,
+    /// Outcome of the last login check, None if the login was never checked
+    #[serde(default)]
+    pub last_verified: Option<LoginVerification>,
+}
+
+/// Accounts saved before the flag existed were all in use
+fn default_active() -> bool {
+    true
+}
+
+/// Why an account is left out of automation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Unavailability {
+    /// The account was disabled
+    Inactive,
+    /// The account is paused until `paused_until`
+    Paused,
+}
+
+/// Whether an account was logged in when its login was checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LoginStatus {
+    /// The platform showed the account as logged in
+    LoggedIn,
+    /// The session had expired and the account has to log in again
+    Expired,
+}
+
+/// Outcome of checking an account's login in its profile window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoginVerification {
+    /// What the operator found
+    pub status: LoginStatus,
+    /// When the login was checked
+    pub verified_at: DateTime<Utc>,
 }
 
 impl BriefCase {
@@ -35,6 +77,32 @@ impl BriefCase {
             social_media,
             profile_id,
             user_name,
+            is_active: true,
+            paused_until: None,
+            last_verified: None,
         }
     }
+
+    /// Checks whether the account can take part in automation at a given time
+    ///
+    /// # Arguments
+    /// * `now` - The time to check against `paused_until`
+    ///
+    /// # Returns
+    /// None if the account is available, otherwise why it is not
+    pub fn unavailability(&self, now: DateTime<Utc>) -> Option<Unavailability> {
+        if !self.is_active {
+            Some(Unavailability::Inactive)
+        } else if self.paused_until.is_some_and(|until| until > now) {
+            Some(Unavailability::Paused)
+        } else {
+            None
+        }
+    }
+
+    /// Checks whether the last login check confirmed the account as logged in
+    pub fn is_login_verified(&self) -> bool {
+        self.last_verified
+            .is_some_and(|verification| verification.status == LoginStatus::LoggedIn)
+    }
 }
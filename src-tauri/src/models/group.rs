@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::brief_case::BriefCase;
+
+/// A named set of BriefCases that tasks can target
+///
+/// Groups let a task address a chosen set of accounts instead of every account
+/// on the task's platform. A group may mix platforms; a task only uses the
+/// members on its own platform.
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+pub struct BriefCaseGroup {
+    /// Unique identifier for this group
+    pub id: Uuid,
+    /// Human-readable name for this group
+    pub name: String,
+    /// IDs of the BriefCases in this group
+    pub briefcase_ids: Vec<Uuid>,
+}
+
+impl BriefCaseGroup {
+    /// Creates a new group with a unique ID
+    ///
+    /// # Arguments
+    /// * `name` - The display name for this group
+    /// * `briefcase_ids` - The BriefCases in this group
+    ///
+    /// # Returns
+    /// A new BriefCaseGroup
+    pub fn new(name: String, briefcase_ids: Vec<Uuid>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            briefcase_ids,
+        }
+    }
+
+    /// Checks the group's name and that every member exists
+    ///
+    /// # Arguments
+    /// * `brief_cases` - All existing BriefCases
+    ///
+    /// # Errors
+    /// Returns a message describing the first invalid value
+    pub fn validate(&self, brief_cases: &[BriefCase]) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Group name cannot be empty".to_string());
+        }
+
+        for (index, id) in self.briefcase_ids.iter().enumerate() {
+            if !brief_cases.iter().any(|bc| bc.id == *id) {
+                return Err(format!(
+                    "Group {} contains unknown briefcase {}",
+                    self.name, id
+                ));
+            }
+            if self.briefcase_ids[..index].contains(id) {
+                return Err(format!(
+                    "Group {} contains briefcase {} twice",
+                    self.name, id
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether a BriefCase belongs to this group
+    pub fn contains(&self, briefcase_id: Uuid) -> bool {
+        self.briefcase_ids.contains(&briefcase_id)
+    }
+}
@@ -1,18 +1,24 @@
-/// Core data models for the attache application
-///
-/// This module defines the fundamental data structures used throughout the application:
-/// - SocialMedia: Enumeration of supported platforms
-/// - Profile: Browser profile that holds multiple user accounts
-/// - BriefCase: Social media user account belonging to a Profile
-/// - Task: A social media post with comments to be posted
+//! Core data models for the attache application
+//!
+//! This module defines the fundamental data structures used throughout the application:
+//! - SocialMedia: ID of a platform defined in the platform registry
+//! - Profile: Browser profile that holds multiple user accounts
+//! - BriefCase: Social media user account belonging to a Profile
+//! - BriefCaseGroup: Named set of BriefCases that tasks can target
+//! - Task: A social media post with comments to be posted
+//! - Session: A recorded automation run and the steps completed in it
 
 pub mod brief_case;
+pub mod group;
 pub mod profile;
+pub mod session;
 pub mod social_media;
 pub mod task;
 
 // Re-export commonly used types for convenience
-pub use brief_case::BriefCase;
+pub use brief_case::{BriefCase, LoginStatus, LoginVerification, Unavailability};
+pub use group::BriefCaseGroup;
 pub use profile::Profile;
+pub use session::{Session, SessionStep};
 pub use social_media::SocialMedia;
 pub use task::Task;
@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use tauri::{Manager, Runtime};
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use uuid::Uuid;
 
+/// Directory inside the app data directory holding one data directory per profile
+pub const PROFILES_DIR: &str = "profiles";
+
 /// Represents a browser profile that can hold multiple social media user accounts (BriefCases)
 ///
 /// A Profile maps to a browser data directory that contains user session data.
@@ -14,6 +16,18 @@ pub struct Profile {
     pub profile_id: Uuid,
     /// Human-readable name for this profile
     pub profile_name: String,
+    /// Free-form notes about what this profile is used for
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Display color as a `#rrggbb` hex string
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Free-form labels for grouping and filtering profiles
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Position in the user-defined profile order, lowest first
+    #[serde(default)]
+    pub sort_order: u32,
 }
 
 impl Profile {
@@ -21,49 +35,72 @@ impl Profile {
     ///
     /// # Arguments
     /// * `profile_name` - The display name for this profile
-    /// * `manager` - The Tauri app manager for accessing the app data directory
+    /// * `app_data_dir` - The app data directory the profile directories live in
     ///
     /// # Returns
     /// A new Profile instance with the directory created on disk
     ///
     /// # Errors
     /// Returns an error if directory creation fails
-    pub async fn new<R: Runtime, M: Manager<R>>(
-        profile_name: String,
-        manager: &M,
-    ) -> Result<Self, std::io::Error> {
-        let profile_id = Uuid::new_v4();
-        
-        // Get the app data directory and create the profile folder
-        let app_data_dir = manager
-            .path()
-            .app_data_dir()
-            .map_err(|_| std::io::Error::new(
-                std::io::ErrorKind::NotFound,
-                "Unable to resolve app data directory"
-            ))?;
-            
-        let profile_path = app_data_dir.join(format!("profiles/{}", profile_id));
-        fs::create_dir_all(&profile_path).await?;
-
-        Ok(Self {
-            profile_id,
+    pub async fn new(profile_name: String, app_data_dir: &Path) -> Result<Self, std::io::Error> {
+        let profile = Self {
+            profile_id: Uuid::new_v4(),
             profile_name,
-        })
+            notes: None,
+            color: None,
+            tags: Vec::new(),
+            sort_order: 0,
+        };
+
+        fs::create_dir_all(profile.get_data_path(app_data_dir)).await?;
+
+        Ok(profile)
     }
 
     /// Returns the file system path to this profile's data directory
     ///
     /// # Arguments
-    /// * `manager` - The Tauri app manager for accessing the app data directory
+    /// * `app_data_dir` - The app data directory the profile directories live in
     ///
     /// # Returns
-    /// The PathBuf to the profile's data directory, or None if the directory cannot be determined
-    pub fn get_data_path<R: Runtime, M: Manager<R>>(&self, manager: &M) -> Option<PathBuf> {
-        manager
-            .path()
-            .app_data_dir()
-            .ok()
-            .map(|dir| dir.join(format!("profiles/{}", self.profile_id)))
+    /// The PathBuf to the profile's data directory
+    pub fn get_data_path(&self, app_data_dir: &Path) -> PathBuf {
+        app_data_dir
+            .join(PROFILES_DIR)
+            .join(self.profile_id.to_string())
+    }
+
+    /// Checks that the profile's metadata is well-formed
+    ///
+    /// # Errors
+    /// Returns a message describing the first invalid value
+    pub fn validate(&self) -> Result<(), String> {
+        if self.profile_name.trim().is_empty() {
+            return Err("Profile name cannot be empty".to_string());
+        }
+
+        if let Some(color) = &self.color {
+            let hex = color.strip_prefix('#').unwrap_or("");
+            if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("Invalid profile color {}, expected #rrggbb", color));
+            }
+        }
+
+        if self.tags.iter().any(|tag| tag.trim().is_empty()) {
+            return Err("Profile tags cannot be empty".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Sorts profiles by their user-defined order and renumbers them from zero
+///
+/// Profiles with equal positions keep their relative order, so profiles saved
+/// before the order existed (all at zero) keep the order they were stored in.
+pub fn normalize_sort_order(profiles: &mut [Profile]) {
+    profiles.sort_by_key(|p| p.sort_order);
+    for (index, profile) in profiles.iter_mut().enumerate() {
+        profile.sort_order = index as u32;
     }
 }
@@ -0,0 +1,116 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::task::Task;
+
+/// A recorded automation run, from `start_automation` until the workspace closes
+///
+/// Sessions and their steps make up the posting history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// Unique identifier for this session
+    pub id: Uuid,
+    /// When the automation was started
+    pub started_at: DateTime<Utc>,
+    /// When the session completed or was closed, None while it is running
+    pub ended_at: Option<DateTime<Utc>>,
+    /// The tasks of this session with the accounts assigned to them, including
+    /// tasks removed while it ran
+    pub tasks: Vec<Task>,
+    /// Number of steps in the execution plan
+    pub total_steps: usize,
+    /// Time spent paused in finished pauses, in milliseconds
+    #[serde(default)]
+    pub paused_ms: i64,
+    /// When the current pause started, None while the session is not paused
+    #[serde(default)]
+    pub paused_at: Option<DateTime<Utc>>,
+}
+
+impl Session {
+    /// Creates a session starting now
+    ///
+    /// # Arguments
+    /// * `tasks` - The tasks of this session
+    /// * `total_steps` - Number of steps in the execution plan
+    ///
+    /// # Returns
+    /// A new running Session
+    pub fn new(tasks: Vec<Task>, total_steps: usize) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            started_at: Utc::now(),
+            ended_at: None,
+            tasks,
+            total_steps,
+            paused_ms: 0,
+            paused_at: None,
+        }
+    }
+
+    /// Checks if the session is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Starts a pause
+    ///
+    /// # Returns
+    /// false if the session was already paused
+    pub fn pause(&mut self, now: DateTime<Utc>) -> bool {
+        if self.is_paused() {
+            return false;
+        }
+        self.paused_at = Some(now);
+        true
+    }
+
+    /// Ends the current pause and adds its length to the paused time
+    ///
+    /// # Returns
+    /// false if the session was not paused
+    pub fn resume(&mut self, now: DateTime<Utc>) -> bool {
+        let Some(paused_at) = self.paused_at.take() else {
+            return false;
+        };
+        self.paused_ms += (now - paused_at).num_milliseconds().max(0);
+        true
+    }
+
+    /// Gets the time spent paused, including a pause still in progress
+    pub fn paused_duration(&self, now: DateTime<Utc>) -> Duration {
+        let current = self
+            .paused_at
+            .map(|paused_at| (now - paused_at).max(Duration::zero()))
+            .unwrap_or_default();
+        Duration::milliseconds(self.paused_ms) + current
+    }
+
+    /// Gets the time spent working, from the start until the end or `now`, minus the pauses
+    pub fn active_duration(&self, now: DateTime<Utc>) -> Duration {
+        let end = self.ended_at.unwrap_or(now);
+        ((end - self.started_at) - self.paused_duration(end)).max(Duration::zero())
+    }
+}
+
+/// A step the operator completed during a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionStep {
+    /// The session this step belongs to
+    pub session_id: Uuid,
+    /// Position of the step in the execution order
+    pub step_index: usize,
+    /// Index of the task in the session's task list
+    pub task_index: usize,
+    /// The BriefCase that posted on this step
+    pub briefcase_id: Uuid,
+    /// The profile whose window displayed this step
+    pub profile_id: Uuid,
+    /// The URL of the social media post
+    pub link: String,
+    /// The comment assigned to this step
+    pub comment: Option<String>,
+    /// When the step was completed
+    pub completed_at: DateTime<Utc>,
+}
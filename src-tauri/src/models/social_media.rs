@@ -1,23 +1,47 @@
-/// Represents a social media platform where tasks can be executed
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
-pub enum SocialMedia {
-    /// YouTube platform
-    Youtube,
-    /// X (formerly Twitter) platform
-    X,
-    /// Instagram platform
-    Instagram,
-    /// Facebook platform
-    Facebook,
+use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Cow;
+
+/// Identifies the social media platform a task or account is for
+///
+/// Platforms are defined as data in the `platforms` registry; this is only the
+/// ID they are known by. IDs are lowercase, so data written while platforms were
+/// a closed enum ("Youtube", "X", ...) reads back as the built-in IDs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(transparent)]
+pub struct SocialMedia(Cow<'static, str>);
+
+impl SocialMedia {
+    /// The built-in YouTube platform
+    pub const YOUTUBE: Self = Self(Cow::Borrowed("youtube"));
+    /// The built-in X (formerly Twitter) platform
+    pub const X: Self = Self(Cow::Borrowed("x"));
+    /// The built-in Instagram platform
+    pub const INSTAGRAM: Self = Self(Cow::Borrowed("instagram"));
+    /// The built-in Facebook platform
+    pub const FACEBOOK: Self = Self(Cow::Borrowed("facebook"));
+
+    /// Creates a platform ID, normalizing it to trimmed lowercase
+    ///
+    /// # Arguments
+    /// * `id` - The platform ID in any case
+    pub fn new(id: &str) -> Self {
+        Self(Cow::Owned(id.trim().to_lowercase()))
+    }
+
+    /// Gets the ID as a string
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for SocialMedia {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|id| Self::new(&id))
+    }
 }
 
 impl std::fmt::Display for SocialMedia {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            SocialMedia::Youtube => write!(f, "Youtube"),
-            SocialMedia::X => write!(f, "X"),
-            SocialMedia::Instagram => write!(f, "Instagram"),
-            SocialMedia::Facebook => write!(f, "Facebook"),
-        }
+        f.write_str(&self.0)
     }
 }
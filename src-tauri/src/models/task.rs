@@ -1,7 +1,9 @@
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use super::brief_case::BriefCase;
+use super::group::BriefCaseGroup;
 use super::social_media::SocialMedia;
 
 /// Represents a task that requires comments to be posted on a social media post
@@ -9,7 +11,7 @@ use super::social_media::SocialMedia;
 /// A Task consists of a link to a social media post and comments that need to be posted
 /// from different user accounts (BriefCases). When a Task is created, it automatically
 /// finds and assigns all BriefCases that match its social media platform.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Task {
     /// Unique identifier for this task
     pub task_id: Uuid,
@@ -23,19 +25,24 @@ pub struct Task {
     pub related_brief_cases: Vec<BriefCase>,
     /// Current position in the comments array
     pub comment_index: usize,
+    /// Group whose members post on this task instead of every account on the platform
+    #[serde(default)]
+    pub target_group_id: Option<Uuid>,
 }
 
 impl Task {
     /// Creates a new Task and assigns relevant BriefCases based on social media platform
     ///
     /// This constructor automatically finds all BriefCases that match the specified
-    /// social_media platform and assigns them to the task.
+    /// social_media platform and assigns them to the task. When a target group is
+    /// given only its members are assigned. Disabled and paused BriefCases are left out.
     ///
     /// # Arguments
     /// * `link` - URL of the social media post
     /// * `comments` - List of comment messages to post (formatted)
     /// * `social_media` - The platform this task targets
     /// * `all_brief_cases` - All available BriefCases in the system
+    /// * `target_group` - Group to take the BriefCases from, None for all on the platform
     ///
     /// # Returns
     /// A new Task with matching BriefCases assigned
@@ -44,11 +51,15 @@ impl Task {
         comments: Vec<String>,
         social_media: SocialMedia,
         all_brief_cases: &[BriefCase],
+        target_group: Option<&BriefCaseGroup>,
     ) -> Self {
-        // Filter and collect all BriefCases matching this task's social media platform
+        // Filter and collect all available BriefCases matching this task's social media platform
+        let now = Utc::now();
         let related_brief_cases: Vec<BriefCase> = all_brief_cases
             .iter()
             .filter(|bc| bc.social_media == social_media)
+            .filter(|bc| bc.unavailability(now).is_none())
+            .filter(|bc| target_group.is_none_or(|group| group.contains(bc.id)))
             .cloned()
             .collect();
 
@@ -59,6 +70,7 @@ impl Task {
             social_media,
             related_brief_cases,
             comment_index: 0,
+            target_group_id: target_group.map(|group| group.id),
         }
     }
 
@@ -78,8 +90,7 @@ impl Task {
             .filter(|line| !line.is_empty())
             .filter_map(|line| {
                 // Remove bullet points (*, -, •) from the start
-                let mut cleaned =
-                    line.trim_start_matches(|c: char| c == '*' || c == '-' || c == '•');
+                let mut cleaned = line.trim_start_matches(['*', '-', '•']);
 
                 // Handle numbered lists (e.g., "1. ", "20) ")
                 // Find first character that isn't a digit, dot, parenthesis, or space
@@ -0,0 +1,277 @@
+use crate::models::SocialMedia;
+use crate::storage::{StorageBackend, PLATFORMS_FILE};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// How links to posts on a platform are cleaned up before they are visited
+///
+/// Tracking parameters and mobile hosts make the same post look like different
+/// links, so tasks are compared and opened by their canonical link.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LinkRules {
+    /// Host links are moved to when they use one of `aliases`
+    pub host: Option<String>,
+    /// Hosts rewritten to `host`, e.g. the mobile site
+    pub aliases: Vec<String>,
+    /// Query parameters that identify the post; all others are dropped.
+    /// None keeps every parameter
+    pub keep_query: Option<Vec<String>>,
+}
+
+/// A social media platform tasks and accounts can be created for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Platform {
+    /// ID stored on tasks and accounts
+    pub id: SocialMedia,
+    /// Name shown to the user
+    pub name: String,
+    /// Hosts of the platform's links; each also matches its subdomains
+    pub hosts: Vec<String>,
+    /// Page opened to log in or check a login
+    pub home_url: String,
+    /// How links to posts are cleaned up
+    #[serde(default)]
+    pub link_rules: LinkRules,
+}
+
+impl Platform {
+    /// Checks that the platform can be used
+    ///
+    /// # Errors
+    /// Returns a message describing the first invalid value
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.as_str().is_empty() {
+            return Err("Platform ID cannot be empty".to_string());
+        }
+        if self.name.trim().is_empty() {
+            return Err(format!("Platform {} has no name", self.id));
+        }
+        if self.hosts.iter().all(|host| host.trim().is_empty()) {
+            return Err(format!("Platform {} has no hosts", self.id));
+        }
+        if !Url::parse(&self.home_url).is_ok_and(|url| url.scheme() == "https") {
+            return Err(format!(
+                "Platform {} has an invalid home URL {}, expected https://",
+                self.id, self.home_url
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether a link points to this platform
+    pub fn matches(&self, link: &str) -> bool {
+        let Some(host) = Url::parse(link.trim())
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_lowercase))
+        else {
+            return false;
+        };
+
+        self.hosts.iter().any(|pattern| {
+            let pattern = pattern.trim().to_lowercase();
+            host == pattern || host.ends_with(&format!(".{}", pattern))
+        })
+    }
+
+    /// Cleans up a link to a post on this platform
+    ///
+    /// Moves alias hosts to the canonical host, drops the fragment, a trailing
+    /// slash and, if the platform lists the parameters to keep, all other query
+    /// parameters. Links that
+    /// cannot be parsed are returned trimmed but otherwise unchanged.
+    pub fn canonicalize(&self, link: &str) -> String {
+        let link = link.trim();
+        let Ok(mut url) = Url::parse(link) else {
+            return link.to_string();
+        };
+
+        let rules = &self.link_rules;
+        if let Some(host) = &rules.host {
+            let is_alias = url.host_str().is_some_and(|current| {
+                rules
+                    .aliases
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(current))
+            });
+            if is_alias && url.set_host(Some(host)).is_err() {
+                return link.to_string();
+            }
+        }
+
+        if let Some(keep_query) = &rules.keep_query {
+            let kept: Vec<(String, String)> = url
+                .query_pairs()
+                .filter(|(key, _)| keep_query.iter().any(|keep| keep == key))
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .collect();
+            if kept.is_empty() {
+                url.set_query(None);
+            } else {
+                url.query_pairs_mut().clear().extend_pairs(kept);
+            }
+        }
+        url.set_fragment(None);
+
+        let path = url.path().to_string();
+        if path.len() > 1 && path.ends_with('/') {
+            url.set_path(path.trim_end_matches('/'));
+        }
+
+        url.to_string()
+    }
+}
+
+/// The platforms known to the app
+///
+/// Starts with the built-in platforms; `platforms.json` in the config
+/// directory can add platforms or replace built-in ones by using their ID.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlatformRegistry {
+    /// All platforms, built-in ones first
+    platforms: Vec<Platform>,
+}
+
+impl Default for PlatformRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl PlatformRegistry {
+    /// Creates a registry holding only the built-in platforms
+    pub fn builtin() -> Self {
+        let platform = |id: SocialMedia,
+                        name: &str,
+                        hosts: &[&str],
+                        home_url: &str,
+                        host: &str,
+                        aliases: &[&str],
+                        keep_query: &[&str]| Platform {
+            id,
+            name: name.to_string(),
+            hosts: hosts.iter().map(|h| h.to_string()).collect(),
+            home_url: home_url.to_string(),
+            link_rules: LinkRules {
+                host: Some(host.to_string()),
+                aliases: aliases.iter().map(|a| a.to_string()).collect(),
+                keep_query: Some(keep_query.iter().map(|k| k.to_string()).collect()),
+            },
+        };
+
+        Self {
+            platforms: vec![
+                platform(
+                    SocialMedia::YOUTUBE,
+                    "YouTube",
+                    &["youtube.com", "youtu.be"],
+                    "https://www.youtube.com/",
+                    "www.youtube.com",
+                    &["youtube.com", "m.youtube.com"],
+                    &["v", "lc"],
+                ),
+                platform(
+                    SocialMedia::X,
+                    "X",
+                    &["x.com", "twitter.com"],
+                    "https://x.com/",
+                    "x.com",
+                    &[
+                        "www.x.com",
+                        "mobile.x.com",
+                        "twitter.com",
+                        "www.twitter.com",
+                        "mobile.twitter.com",
+                    ],
+                    &[],
+                ),
+                platform(
+                    SocialMedia::INSTAGRAM,
+                    "Instagram",
+                    &["instagram.com"],
+                    "https://www.instagram.com/",
+                    "www.instagram.com",
+                    &["instagram.com", "m.instagram.com"],
+                    &[],
+                ),
+                platform(
+                    SocialMedia::FACEBOOK,
+                    "Facebook",
+                    &["facebook.com", "fb.com", "fb.watch"],
+                    "https://www.facebook.com/",
+                    "www.facebook.com",
+                    &["facebook.com", "m.facebook.com", "mobile.facebook.com"],
+                    &["v", "story_fbid", "fbid", "id"],
+                ),
+            ],
+        }
+    }
+
+    /// Creates a registry from the built-in platforms and user-defined ones
+    ///
+    /// A user-defined platform replaces the built-in one with the same ID.
+    /// Invalid platforms are logged and left out.
+    ///
+    /// # Arguments
+    /// * `custom` - The user-defined platforms
+    pub fn with_custom(custom: Vec<Platform>) -> Self {
+        let mut registry = Self::builtin();
+
+        for platform in custom {
+            if let Err(e) = platform.validate() {
+                log::warn!("Ignoring platform from {}: {}", PLATFORMS_FILE, e);
+                continue;
+            }
+
+            match registry.platforms.iter_mut().find(|p| p.id == platform.id) {
+                Some(existing) => *existing = platform,
+                None => registry.platforms.push(platform),
+            }
+        }
+
+        registry
+    }
+
+    /// Loads the built-in platforms and the user-defined ones in storage
+    ///
+    /// Without user-defined platforms only the built-in ones are used. If they
+    /// cannot be read, the error is logged and the built-in ones are used.
+    ///
+    /// # Arguments
+    /// * `storage` - The backend holding the user-defined platforms
+    pub async fn load(storage: &dyn StorageBackend) -> Self {
+        match storage.read_platforms().await {
+            Ok(custom) if custom.is_empty() => Self::builtin(),
+            Ok(custom) => {
+                log::info!("Loaded {} platforms from {}", custom.len(), PLATFORMS_FILE);
+                Self::with_custom(custom)
+            }
+            Err(e) => {
+                log::error!("Failed to read {}: {}", PLATFORMS_FILE, e);
+                Self::builtin()
+            }
+        }
+    }
+
+    /// Gets all platforms, built-in ones first
+    pub fn all(&self) -> &[Platform] {
+        &self.platforms
+    }
+
+    /// Finds a platform by its ID
+    pub fn get(&self, id: &SocialMedia) -> Option<&Platform> {
+        self.platforms.iter().find(|p| p.id == *id)
+    }
+
+    /// Finds the platform a link points to
+    pub fn detect(&self, link: &str) -> Option<&Platform> {
+        self.platforms.iter().find(|p| p.matches(link))
+    }
+
+    /// Gets the name of a platform, or its ID if it is not known
+    pub fn name(&self, id: &SocialMedia) -> String {
+        self.get(id)
+            .map_or_else(|| id.to_string(), |platform| platform.name.clone())
+    }
+}
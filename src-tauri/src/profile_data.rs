@@ -0,0 +1,318 @@
+use crate::models::profile::PROFILES_DIR;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+use walkdir::WalkDir;
+
+/// Webview directories holding disposable caches
+///
+/// Covers the layouts of WebView2 (Chromium) and WebKit. Cookies, local storage
+/// and IndexedDB live outside these directories, so logins survive clearing them.
+const CACHE_DIR_NAMES: &[&str] = &[
+    "Cache",
+    "Code Cache",
+    "GPUCache",
+    "DawnCache",
+    "DawnGraphiteCache",
+    "DawnWebGPUCache",
+    "GrShaderCache",
+    "GraphiteDawnCache",
+    "ShaderCache",
+    "ScriptCache",
+    "WebKitCache",
+];
+
+/// Webview files and directories holding logins and site storage
+///
+/// Left out when a profile is cloned without its session so the clone starts
+/// logged out while keeping extensions and site preferences.
+const SESSION_DATA_NAMES: &[&str] = &[
+    "Cookies",
+    "Cookies-journal",
+    "Network",
+    "Local Storage",
+    "Session Storage",
+    "IndexedDB",
+    "Service Worker",
+    "WebStorage",
+    "databases",
+    "cookies.sqlite",
+    "localstorage",
+    "indexeddb",
+];
+
+/// Disk usage of a single profile data directory
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileDiskUsage {
+    /// ID of the profile owning the directory
+    pub profile_id: Uuid,
+    /// Total size of the directory in bytes
+    pub size_bytes: u64,
+    /// Part of the size taken by caches that can be cleared without logging out
+    pub cache_bytes: u64,
+}
+
+/// A profile data directory whose profile no longer exists
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanProfileDir {
+    /// The UUID the directory is named after
+    pub profile_id: Uuid,
+    /// Full path of the directory
+    pub path: PathBuf,
+    /// Size of the directory in bytes
+    pub size_bytes: u64,
+}
+
+/// Resolves the directory containing all profile data directories
+///
+/// # Arguments
+/// * `app_data_dir` - The app data directory the profile directories live in
+pub fn profiles_root(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(PROFILES_DIR)
+}
+
+/// Returns whether a directory entry is one of the webview cache directories
+pub fn is_cache_dir(entry: &walkdir::DirEntry) -> bool {
+    entry.file_type().is_dir()
+        && entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| CACHE_DIR_NAMES.contains(&name))
+}
+
+/// Sums the size of all files below a directory, ignoring unreadable entries
+fn dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Lists the cache directories below a profile data directory
+///
+/// Unreadable entries are logged and skipped so the rest of the directory is still searched.
+fn cache_dirs(path: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut walker = WalkDir::new(path).min_depth(1).into_iter();
+
+    while let Some(entry) = walker.next() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                log::warn!("Skipping unreadable entry in {}: {}", path.display(), e);
+                continue;
+            }
+        };
+
+        if is_cache_dir(&entry) {
+            dirs.push(entry.into_path());
+            walker.skip_current_dir();
+        }
+    }
+
+    dirs
+}
+
+/// Measures a profile data directory and the caches inside it
+///
+/// Runs on a blocking thread since large profiles hold many thousands of files.
+///
+/// # Arguments
+/// * `profile_id` - ID of the profile owning the directory
+/// * `path` - The profile data directory
+pub async fn disk_usage(profile_id: Uuid, path: PathBuf) -> Result<ProfileDiskUsage, String> {
+    tokio::task::spawn_blocking(move || {
+        let cache_bytes = cache_dirs(&path).iter().map(|dir| dir_size(dir)).sum();
+
+        ProfileDiskUsage {
+            profile_id,
+            size_bytes: dir_size(&path),
+            cache_bytes,
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to measure profile directory: {}", e))
+}
+
+/// Deletes the cache directories of a profile, keeping cookies and storage
+///
+/// # Arguments
+/// * `path` - The profile data directory
+///
+/// # Returns
+/// The number of bytes freed
+pub async fn clear_cache(path: PathBuf) -> Result<u64, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut freed = 0;
+
+        for dir in cache_dirs(&path) {
+            let size = dir_size(&dir);
+            std::fs::remove_dir_all(&dir)
+                .map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+            freed += size;
+        }
+
+        Ok(freed)
+    })
+    .await
+    .map_err(|e| format!("Failed to clear profile cache: {}", e))?
+}
+
+/// Deletes everything in a profile data directory and recreates it empty
+///
+/// # Arguments
+/// * `path` - The profile data directory
+///
+/// # Returns
+/// The number of bytes freed
+pub async fn wipe(path: PathBuf) -> Result<u64, String> {
+    let freed = tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || dir_size(&path)
+    })
+    .await
+    .map_err(|e| format!("Failed to measure profile directory: {}", e))?;
+
+    if path.exists() {
+        tokio::fs::remove_dir_all(&path)
+            .await
+            .map_err(|e| format!("Failed to remove profile directory: {}", e))?;
+    }
+    tokio::fs::create_dir_all(&path)
+        .await
+        .map_err(|e| format!("Failed to recreate profile directory: {}", e))?;
+
+    Ok(freed)
+}
+
+/// Finds profile data directories that belong to no known profile
+///
+/// Only directories named after a UUID are considered, so unrelated files in the
+/// profiles directory are never reported.
+///
+/// # Arguments
+/// * `root` - The directory containing all profile data directories
+/// * `known` - IDs of the existing profiles
+pub async fn find_orphans(
+    root: PathBuf,
+    known: HashSet<Uuid>,
+) -> Result<Vec<OrphanProfileDir>, String> {
+    tokio::task::spawn_blocking(move || {
+        let Ok(entries) = std::fs::read_dir(&root) else {
+            return Vec::new();
+        };
+
+        entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+            .filter_map(|entry| {
+                let profile_id = Uuid::parse_str(entry.file_name().to_str()?).ok()?;
+                (!known.contains(&profile_id)).then(|| OrphanProfileDir {
+                    profile_id,
+                    size_bytes: dir_size(&entry.path()),
+                    path: entry.path(),
+                })
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Failed to scan profile directories: {}", e))
+}
+
+/// Deletes the profile data directories that belong to no known profile
+///
+/// Only real directories directly inside `root` are removed; symbolic links and
+/// anything resolving outside of it are logged and left alone.
+///
+/// # Arguments
+/// * `root` - The directory containing all profile data directories
+/// * `known` - IDs of the existing profiles
+///
+/// # Returns
+/// The number of bytes freed
+pub async fn delete_orphans(root: PathBuf, known: HashSet<Uuid>) -> Result<u64, String> {
+    let orphans = find_orphans(root.clone(), known).await?;
+    if orphans.is_empty() {
+        return Ok(0);
+    }
+
+    let root = tokio::fs::canonicalize(&root)
+        .await
+        .map_err(|e| format!("Failed to resolve profiles directory: {}", e))?;
+    let mut freed = 0;
+
+    for orphan in orphans {
+        let is_dir = tokio::fs::symlink_metadata(&orphan.path)
+            .await
+            .is_ok_and(|metadata| metadata.is_dir());
+        let resolved = tokio::fs::canonicalize(&orphan.path).await.ok();
+        let Some(resolved) = resolved.filter(|path| is_dir && path.parent() == Some(&root)) else {
+            log::warn!(
+                "Not deleting {}, it is not a profile data directory",
+                orphan.path.display()
+            );
+            continue;
+        };
+
+        tokio::fs::remove_dir_all(&resolved)
+            .await
+            .map_err(|e| format!("Failed to delete {}: {}", resolved.display(), e))?;
+        log::info!("Deleted orphan profile directory {}", orphan.profile_id);
+        freed += orphan.size_bytes;
+    }
+
+    Ok(freed)
+}
+
+/// Copies a profile data directory into another one
+///
+/// Caches are never copied. Runs on a blocking thread since large profiles hold
+/// many thousands of files.
+///
+/// # Arguments
+/// * `source` - The profile data directory to copy
+/// * `target` - The directory to copy into, created if missing
+/// * `include_session` - Whether cookies and site storage are copied too
+pub async fn copy(source: PathBuf, target: PathBuf, include_session: bool) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        std::fs::create_dir_all(&target)
+            .map_err(|e| format!("Failed to create profile directory: {}", e))?;
+
+        let skip = |entry: &walkdir::DirEntry| {
+            is_cache_dir(entry)
+                || (!include_session
+                    && entry
+                        .file_name()
+                        .to_str()
+                        .is_some_and(|name| SESSION_DATA_NAMES.contains(&name)))
+        };
+
+        for entry in WalkDir::new(&source)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|entry| !skip(entry))
+        {
+            let entry = entry.map_err(|e| format!("Failed to read profile directory: {}", e))?;
+            let relative = entry
+                .path()
+                .strip_prefix(&source)
+                .map_err(|e| format!("Failed to copy profile directory: {}", e))?;
+            let destination = target.join(relative);
+
+            if entry.file_type().is_dir() {
+                std::fs::create_dir_all(&destination)
+            } else {
+                std::fs::copy(entry.path(), &destination).map(|_| ())
+            }
+            .map_err(|e| format!("Failed to copy {}: {}", relative.display(), e))?;
+        }
+
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Failed to copy profile directory: {}", e))?
+}
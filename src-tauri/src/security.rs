@@ -0,0 +1,301 @@
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use thiserror::Error;
+
+/// Header of an encrypted data file, followed by the base64-encoded payload
+const FILE_MAGIC: &str = "ATTACHE-ENC1\n";
+/// Prefix of an encrypted line in an append-only file
+const LINE_PREFIX: &str = "enc1:";
+/// Plaintext sealed into the verifier to check passphrases against
+const VERIFIER_PLAINTEXT: &[u8] = b"attache";
+/// Shortest passphrase accepted
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+/// Length of the AES-GCM nonce in bytes
+const NONCE_LEN: usize = 12;
+
+/// Errors that can occur while encrypting or decrypting data
+#[derive(Debug, Error)]
+pub enum SecurityError {
+    /// Encrypted data was accessed before the app was unlocked
+    #[error("The app is locked")]
+    Locked,
+
+    /// The passphrase does not match the configured one
+    #[error("Wrong passphrase")]
+    WrongPassphrase,
+
+    /// Encrypted data could not be decoded or authenticated
+    #[error("Encrypted data is corrupted")]
+    Corrupted,
+
+    /// The key could not be derived from the passphrase
+    #[error("Key derivation failed: {0}")]
+    KeyDerivation(String),
+}
+
+/// Passphrase and encryption configuration, persisted as `security.json`
+///
+/// The passphrase itself is never stored; a verifier sealed with the derived key
+/// is used to check it instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityConfig {
+    /// Base64-encoded salt for the key derivation
+    pub salt: String,
+    /// Argon2id memory cost in KiB
+    pub memory_kib: u32,
+    /// Argon2id number of passes
+    pub iterations: u32,
+    /// Argon2id degree of parallelism
+    pub parallelism: u32,
+    /// Known plaintext sealed with the derived key, used to verify passphrases
+    pub verifier: String,
+    /// Whether the data files are encrypted with the derived key
+    pub encrypt_data: bool,
+}
+
+/// Lock and encryption state reported to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct SecurityStatus {
+    /// Whether a passphrase is configured
+    pub passphrase_set: bool,
+    /// Whether the data files are encrypted
+    pub encrypted: bool,
+    /// Whether the passphrase must be entered before profile windows can open
+    pub locked: bool,
+}
+
+impl SecurityConfig {
+    /// Creates a configuration for a new passphrase
+    ///
+    /// # Arguments
+    /// * `passphrase` - The new passphrase
+    /// * `encrypt_data` - Whether the data files are encrypted
+    ///
+    /// # Returns
+    /// The configuration and the key derived from the passphrase
+    pub fn create(passphrase: &str, encrypt_data: bool) -> Result<(Self, [u8; 32]), SecurityError> {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+
+        let params = Params::default();
+        let mut config = Self {
+            salt: BASE64.encode(salt),
+            memory_kib: params.m_cost(),
+            iterations: params.t_cost(),
+            parallelism: params.p_cost(),
+            verifier: String::new(),
+            encrypt_data,
+        };
+
+        let key = config.derive_key(passphrase)?;
+        config.verifier = seal_bytes(&Aes256Gcm::new(&key.into()), VERIFIER_PLAINTEXT)?;
+
+        Ok((config, key))
+    }
+
+    /// Derives the key for a passphrase and checks it against the verifier
+    ///
+    /// # Errors
+    /// Returns `WrongPassphrase` if the passphrase does not match
+    pub fn unlock(&self, passphrase: &str) -> Result<[u8; 32], SecurityError> {
+        let key = self.derive_key(passphrase)?;
+
+        match open_bytes(&Aes256Gcm::new(&key.into()), &self.verifier) {
+            Ok(plaintext) if plaintext == VERIFIER_PLAINTEXT => Ok(key),
+            _ => Err(SecurityError::WrongPassphrase),
+        }
+    }
+
+    /// Derives the 256-bit key for a passphrase with Argon2id
+    fn derive_key(&self, passphrase: &str) -> Result<[u8; 32], SecurityError> {
+        let salt = BASE64
+            .decode(&self.salt)
+            .map_err(|_| SecurityError::Corrupted)?;
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| SecurityError::KeyDerivation(e.to_string()))?;
+
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| SecurityError::KeyDerivation(e.to_string()))?;
+
+        Ok(key)
+    }
+}
+
+/// Checks that a new passphrase is acceptable
+pub fn validate_passphrase(passphrase: &str) -> Result<(), String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!(
+            "Passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LEN
+        ));
+    }
+    Ok(())
+}
+
+/// Encrypts bytes into a base64 string of nonce and ciphertext
+fn seal_bytes(cipher: &Aes256Gcm, plaintext: &[u8]) -> Result<String, SecurityError> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| SecurityError::Corrupted)?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend(ciphertext);
+    Ok(BASE64.encode(payload))
+}
+
+/// Decrypts a base64 string of nonce and ciphertext
+fn open_bytes(cipher: &Aes256Gcm, encoded: &str) -> Result<Vec<u8>, SecurityError> {
+    let payload = BASE64
+        .decode(encoded.trim())
+        .map_err(|_| SecurityError::Corrupted)?;
+    if payload.len() < NONCE_LEN {
+        return Err(SecurityError::Corrupted);
+    }
+
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| SecurityError::Corrupted)
+}
+
+/// Encrypts the contents of a file with the given key instead of the active one
+pub fn seal_file_with(key: &[u8; 32], contents: &str) -> Result<String, SecurityError> {
+    let cipher = Aes256Gcm::new(&(*key).into());
+    Ok(format!(
+        "{}{}",
+        FILE_MAGIC,
+        seal_bytes(&cipher, contents.as_bytes())?
+    ))
+}
+
+/// Decrypts the contents of a file written by `seal_file_with`
+pub fn open_file_with(key: &[u8; 32], data: &str) -> Result<String, SecurityError> {
+    let encoded = data
+        .strip_prefix(FILE_MAGIC)
+        .ok_or(SecurityError::Corrupted)?;
+    let plaintext = open_bytes(&Aes256Gcm::new(&(*key).into()), encoded)?;
+    String::from_utf8(plaintext).map_err(|_| SecurityError::Corrupted)
+}
+
+/// The encryption in effect, taken to switch back if a key change fails
+pub struct ActiveKey {
+    cipher: Option<Aes256Gcm>,
+    required: bool,
+}
+
+/// The key a data store is encrypted with
+///
+/// Each storage backend holds its own, so two stores open in the same process
+/// never read or write with each other's key.
+#[derive(Default)]
+pub struct DataKey {
+    /// Cipher for the data files, set once the app is unlocked with encryption enabled
+    cipher: RwLock<Option<Aes256Gcm>>,
+    /// Whether data files must be encrypted, so nothing is written in plaintext while locked
+    required: AtomicBool,
+}
+
+impl DataKey {
+    /// Sets how data files are encrypted from now on
+    ///
+    /// # Arguments
+    /// * `key` - The key to encrypt with, None to write plaintext
+    /// * `required` - Whether writing must fail while no key is set
+    pub fn activate(&self, key: Option<[u8; 32]>, required: bool) {
+        let cipher = key.map(|key| Aes256Gcm::new(&key.into()));
+        *self.cipher.write().unwrap_or_else(|e| e.into_inner()) = cipher;
+        self.required.store(required, Ordering::SeqCst);
+    }
+
+    /// Gets the encryption currently in effect
+    pub fn active_key(&self) -> ActiveKey {
+        ActiveKey {
+            cipher: self
+                .cipher
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+            required: self.required.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Puts encryption taken with `active_key` back into effect
+    pub fn reactivate(&self, key: ActiveKey) {
+        *self.cipher.write().unwrap_or_else(|e| e.into_inner()) = key.cipher;
+        self.required.store(key.required, Ordering::SeqCst);
+    }
+
+    /// Runs a closure with the active cipher, or None when writing plaintext is allowed
+    fn with_cipher<T>(
+        &self,
+        f: impl FnOnce(Option<&Aes256Gcm>) -> Result<T, SecurityError>,
+    ) -> Result<T, SecurityError> {
+        let cipher = self.cipher.read().unwrap_or_else(|e| e.into_inner());
+        if cipher.is_none() && self.required.load(Ordering::SeqCst) {
+            return Err(SecurityError::Locked);
+        }
+        f(cipher.as_ref())
+    }
+
+    /// Decrypts a payload with the active cipher, failing while locked
+    fn open_text(&self, encoded: &str) -> Result<String, SecurityError> {
+        let cipher = self.cipher.read().unwrap_or_else(|e| e.into_inner());
+        let cipher = cipher.as_ref().ok_or(SecurityError::Locked)?;
+        String::from_utf8(open_bytes(cipher, encoded)?).map_err(|_| SecurityError::Corrupted)
+    }
+
+    /// Prepares the contents of a data file for writing
+    ///
+    /// Returns the contents unchanged when encryption is off.
+    pub fn seal_file(&self, contents: &str) -> Result<String, SecurityError> {
+        self.with_cipher(|cipher| match cipher {
+            Some(cipher) => Ok(format!(
+                "{}{}",
+                FILE_MAGIC,
+                seal_bytes(cipher, contents.as_bytes())?
+            )),
+            None => Ok(contents.to_string()),
+        })
+    }
+
+    /// Reads the contents of a data file, decrypting it if it was encrypted
+    ///
+    /// Plaintext files are returned as they are, so data written before encryption
+    /// was enabled keeps loading.
+    pub fn open_file(&self, data: &str) -> Result<String, SecurityError> {
+        match data.strip_prefix(FILE_MAGIC) {
+            Some(encoded) => self.open_text(encoded),
+            None => Ok(data.to_string()),
+        }
+    }
+
+    /// Prepares a single line of an append-only file for writing
+    pub fn seal_line(&self, line: &str) -> Result<String, SecurityError> {
+        self.with_cipher(|cipher| match cipher {
+            Some(cipher) => Ok(format!(
+                "{}{}",
+                LINE_PREFIX,
+                seal_bytes(cipher, line.as_bytes())?
+            )),
+            None => Ok(line.to_string()),
+        })
+    }
+
+    /// Reads a single line of an append-only file, decrypting it if it was encrypted
+    pub fn open_line(&self, line: &str) -> Result<String, SecurityError> {
+        match line.strip_prefix(LINE_PREFIX) {
+            Some(encoded) => self.open_text(encoded),
+            None => Ok(line.to_string()),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use crate::shortcut_bindings::ShortcutBindings;
+use crate::window_layout::WindowLayout;
+use serde::{Deserialize, Serialize};
+
+/// User-configurable application settings
+///
+/// Persisted as `settings.json` next to `profiles.json`. Missing fields fall back
+/// to their defaults so older settings files keep loading after new options are added.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Whether the current comment is copied to the clipboard on every step
+    pub auto_copy_comment: bool,
+    /// Whether other profile windows are closed when switching profiles
+    pub close_previous_profile_window: bool,
+    /// Whether the profile window is hidden while automation is paused
+    pub hide_window_on_pause: bool,
+    /// Global shortcuts bound to the automation actions
+    pub shortcuts: ShortcutBindings,
+    /// How the profile window and the panel share the screen
+    pub layout: WindowLayout,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            auto_copy_comment: true,
+            close_previous_profile_window: true,
+            hide_window_on_pause: false,
+            shortcuts: ShortcutBindings::default(),
+            layout: WindowLayout::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Checks that the settings can be applied
+    ///
+    /// # Errors
+    /// Returns a message describing the first invalid value
+    pub fn validate(&self) -> Result<(), String> {
+        self.layout.validate()?;
+        self.shortcuts.parse()?;
+        Ok(())
+    }
+
+    /// Repairs values that cannot be applied so loaded settings always validate
+    ///
+    /// The split ratio is clamped into range and shortcuts that do not parse
+    /// fall back to the defaults.
+    pub fn repair(&mut self) {
+        self.layout.clamp();
+
+        if let Err(e) = self.shortcuts.parse() {
+            log::warn!("{}, using the default shortcuts", e);
+            self.shortcuts = ShortcutBindings::default();
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use global_hotkey::hotkey::HotKey;
+use serde::{Deserialize, Serialize};
+
+/// An automation action that can be triggered by a global shortcut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    /// Marks the current step as done and moves to the next one
+    Next,
+    /// Moves back to the previous step
+    Previous,
+    /// Moves to the next step without marking the current one as done
+    Skip,
+    /// Copies the current comment to the clipboard
+    CopyComment,
+    /// Pauses the automation, or resumes it if it is paused
+    Pause,
+}
+
+/// Accelerators bound to each automation action
+///
+/// Accelerators use the format understood by the global shortcut plugin,
+/// e.g. `CommandOrControl+Alt+Right`. An empty string leaves the action unbound.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShortcutBindings {
+    /// Accelerator for moving to the next step
+    pub next: String,
+    /// Accelerator for moving back to the previous step
+    pub previous: String,
+    /// Accelerator for skipping the current step
+    pub skip: String,
+    /// Accelerator for copying the current comment
+    pub copy_comment: String,
+    /// Accelerator for pausing and resuming the automation
+    pub pause: String,
+}
+
+impl Default for ShortcutBindings {
+    fn default() -> Self {
+        Self {
+            next: "CommandOrControl+Alt+Right".to_string(),
+            previous: "CommandOrControl+Alt+Left".to_string(),
+            skip: "CommandOrControl+Alt+Down".to_string(),
+            copy_comment: "CommandOrControl+Alt+C".to_string(),
+            pause: "CommandOrControl+Alt+P".to_string(),
+        }
+    }
+}
+
+impl ShortcutBindings {
+    /// Returns every bound accelerator together with its action
+    fn entries(&self) -> [(ShortcutAction, &str); 5] {
+        [
+            (ShortcutAction::Next, self.next.as_str()),
+            (ShortcutAction::Previous, self.previous.as_str()),
+            (ShortcutAction::Skip, self.skip.as_str()),
+            (ShortcutAction::CopyComment, self.copy_comment.as_str()),
+            (ShortcutAction::Pause, self.pause.as_str()),
+        ]
+    }
+
+    /// Parses all bound accelerators
+    ///
+    /// # Returns
+    /// The parsed shortcuts with their actions, or an error naming the first
+    /// invalid or duplicated accelerator
+    pub fn parse(&self) -> Result<Vec<(ShortcutAction, HotKey)>, String> {
+        let mut parsed: Vec<(ShortcutAction, HotKey)> = Vec::new();
+
+        for (action, accelerator) in self.entries() {
+            if accelerator.trim().is_empty() {
+                continue;
+            }
+
+            let shortcut: HotKey = accelerator
+                .parse()
+                .map_err(|e| format!("Invalid shortcut '{}': {}", accelerator, e))?;
+
+            if parsed.iter().any(|(_, existing)| *existing == shortcut) {
+                return Err(format!("Shortcut '{}' is bound twice", accelerator));
+            }
+
+            parsed.push((action, shortcut));
+        }
+
+        Ok(parsed)
+    }
+
+    /// Finds the action bound to a pressed shortcut
+    pub fn action_for(&self, shortcut: &HotKey) -> Option<ShortcutAction> {
+        self.parse()
+            .ok()?
+            .into_iter()
+            .find(|(_, bound)| bound == shortcut)
+            .map(|(action, _)| action)
+    }
+}
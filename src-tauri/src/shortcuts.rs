@@ -0,0 +1,95 @@
+use crate::commands::{
+    copy_current_comment, next_execution, pause_automation, previous_execution, resume_automation,
+    skip_execution,
+};
+use crate::shortcut_bindings::{ShortcutAction, ShortcutBindings};
+use crate::state::AppState;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+/// Replaces the registered global shortcuts with the given bindings
+///
+/// The new shortcuts are registered before the previous ones are released, so
+/// if the OS refuses one of them the previous bindings stay in effect.
+///
+/// # Arguments
+/// * `previous` - The bindings currently registered, None if there are none yet
+/// * `bindings` - The bindings to register
+///
+/// # Errors
+/// Returns an error if a binding cannot be parsed or the OS refuses to register it
+pub fn register(
+    app: &AppHandle,
+    previous: Option<&ShortcutBindings>,
+    bindings: &ShortcutBindings,
+) -> Result<(), String> {
+    let shortcuts = bindings.parse()?;
+    let registered: Vec<Shortcut> = previous
+        .and_then(|previous| previous.parse().ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(_, shortcut)| shortcut)
+        .collect();
+
+    let global_shortcut = app.global_shortcut();
+    let mut added = Vec::new();
+    for (action, shortcut) in shortcuts {
+        if registered.contains(&shortcut) {
+            added.push(shortcut);
+            continue;
+        }
+
+        log::debug!("Registering {} for {:?}", shortcut, action);
+        if let Err(e) = global_shortcut.register(shortcut) {
+            for shortcut in added.iter().filter(|s| !registered.contains(s)) {
+                let _ = global_shortcut.unregister(*shortcut);
+            }
+            return Err(format!("Failed to register shortcut '{}': {}", shortcut, e));
+        }
+        added.push(shortcut);
+    }
+
+    for shortcut in registered.iter().filter(|s| !added.contains(s)) {
+        if let Err(e) = global_shortcut.unregister(*shortcut) {
+            log::warn!("Failed to unregister shortcut '{}': {}", shortcut, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Routes a pressed global shortcut to the matching automation command
+pub fn handle(app: &AppHandle, shortcut: &Shortcut) {
+    let app = app.clone();
+    let shortcut = *shortcut;
+
+    tauri::async_runtime::spawn(async move {
+        let bindings = app.state::<AppState>().get_shortcuts().await;
+
+        if let Some(action) = bindings.action_for(&shortcut) {
+            log::debug!("Shortcut {} triggered {:?}", shortcut, action);
+            if let Err(e) = run_action(&app, action).await {
+                log::warn!("Shortcut {:?} failed: {}", action, e);
+            }
+        }
+    });
+}
+
+/// Runs a single automation action
+async fn run_action(app: &AppHandle, action: ShortcutAction) -> Result<(), String> {
+    let state = app.state::<AppState>();
+
+    match action {
+        ShortcutAction::Next => next_execution(app.clone(), state).await.map(|_| ()),
+        ShortcutAction::Previous => previous_execution(app.clone(), state).await.map(|_| ()),
+        ShortcutAction::Skip => skip_execution(app.clone(), state).await.map(|_| ()),
+        ShortcutAction::CopyComment => copy_current_comment(app.clone(), state).await.map(|_| ()),
+        ShortcutAction::Pause => {
+            if state.is_paused().await {
+                resume_automation(app.clone(), state).await.map(|_| ())
+            } else {
+                pause_automation(app.clone(), state).await.map(|_| ())
+            }
+        }
+    }
+}
@@ -1,5 +1,15 @@
 use crate::execution::ExecutionPlan;
-use crate::models::{BriefCase, Profile, Task};
+use crate::login_check::LoginCheck;
+use crate::models::{BriefCase, BriefCaseGroup, Profile, Session, Task};
+use crate::platforms::PlatformRegistry;
+use crate::security::SecurityConfig;
+use crate::settings::Settings;
+use crate::shortcut_bindings::ShortcutBindings;
+use crate::storage::{StorageBackend, StorageError};
+use crate::window_layout::WindowLayout;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::sync::RwLock;
 
 /// Global application state managing all data and execution progress
@@ -8,40 +18,198 @@ use tokio::sync::RwLock;
 /// 1. Stores all Profiles, BriefCases, and Tasks
 /// 2. Manages the current execution plan and progress
 /// 3. Coordinates between the UI and backend execution
-/// 4. Handles persistence to disk
+/// 4. Handles persistence through the selected storage backend
+///
+/// Methods holding several locks at once take `execution_plan` before `tasks`.
 pub struct AppState {
+    /// Where all data is persisted
+    storage: Arc<dyn StorageBackend>,
+    /// The app data directory holding the profile data directories
+    app_data_dir: PathBuf,
     /// All browser profiles in the system
     profiles: RwLock<Vec<Profile>>,
     /// All social media user accounts in the system
     brief_cases: RwLock<Vec<BriefCase>>,
+    /// Named sets of user accounts that tasks can target
+    groups: RwLock<Vec<BriefCaseGroup>>,
     /// Current tasks being executed
     tasks: RwLock<Vec<Task>>,
     /// The execution plan for the current session
     execution_plan: RwLock<Option<ExecutionPlan>>,
+    /// History record of the current session, None when no session is running
+    session: RwLock<Option<Session>>,
     /// Label of the currently active profile window
     current_window_label: RwLock<Option<String>>,
+    /// Label of the active profile window if the user closed it during automation
+    lost_window_label: RwLock<Option<String>>,
+    /// Labels of profile windows the app is closing itself, which are not lost
+    closing_window_labels: RwLock<HashSet<String>>,
     /// Whether automation is currently running
     is_running: RwLock<bool>,
+    /// The running login check, None when no login check is running
+    login_check: RwLock<Option<LoginCheck>>,
+    /// User-configurable application settings
+    settings: RwLock<Settings>,
+    /// Passphrase configuration, None if no passphrase is set
+    security: RwLock<Option<SecurityConfig>>,
+    /// Whether the passphrase must be entered before profile windows can open
+    locked: RwLock<bool>,
+    /// The platforms tasks and accounts can be created for
+    platforms: RwLock<PlatformRegistry>,
+}
+
+/// The data an AppState starts out with
+#[derive(Default)]
+pub struct InitialData {
+    /// Initial list of profiles
+    pub profiles: Vec<Profile>,
+    /// Initial list of briefcases
+    pub brief_cases: Vec<BriefCase>,
+    /// Initial list of briefcase groups
+    pub groups: Vec<BriefCaseGroup>,
+    /// Persisted application settings
+    pub settings: Settings,
+    /// Passphrase configuration; the app starts locked if one is set
+    pub security: Option<SecurityConfig>,
+    /// The built-in and user-defined platforms
+    pub platforms: PlatformRegistry,
 }
 
 impl AppState {
-    /// Creates a new AppState instance and loads data from disk
+    /// Creates a new AppState instance
     ///
     /// # Arguments
-    /// * `profiles` - Initial list of profiles
-    /// * `brief_cases` - Initial list of briefcases
+    /// * `storage` - The backend all data is persisted to
+    /// * `app_data_dir` - The app data directory holding the profile data directories
+    /// * `data` - The data the state starts out with
     ///
     /// # Returns
     /// A new AppState instance
-    pub fn new(profiles: Vec<Profile>, brief_cases: Vec<BriefCase>) -> Self {
+    pub fn new(storage: Arc<dyn StorageBackend>, app_data_dir: PathBuf, data: InitialData) -> Self {
         Self {
-            profiles: RwLock::new(profiles),
-            brief_cases: RwLock::new(brief_cases),
+            storage,
+            app_data_dir,
+            profiles: RwLock::new(data.profiles),
+            brief_cases: RwLock::new(data.brief_cases),
+            groups: RwLock::new(data.groups),
             tasks: RwLock::new(Vec::new()),
             execution_plan: RwLock::new(None),
+            session: RwLock::new(None),
             current_window_label: RwLock::new(None),
+            lost_window_label: RwLock::new(None),
+            closing_window_labels: RwLock::new(HashSet::new()),
             is_running: RwLock::new(false),
+            login_check: RwLock::new(None),
+            settings: RwLock::new(data.settings),
+            locked: RwLock::new(data.security.is_some()),
+            security: RwLock::new(data.security),
+            platforms: RwLock::new(data.platforms),
+        }
+    }
+
+    /// Initializes a storage backend and loads the state from it
+    ///
+    /// Data that fails to load is logged and replaced by an empty set or the
+    /// defaults. If the data is encrypted, only the settings are loaded until
+    /// the app is unlocked.
+    ///
+    /// # Arguments
+    /// * `storage` - The backend to load from
+    /// * `app_data_dir` - The app data directory holding the profile data directories
+    ///
+    /// # Errors
+    /// Returns an error if the backend cannot be initialized or the passphrase
+    /// configuration cannot be read
+    pub async fn load(
+        storage: Arc<dyn StorageBackend>,
+        app_data_dir: PathBuf,
+    ) -> Result<Self, StorageError> {
+        storage.initialize().await?;
+
+        // A configured passphrase locks the app until it is entered again. If it
+        // cannot be read, starting without it would write encrypted data back
+        // in plaintext, so startup is aborted instead.
+        let security_config = storage.read_security().await.inspect_err(|e| {
+            log::error!("Failed to read security configuration: {}", e);
+        })?;
+        let encrypted = security_config
+            .as_ref()
+            .is_some_and(|config| config.encrypt_data);
+        storage.data_key().activate(None, encrypted);
+
+        let mut settings = storage.read_settings().await.unwrap_or_else(|e| {
+            log::error!("Failed to read settings, using defaults: {}", e);
+            Settings::default()
+        });
+        settings.repair();
+        let platforms = PlatformRegistry::load(storage.as_ref()).await;
+
+        // Encrypted data is loaded once the passphrase has been entered
+        if encrypted {
+            log::info!("Data is encrypted, waiting for the passphrase");
+            return Ok(Self::new(
+                storage,
+                app_data_dir,
+                InitialData {
+                    settings,
+                    security: security_config,
+                    platforms,
+                    ..InitialData::default()
+                },
+            ));
         }
+
+        let profiles = storage.read_profiles().await.unwrap_or_else(|e| {
+            log::error!("Failed to read profiles: {}", e);
+            Vec::new()
+        });
+        let brief_cases = storage.read_briefcases().await.unwrap_or_else(|e| {
+            log::error!("Failed to read briefcases: {}", e);
+            Vec::new()
+        });
+        let groups = storage.read_groups().await.unwrap_or_else(|e| {
+            log::error!("Failed to read groups: {}", e);
+            Vec::new()
+        });
+        log::info!(
+            "Loaded {} profiles and {} briefcases from {} storage",
+            profiles.len(),
+            brief_cases.len(),
+            storage.name()
+        );
+
+        Ok(Self::new(
+            storage,
+            app_data_dir,
+            InitialData {
+                profiles,
+                brief_cases,
+                groups,
+                settings,
+                security: security_config,
+                platforms,
+            },
+        ))
+    }
+
+    /// Gets the backend all data is persisted to
+    pub fn storage(&self) -> &dyn StorageBackend {
+        self.storage.as_ref()
+    }
+
+    /// Gets the app data directory holding the profile data directories
+    pub fn app_data_dir(&self) -> &Path {
+        &self.app_data_dir
+    }
+
+    /// Gets the platforms tasks and accounts can be created for
+    pub async fn get_platforms(&self) -> PlatformRegistry {
+        self.platforms.read().await.clone()
+    }
+
+    /// Replaces the platforms tasks and accounts can be created for
+    pub async fn set_platforms(&self, platforms: PlatformRegistry) {
+        *self.platforms.write().await = platforms;
     }
 
     // ==================== Profile Management ====================
@@ -88,15 +256,82 @@ impl AppState {
         *self.brief_cases.write().await = brief_cases;
     }
 
+    // ==================== Group Management ====================
+
+    /// Gets all briefcase groups
+    pub async fn get_groups(&self) -> Vec<BriefCaseGroup> {
+        self.groups.read().await.clone()
+    }
+
+    /// Replaces all briefcase groups
+    pub async fn set_groups(&self, groups: Vec<BriefCaseGroup>) {
+        *self.groups.write().await = groups;
+    }
+
     // ==================== Task Management ====================
 
     /// Sets the tasks for the current session and creates an execution plan
     pub async fn set_tasks(&self, tasks: Vec<Task>) {
-        let plan = ExecutionPlan::new(&tasks);
+        let plan = ExecutionPlan::new(&tasks, &self.profiles.read().await);
         *self.tasks.write().await = tasks;
         *self.execution_plan.write().await = Some(plan);
     }
 
+    /// Appends a task to the running session and adds its steps to the execution plan
+    ///
+    /// # Returns
+    /// The index of the new task and the number of steps added
+    pub async fn add_task(&self, task: Task) -> (usize, usize) {
+        let profiles = self.profiles.read().await;
+        let mut plan_lock = self.execution_plan.write().await;
+        let mut tasks = self.tasks.write().await;
+
+        tasks.push(task);
+        let task_index = tasks.len() - 1;
+        let added = match *plan_lock {
+            Some(ref mut plan) => plan.add_task(&tasks, task_index, &profiles),
+            None => 0,
+        };
+        (task_index, added)
+    }
+
+    /// Removes a task and its steps from the running session
+    ///
+    /// # Returns
+    /// The removed task and whether the current step belonged to it, None if
+    /// there is no task at the index
+    pub async fn remove_task(&self, task_index: usize) -> Option<(Task, bool)> {
+        let mut plan_lock = self.execution_plan.write().await;
+        let mut tasks = self.tasks.write().await;
+
+        if task_index >= tasks.len() {
+            return None;
+        }
+        let task = tasks.remove(task_index);
+        let current_removed = match *plan_lock {
+            Some(ref mut plan) => plan.remove_task(task_index),
+            None => false,
+        };
+        Some((task, current_removed))
+    }
+
+    /// Replaces the comments of a task, keeping its comment index if it is still valid
+    ///
+    /// # Returns
+    /// false if there is no task at the index
+    pub async fn set_task_comments(&self, task_index: usize, comments: Vec<String>) -> bool {
+        let mut tasks = self.tasks.write().await;
+        let Some(task) = tasks.get_mut(task_index) else {
+            return false;
+        };
+
+        if task.comment_index >= comments.len() {
+            task.comment_index = 0;
+        }
+        task.comments = comments;
+        true
+    }
+
     /// Gets all tasks
     pub async fn get_tasks(&self) -> Vec<Task> {
         self.tasks.read().await.clone()
@@ -108,12 +343,25 @@ impl AppState {
     }
 
     /// Sets the comment index for a task
+    ///
+    /// If the current step is a completed step of the task, only the comment
+    /// posted on that step changes and the task's rotation is left alone.
     pub async fn set_task_comment_index(&self, task_index: usize, comment_index: usize) {
+        let mut plan_lock = self.execution_plan.write().await;
         let mut tasks = self.tasks.write().await;
-        if let Some(task) = tasks.get_mut(task_index) {
-            if comment_index < task.comments.len() {
-                task.comment_index = comment_index;
-            }
+        let Some(task) = tasks.get_mut(task_index) else {
+            return;
+        };
+        if comment_index >= task.comments.len() {
+            return;
+        }
+
+        let assigned = match *plan_lock {
+            Some(ref mut plan) => plan.assign_comment(task_index, comment_index),
+            None => false,
+        };
+        if !assigned {
+            task.comment_index = comment_index;
         }
     }
 
@@ -122,19 +370,27 @@ impl AppState {
         self.tasks.read().await.len()
     }
 
-    /// Gets the current comment for a task
-    pub async fn get_current_comment(&self, task_index: usize) -> Option<String> {
-        let tasks = self.tasks.read().await;
-        if let Some(task) = tasks.get(task_index) {
-            if task.comments.is_empty() {
-                None
-            } else {
-                let index = task.comment_index % task.comments.len();
-                Some(task.comments.get(index).cloned().unwrap_or_default())
-            }
-        } else {
-            None
-        }
+    /// Gets the index of the comment shown for the current step
+    ///
+    /// A completed step shows the comment posted on it, any other step the
+    /// task's next comment.
+    pub async fn current_comment_index(&self) -> Option<usize> {
+        let plan_lock = self.execution_plan.read().await;
+        let tasks_lock = self.tasks.read().await;
+        plan_lock.as_ref()?.current_comment_index(&tasks_lock)
+    }
+
+    /// Gets the comment shown for the current step
+    pub async fn current_comment(&self) -> Option<String> {
+        let plan_lock = self.execution_plan.read().await;
+        let tasks_lock = self.tasks.read().await;
+        let plan = plan_lock.as_ref()?;
+        let index = plan.current_comment_index(&tasks_lock)?;
+        tasks_lock
+            .get(plan.current_step()?.task_index)?
+            .comments
+            .get(index)
+            .cloned()
     }
 
     /// Increments the comment index for a task (with wrapping)
@@ -152,7 +408,7 @@ impl AppState {
     /// Creates a new execution plan from the current tasks
     pub async fn create_execution_plan(&self) {
         let tasks = self.tasks.read().await.clone();
-        let plan = ExecutionPlan::new(&tasks);
+        let plan = ExecutionPlan::new(&tasks, &self.profiles.read().await);
         *self.execution_plan.write().await = Some(plan);
     }
 
@@ -160,7 +416,52 @@ impl AppState {
     pub async fn next_execution_step(&self) -> Option<crate::execution::ExecutionStep> {
         let mut plan_lock = self.execution_plan.write().await;
         if let Some(ref mut plan) = *plan_lock {
-            plan.next()
+            plan.advance()
+        } else {
+            None
+        }
+    }
+
+    /// Moves back to the previous execution step
+    pub async fn previous_execution_step(&self) -> Option<crate::execution::ExecutionStep> {
+        let mut plan_lock = self.execution_plan.write().await;
+        if let Some(ref mut plan) = *plan_lock {
+            plan.previous()
+        } else {
+            None
+        }
+    }
+
+    /// Moves the execution plan to any step
+    ///
+    /// # Arguments
+    /// * `position` - Position of the step in the execution order
+    pub async fn goto_execution_step(
+        &self,
+        position: usize,
+    ) -> Option<crate::execution::ExecutionStep> {
+        let mut plan_lock = self.execution_plan.write().await;
+        if let Some(ref mut plan) = *plan_lock {
+            plan.goto(position)
+        } else {
+            None
+        }
+    }
+
+    /// Finds the position of a task's step in the execution order
+    ///
+    /// # Arguments
+    /// * `task_index` - Index of the task
+    /// * `briefcase_id` - The BriefCase whose step to find, None for the task's next open step
+    pub async fn task_step_position(
+        &self,
+        task_index: usize,
+        briefcase_id: Option<uuid::Uuid>,
+    ) -> Option<usize> {
+        let plan_lock = self.execution_plan.read().await;
+        let tasks_lock = self.tasks.read().await;
+        if let Some(ref plan) = *plan_lock {
+            plan.task_position(&tasks_lock, task_index, briefcase_id)
         } else {
             None
         }
@@ -196,11 +497,26 @@ impl AppState {
         }
     }
 
-    /// Marks a BriefCase as visited
-    pub async fn mark_briefcase_visited(&self, briefcase_id: uuid::Uuid) {
+    /// Gets the position of the current step in the execution order
+    pub async fn current_position(&self) -> Option<usize> {
+        let plan_lock = self.execution_plan.read().await;
+        if let Some(ref plan) = *plan_lock {
+            plan.current_position()
+        } else {
+            None
+        }
+    }
+
+    /// Marks the current step as completed with the comment posted on it
+    ///
+    /// # Returns
+    /// true if the step was completed for the first time
+    pub async fn complete_current_step(&self, comment_index: Option<usize>) -> bool {
         let mut plan_lock = self.execution_plan.write().await;
         if let Some(ref mut plan) = *plan_lock {
-            plan.mark_visited(briefcase_id);
+            plan.complete_current(comment_index)
+        } else {
+            false
         }
     }
 
@@ -222,6 +538,30 @@ impl AppState {
         }
     }
 
+    /// Sets the history record of the current session
+    pub async fn set_session(&self, session: Option<Session>) {
+        *self.session.write().await = session;
+    }
+
+    /// Gets the history record of the current session
+    pub async fn get_session(&self) -> Option<Session> {
+        self.session.read().await.clone()
+    }
+
+    /// Removes and returns the history record of the current session
+    pub async fn take_session(&self) -> Option<Session> {
+        self.session.write().await.take()
+    }
+
+    /// Checks if the current session is paused
+    pub async fn is_paused(&self) -> bool {
+        self.session
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(Session::is_paused)
+    }
+
     // ==================== Progress Tracking ====================
 
     /// Gets overall progress statistics
@@ -266,6 +606,7 @@ impl AppState {
     /// Sets the label of the currently active profile window
     pub async fn set_current_window_label(&self, label: String) {
         *self.current_window_label.write().await = Some(label);
+        *self.lost_window_label.write().await = None;
     }
 
     /// Gets the label of the currently active profile window
@@ -276,6 +617,46 @@ impl AppState {
     /// Clears the current window label
     pub async fn clear_current_window_label(&self) {
         *self.current_window_label.write().await = None;
+        *self.lost_window_label.write().await = None;
+    }
+
+    /// Records that the app is about to close a profile window itself
+    ///
+    /// The window is destroyed asynchronously, possibly after a new window got
+    /// the same label, so its destruction must not be taken for the user closing it.
+    pub async fn mark_window_closing(&self, label: &str) {
+        self.closing_window_labels
+            .write()
+            .await
+            .insert(label.to_string());
+    }
+
+    /// Records that a profile window was closed
+    ///
+    /// # Returns
+    /// true if the closed window was the active profile window of a running
+    /// automation and was not closed by the app itself
+    pub async fn mark_window_lost(&self, label: &str) -> bool {
+        if self.closing_window_labels.write().await.remove(label) {
+            return false;
+        }
+        if !self.is_running().await {
+            return false;
+        }
+
+        let mut current = self.current_window_label.write().await;
+        if current.as_deref() != Some(label) {
+            return false;
+        }
+
+        *current = None;
+        *self.lost_window_label.write().await = Some(label.to_string());
+        true
+    }
+
+    /// Gets the label of the active profile window if the user closed it
+    pub async fn get_lost_window_label(&self) -> Option<String> {
+        self.lost_window_label.read().await.clone()
     }
 
     // ==================== Running State ====================
@@ -289,4 +670,65 @@ impl AppState {
     pub async fn is_running(&self) -> bool {
         *self.is_running.read().await
     }
+
+    // ==================== Login Check ====================
+
+    /// Sets or clears the running login check
+    pub async fn set_login_check(&self, check: Option<LoginCheck>) {
+        *self.login_check.write().await = check;
+    }
+
+    /// Gets the running login check
+    pub async fn get_login_check(&self) -> Option<LoginCheck> {
+        self.login_check.read().await.clone()
+    }
+
+    // ==================== Settings ====================
+
+    /// Sets the application settings
+    pub async fn set_settings(&self, settings: Settings) {
+        *self.settings.write().await = settings;
+    }
+
+    /// Gets the application settings
+    pub async fn get_settings(&self) -> Settings {
+        self.settings.read().await.clone()
+    }
+
+    /// Checks if the current comment is copied to the clipboard on every step
+    pub async fn auto_copy_comment(&self) -> bool {
+        self.settings.read().await.auto_copy_comment
+    }
+
+    /// Gets the global shortcut bindings
+    pub async fn get_shortcuts(&self) -> ShortcutBindings {
+        self.settings.read().await.shortcuts.clone()
+    }
+
+    /// Gets the window layout
+    pub async fn get_layout(&self) -> WindowLayout {
+        self.settings.read().await.layout.clone()
+    }
+
+    // ==================== Security ====================
+
+    /// Gets the passphrase configuration
+    pub async fn get_security(&self) -> Option<SecurityConfig> {
+        self.security.read().await.clone()
+    }
+
+    /// Sets the passphrase configuration
+    pub async fn set_security(&self, security: Option<SecurityConfig>) {
+        *self.security.write().await = security;
+    }
+
+    /// Sets whether the app is locked
+    pub async fn set_locked(&self, locked: bool) {
+        *self.locked.write().await = locked;
+    }
+
+    /// Checks if the passphrase must be entered before profile windows can open
+    pub async fn is_locked(&self) -> bool {
+        *self.locked.read().await
+    }
 }
@@ -0,0 +1,429 @@
+use super::{
+    decode_lines, decode_list, encode_lines, encode_list, DataFileStats, DataSnapshot,
+    StorageBackend, StorageError, AUDIT_FILE, BRIEFCASES_FILE, GROUPS_FILE, PLATFORMS_FILE,
+    PROFILES_FILE, SECURITY_FILE, SESSIONS_FILE, SESSION_STEPS_FILE, SETTINGS_FILE,
+};
+use crate::audit::AuditEntry;
+use crate::models::profile::normalize_sort_order;
+use crate::models::{BriefCase, BriefCaseGroup, Profile, Session, SessionStep};
+use crate::platforms::Platform;
+use crate::security::{DataKey, SecurityConfig};
+use crate::settings::Settings;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+/// Storage backend keeping every data set in a JSON file of the config directory
+///
+/// Profiles, briefcases, groups, the audit log and the session history are
+/// encrypted when data encryption is enabled; settings, platforms and the
+/// passphrase configuration are not.
+pub struct JsonStorage {
+    /// Directory holding the data files
+    config_dir: PathBuf,
+    /// Whether `initialize` leaves missing files alone
+    read_only: bool,
+    /// Key the encrypted data sets are sealed with
+    key: Arc<DataKey>,
+}
+
+impl JsonStorage {
+    /// Creates a backend storing its files in the given directory
+    ///
+    /// # Arguments
+    /// * `config_dir` - Directory holding the data files, created by `initialize`
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self::with_key(config_dir, Arc::default())
+    }
+
+    /// Creates a backend sealing its files with a key shared with another backend
+    ///
+    /// # Arguments
+    /// * `config_dir` - Directory holding the data files, created by `initialize`
+    /// * `key` - The key of the backend the files belong to
+    pub(super) fn with_key(config_dir: PathBuf, key: Arc<DataKey>) -> Self {
+        Self {
+            config_dir,
+            read_only: false,
+            key,
+        }
+    }
+
+    /// Creates a backend reading the files of the given directory without creating any
+    ///
+    /// # Arguments
+    /// * `config_dir` - Directory holding the data files
+    pub fn read_only(config_dir: PathBuf) -> Self {
+        Self {
+            read_only: true,
+            ..Self::new(config_dir)
+        }
+    }
+
+    /// Reads and decrypts a JSON array file, empty if the file doesn't exist
+    async fn read_list<T: DeserializeOwned>(&self, name: &str) -> Result<Vec<T>, StorageError> {
+        let path = self.config_dir.join(name);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        decode_list(&self.key, &fs::read_to_string(&path).await?)
+    }
+
+    /// Serializes, encrypts if enabled and writes a JSON array file
+    async fn write_list<T: Serialize>(&self, name: &str, items: &[T]) -> Result<(), StorageError> {
+        fs::write(self.config_dir.join(name), encode_list(&self.key, items)?).await?;
+        log::debug!("Wrote {} records to {}", items.len(), name);
+
+        Ok(())
+    }
+
+    /// Reads and decrypts an append-only file, empty if the file doesn't exist
+    async fn read_lines<T: DeserializeOwned>(&self, name: &str) -> Result<Vec<T>, StorageError> {
+        let path = self.config_dir.join(name);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        decode_lines(&self.key, &fs::read_to_string(&path).await?)
+    }
+
+    /// Encodes every data set of a snapshot into the contents of its file
+    fn data_files(&self, data: &DataSnapshot) -> Result<Vec<(&'static str, String)>, StorageError> {
+        Ok(vec![
+            (PROFILES_FILE, encode_list(&self.key, &data.profiles)?),
+            (BRIEFCASES_FILE, encode_list(&self.key, &data.briefcases)?),
+            (GROUPS_FILE, encode_list(&self.key, &data.groups)?),
+            (SETTINGS_FILE, serde_json::to_string_pretty(&data.settings)?),
+            (
+                PLATFORMS_FILE,
+                serde_json::to_string_pretty(&data.platforms)?,
+            ),
+            (SESSIONS_FILE, encode_list(&self.key, &data.sessions)?),
+            (SESSION_STEPS_FILE, encode_lines(&self.key, &data.steps)?),
+        ])
+    }
+
+    /// Replaces several files at once
+    ///
+    /// Every file is written next to its target first and only renamed into
+    /// place once all of them were written, so a failed write changes nothing.
+    async fn replace_files(&self, files: Vec<(&'static str, String)>) -> Result<(), StorageError> {
+        let names: Vec<&str> = files.iter().map(|(name, _)| *name).collect();
+        self.stage_files(&files).await?;
+        self.commit_files(&names).await
+    }
+
+    /// Path a file is written to before it replaces the real one
+    fn staged_path(&self, name: &str) -> PathBuf {
+        self.config_dir.join(format!("{}.staged", name))
+    }
+
+    /// Writes files next to their targets, removing them all again if one fails
+    pub(super) async fn stage_files(&self, files: &[(&str, String)]) -> Result<(), StorageError> {
+        for (name, contents) in files {
+            if let Err(e) = fs::write(self.staged_path(name), contents).await {
+                self.discard_files(&files.iter().map(|(name, _)| *name).collect::<Vec<_>>())
+                    .await;
+                return Err(e.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Renames staged files into place
+    pub(super) async fn commit_files(&self, names: &[&str]) -> Result<(), StorageError> {
+        for name in names {
+            fs::rename(self.staged_path(name), self.config_dir.join(name)).await?;
+        }
+        Ok(())
+    }
+
+    /// Removes staged files that will not be committed
+    pub(super) async fn discard_files(&self, names: &[&str]) {
+        for name in names {
+            let _ = fs::remove_file(self.staged_path(name)).await;
+        }
+    }
+
+    /// Appends records to an append-only file, encrypting each line if enabled
+    async fn append_lines<T: Serialize>(
+        &self,
+        name: &str,
+        items: &[T],
+    ) -> Result<(), StorageError> {
+        let data = encode_lines(&self.key, items)?;
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.config_dir.join(name))
+            .await?;
+        file.write_all(data.as_bytes()).await?;
+        file.flush().await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StorageBackend for JsonStorage {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn data_key(&self) -> &DataKey {
+        &self.key
+    }
+
+    async fn initialize(&self) -> Result<(), StorageError> {
+        // Missing files read as empty data sets
+        if self.read_only {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.config_dir).await?;
+
+        for name in [PROFILES_FILE, BRIEFCASES_FILE] {
+            let path = self.config_dir.join(name);
+            if !path.exists() {
+                fs::write(&path, "[]").await?;
+                log::info!("Created {}", name);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn read_profiles(&self) -> Result<Vec<Profile>, StorageError> {
+        let mut profiles = self.read_list(PROFILES_FILE).await?;
+
+        // Profiles saved before the user-defined order existed keep their stored order
+        normalize_sort_order(&mut profiles);
+
+        Ok(profiles)
+    }
+
+    async fn write_profiles(&self, profiles: &[Profile]) -> Result<(), StorageError> {
+        self.write_list(PROFILES_FILE, profiles).await
+    }
+
+    async fn read_briefcases(&self) -> Result<Vec<BriefCase>, StorageError> {
+        self.read_list(BRIEFCASES_FILE).await
+    }
+
+    async fn write_briefcases(&self, briefcases: &[BriefCase]) -> Result<(), StorageError> {
+        self.write_list(BRIEFCASES_FILE, briefcases).await
+    }
+
+    async fn read_groups(&self) -> Result<Vec<BriefCaseGroup>, StorageError> {
+        self.read_list(GROUPS_FILE).await
+    }
+
+    async fn write_groups(&self, groups: &[BriefCaseGroup]) -> Result<(), StorageError> {
+        self.write_list(GROUPS_FILE, groups).await
+    }
+
+    /// Falls back to the defaults when no settings file exists yet, carrying over
+    /// a window layout saved by older versions
+    async fn read_settings(&self) -> Result<Settings, StorageError> {
+        let settings_path = self.config_dir.join(SETTINGS_FILE);
+
+        if !settings_path.exists() {
+            return Ok(Settings::default());
+        }
+
+        let data = fs::read_to_string(&settings_path).await?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    async fn write_settings(&self, settings: &Settings) -> Result<(), StorageError> {
+        let data = serde_json::to_string_pretty(settings)?;
+        fs::write(self.config_dir.join(SETTINGS_FILE), data).await?;
+        Ok(())
+    }
+
+    async fn read_platforms(&self) -> Result<Vec<Platform>, StorageError> {
+        let path = self.config_dir.join(PLATFORMS_FILE);
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(serde_json::from_str(&fs::read_to_string(&path).await?)?)
+    }
+
+    async fn write_platforms(&self, platforms: &[Platform]) -> Result<(), StorageError> {
+        let data = serde_json::to_string_pretty(platforms)?;
+        fs::write(self.config_dir.join(PLATFORMS_FILE), data).await?;
+        Ok(())
+    }
+
+    async fn append_audit(&self, entries: &[AuditEntry]) -> Result<(), StorageError> {
+        self.append_lines(AUDIT_FILE, entries).await?;
+
+        log::debug!("Appended {} audit entries", entries.len());
+        Ok(())
+    }
+
+    async fn read_audit(&self) -> Result<Vec<AuditEntry>, StorageError> {
+        self.read_lines(AUDIT_FILE).await
+    }
+
+    async fn trim_audit(&self, keep: usize) -> Result<(), StorageError> {
+        let path = self.config_dir.join(AUDIT_FILE);
+        if !path.exists() {
+            return Ok(());
+        }
+
+        // Lines are trimmed without decrypting them
+        let data = fs::read_to_string(&path).await?;
+        let lines: Vec<&str> = data
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        if lines.len() <= keep {
+            return Ok(());
+        }
+
+        let mut kept = lines[lines.len() - keep..].join("\n");
+        kept.push('\n');
+        self.stage_files(&[(AUDIT_FILE, kept)]).await?;
+        self.commit_files(&[AUDIT_FILE]).await?;
+
+        log::info!("Dropped {} old audit entries", lines.len() - keep);
+        Ok(())
+    }
+
+    async fn read_security(&self) -> Result<Option<SecurityConfig>, StorageError> {
+        let security_path = self.config_dir.join(SECURITY_FILE);
+        if !security_path.exists() {
+            return Ok(None);
+        }
+
+        let data = fs::read_to_string(&security_path).await?;
+        Ok(Some(serde_json::from_str(&data)?))
+    }
+
+    async fn write_security(&self, config: Option<&SecurityConfig>) -> Result<(), StorageError> {
+        let security_path = self.config_dir.join(SECURITY_FILE);
+
+        match config {
+            Some(config) => {
+                let data = serde_json::to_string_pretty(config)?;
+                fs::write(&security_path, data).await?;
+            }
+            None if security_path.exists() => fs::remove_file(&security_path).await?,
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    async fn write_session(&self, session: &Session) -> Result<(), StorageError> {
+        let mut sessions: Vec<Session> = self.read_list(SESSIONS_FILE).await?;
+        match sessions.iter_mut().find(|s| s.id == session.id) {
+            Some(stored) => *stored = session.clone(),
+            None => sessions.push(session.clone()),
+        }
+
+        self.write_list(SESSIONS_FILE, &sessions).await
+    }
+
+    async fn append_session_step(&self, step: &SessionStep) -> Result<(), StorageError> {
+        self.append_lines(SESSION_STEPS_FILE, std::slice::from_ref(step))
+            .await
+    }
+
+    async fn read_sessions(&self) -> Result<Vec<Session>, StorageError> {
+        self.read_list(SESSIONS_FILE).await
+    }
+
+    async fn read_session_steps(&self, session_id: Uuid) -> Result<Vec<SessionStep>, StorageError> {
+        let steps: Vec<SessionStep> = self.read_lines(SESSION_STEPS_FILE).await?;
+        Ok(steps
+            .into_iter()
+            .filter(|step| step.session_id == session_id)
+            .collect())
+    }
+
+    async fn restore(&self, data: &DataSnapshot) -> Result<(), StorageError> {
+        self.replace_files(self.data_files(data)?).await?;
+
+        log::debug!("Restored the data files");
+        Ok(())
+    }
+
+    /// Removes the passphrase configuration only after the other files were replaced
+    async fn rekey(
+        &self,
+        data: &DataSnapshot,
+        audit: &[AuditEntry],
+        security: Option<&SecurityConfig>,
+    ) -> Result<(), StorageError> {
+        let mut files = self.data_files(data)?;
+        files.push((AUDIT_FILE, encode_lines(&self.key, audit)?));
+        if let Some(config) = security {
+            files.push((SECURITY_FILE, serde_json::to_string_pretty(config)?));
+        }
+        self.replace_files(files).await?;
+
+        if security.is_none() {
+            self.write_security(None).await?;
+        }
+
+        log::debug!("Rewrote the data files with the new key");
+        Ok(())
+    }
+
+    async fn data_file_stats(&self) -> Result<Vec<DataFileStats>, StorageError> {
+        let mut stats = Vec::new();
+
+        for name in [
+            PROFILES_FILE,
+            BRIEFCASES_FILE,
+            GROUPS_FILE,
+            SETTINGS_FILE,
+            SESSIONS_FILE,
+        ] {
+            let path = self.config_dir.join(name);
+
+            let Ok(metadata) = fs::metadata(&path).await else {
+                stats.push(DataFileStats {
+                    name: name.to_string(),
+                    exists: false,
+                    size_bytes: 0,
+                    modified: None,
+                    records: None,
+                });
+                continue;
+            };
+
+            let modified = metadata
+                .modified()
+                .ok()
+                .map(|time| chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339());
+
+            let records = fs::read_to_string(&path)
+                .await
+                .ok()
+                .and_then(|data| self.key.open_file(&data).ok())
+                .and_then(|data| serde_json::from_str::<Vec<serde_json::Value>>(&data).ok())
+                .map(|values| values.len());
+
+            stats.push(DataFileStats {
+                name: name.to_string(),
+                exists: true,
+                size_bytes: metadata.len(),
+                modified,
+                records,
+            });
+        }
+
+        Ok(stats)
+    }
+}
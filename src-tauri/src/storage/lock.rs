@@ -0,0 +1,41 @@
+use super::StorageError;
+use std::fs::{self, File, TryLockError};
+use std::path::Path;
+
+/// Lock file in the app data directory, held by every process changing the data
+pub const LOCK_FILE: &str = "attache.lock";
+
+/// Exclusive hold on an app data directory
+///
+/// The app holds it while it runs and the CLI while it changes data, so neither
+/// overwrites changes the other keeps in memory. The OS releases it when the
+/// holding process exits, even if it crashed.
+#[derive(Debug)]
+pub struct StoreLock {
+    /// The locked file, unlocked when dropped
+    _file: File,
+}
+
+impl StoreLock {
+    /// Takes the lock of an app data directory, creating the directory if needed
+    ///
+    /// # Arguments
+    /// * `app_data_dir` - The app data directory to lock
+    ///
+    /// # Errors
+    /// Returns `StorageError::Locked` if another process holds the lock
+    pub fn acquire(app_data_dir: &Path) -> Result<Self, StorageError> {
+        fs::create_dir_all(app_data_dir)?;
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(app_data_dir.join(LOCK_FILE))?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(Self { _file: file }),
+            Err(TryLockError::WouldBlock) => Err(StorageError::Locked),
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+}
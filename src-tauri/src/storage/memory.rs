@@ -0,0 +1,217 @@
+use super::{
+    DataFileStats, DataSnapshot, StorageBackend, StorageError, BRIEFCASES_FILE, GROUPS_FILE,
+    PROFILES_FILE, SESSIONS_FILE, SETTINGS_FILE,
+};
+use crate::audit::AuditEntry;
+use crate::models::{BriefCase, BriefCaseGroup, Profile, Session, SessionStep};
+use crate::platforms::Platform;
+use crate::security::{DataKey, SecurityConfig};
+use crate::settings::Settings;
+use async_trait::async_trait;
+use serde::Serialize;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+/// Everything held by a `MemoryStorage`
+#[derive(Default)]
+struct MemoryData {
+    profiles: Vec<Profile>,
+    briefcases: Vec<BriefCase>,
+    groups: Vec<BriefCaseGroup>,
+    settings: Option<Settings>,
+    platforms: Vec<Platform>,
+    audit: Vec<AuditEntry>,
+    security: Option<SecurityConfig>,
+    sessions: Vec<Session>,
+    session_steps: Vec<SessionStep>,
+}
+
+/// Storage backend keeping all data in memory
+///
+/// Nothing survives a restart; meant for tests and throwaway sessions.
+#[derive(Default)]
+pub struct MemoryStorage {
+    data: RwLock<MemoryData>,
+    /// Key state of the store, never used to seal anything since the data stays in memory
+    key: DataKey,
+}
+
+impl MemoryStorage {
+    /// Builds the stats entry of one data set
+    fn stats<T: Serialize>(name: &str, value: Option<&T>, records: Option<usize>) -> DataFileStats {
+        DataFileStats {
+            name: name.to_string(),
+            exists: value.is_some(),
+            size_bytes: value
+                .and_then(|value| serde_json::to_vec(value).ok())
+                .map_or(0, |data| data.len() as u64),
+            modified: None,
+            records,
+        }
+    }
+}
+
+#[async_trait]
+impl StorageBackend for MemoryStorage {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn data_key(&self) -> &DataKey {
+        &self.key
+    }
+
+    async fn initialize(&self) -> Result<(), StorageError> {
+        Ok(())
+    }
+
+    async fn read_profiles(&self) -> Result<Vec<Profile>, StorageError> {
+        Ok(self.data.read().await.profiles.clone())
+    }
+
+    async fn write_profiles(&self, profiles: &[Profile]) -> Result<(), StorageError> {
+        self.data.write().await.profiles = profiles.to_vec();
+        Ok(())
+    }
+
+    async fn read_briefcases(&self) -> Result<Vec<BriefCase>, StorageError> {
+        Ok(self.data.read().await.briefcases.clone())
+    }
+
+    async fn write_briefcases(&self, briefcases: &[BriefCase]) -> Result<(), StorageError> {
+        self.data.write().await.briefcases = briefcases.to_vec();
+        Ok(())
+    }
+
+    async fn read_groups(&self) -> Result<Vec<BriefCaseGroup>, StorageError> {
+        Ok(self.data.read().await.groups.clone())
+    }
+
+    async fn write_groups(&self, groups: &[BriefCaseGroup]) -> Result<(), StorageError> {
+        self.data.write().await.groups = groups.to_vec();
+        Ok(())
+    }
+
+    async fn read_settings(&self) -> Result<Settings, StorageError> {
+        Ok(self.data.read().await.settings.clone().unwrap_or_default())
+    }
+
+    async fn write_settings(&self, settings: &Settings) -> Result<(), StorageError> {
+        self.data.write().await.settings = Some(settings.clone());
+        Ok(())
+    }
+
+    async fn read_platforms(&self) -> Result<Vec<Platform>, StorageError> {
+        Ok(self.data.read().await.platforms.clone())
+    }
+
+    async fn write_platforms(&self, platforms: &[Platform]) -> Result<(), StorageError> {
+        self.data.write().await.platforms = platforms.to_vec();
+        Ok(())
+    }
+
+    async fn append_audit(&self, entries: &[AuditEntry]) -> Result<(), StorageError> {
+        self.data.write().await.audit.extend_from_slice(entries);
+        Ok(())
+    }
+
+    async fn read_audit(&self) -> Result<Vec<AuditEntry>, StorageError> {
+        Ok(self.data.read().await.audit.clone())
+    }
+
+    async fn trim_audit(&self, keep: usize) -> Result<(), StorageError> {
+        let audit = &mut self.data.write().await.audit;
+        let excess = audit.len().saturating_sub(keep);
+        audit.drain(..excess);
+        Ok(())
+    }
+
+    async fn read_security(&self) -> Result<Option<SecurityConfig>, StorageError> {
+        Ok(self.data.read().await.security.clone())
+    }
+
+    async fn write_security(&self, config: Option<&SecurityConfig>) -> Result<(), StorageError> {
+        self.data.write().await.security = config.cloned();
+        Ok(())
+    }
+
+    async fn write_session(&self, session: &Session) -> Result<(), StorageError> {
+        let mut data = self.data.write().await;
+        match data.sessions.iter_mut().find(|s| s.id == session.id) {
+            Some(stored) => *stored = session.clone(),
+            None => data.sessions.push(session.clone()),
+        }
+        Ok(())
+    }
+
+    async fn append_session_step(&self, step: &SessionStep) -> Result<(), StorageError> {
+        self.data.write().await.session_steps.push(step.clone());
+        Ok(())
+    }
+
+    async fn read_sessions(&self) -> Result<Vec<Session>, StorageError> {
+        Ok(self.data.read().await.sessions.clone())
+    }
+
+    async fn read_session_steps(&self, session_id: Uuid) -> Result<Vec<SessionStep>, StorageError> {
+        Ok(self
+            .data
+            .read()
+            .await
+            .session_steps
+            .iter()
+            .filter(|step| step.session_id == session_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn restore(&self, snapshot: &DataSnapshot) -> Result<(), StorageError> {
+        let mut data = self.data.write().await;
+        data.profiles = snapshot.profiles.clone();
+        data.briefcases = snapshot.briefcases.clone();
+        data.groups = snapshot.groups.clone();
+        data.settings = Some(snapshot.settings.clone());
+        data.platforms = snapshot.platforms.clone();
+        data.sessions = snapshot.sessions.clone();
+        data.session_steps = snapshot.steps.clone();
+        Ok(())
+    }
+
+    async fn rekey(
+        &self,
+        snapshot: &DataSnapshot,
+        audit: &[AuditEntry],
+        security: Option<&SecurityConfig>,
+    ) -> Result<(), StorageError> {
+        self.restore(snapshot).await?;
+
+        let mut data = self.data.write().await;
+        data.audit = audit.to_vec();
+        data.security = security.cloned();
+        Ok(())
+    }
+
+    async fn data_file_stats(&self) -> Result<Vec<DataFileStats>, StorageError> {
+        let data = self.data.read().await;
+
+        Ok(vec![
+            Self::stats(
+                PROFILES_FILE,
+                Some(&data.profiles),
+                Some(data.profiles.len()),
+            ),
+            Self::stats(
+                BRIEFCASES_FILE,
+                Some(&data.briefcases),
+                Some(data.briefcases.len()),
+            ),
+            Self::stats(GROUPS_FILE, Some(&data.groups), Some(data.groups.len())),
+            Self::stats(SETTINGS_FILE, data.settings.as_ref(), None),
+            Self::stats(
+                SESSIONS_FILE,
+                Some(&data.sessions),
+                Some(data.sessions.len()),
+            ),
+        ])
+    }
+}
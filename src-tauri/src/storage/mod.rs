@@ -0,0 +1,335 @@
+use crate::audit::AuditEntry;
+use crate::models::{BriefCase, BriefCaseGroup, Profile, Session, SessionStep};
+use crate::platforms::Platform;
+use crate::security::{DataKey, SecurityConfig, SecurityError};
+use crate::settings::Settings;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use uuid::Uuid;
+
+mod json;
+mod lock;
+mod memory;
+mod sqlite;
+
+pub use json::JsonStorage;
+pub use lock::{StoreLock, LOCK_FILE};
+pub use memory::MemoryStorage;
+pub use sqlite::SqliteStorage;
+
+/// File names for persisted data
+pub const PROFILES_FILE: &str = "profiles.json";
+pub const BRIEFCASES_FILE: &str = "briefcases.json";
+pub const GROUPS_FILE: &str = "groups.json";
+pub const SETTINGS_FILE: &str = "settings.json";
+/// Append-only log of data mutations, one JSON entry per line
+pub const AUDIT_FILE: &str = "audit.jsonl";
+/// Passphrase configuration, kept in plaintext so the app can be unlocked
+pub const SECURITY_FILE: &str = "security.json";
+/// Recorded automation sessions
+pub const SESSIONS_FILE: &str = "sessions.json";
+/// Steps completed in the recorded sessions, one JSON entry per line
+pub const SESSION_STEPS_FILE: &str = "session_steps.jsonl";
+/// User-defined platforms, kept as a plaintext file by every file-based backend
+/// so it can be edited by hand
+pub const PLATFORMS_FILE: &str = "platforms.json";
+/// SQLite database holding all data when the SQLite backend is used
+pub const DATABASE_FILE: &str = "attache.db";
+/// Directory inside the app data directory holding the data files
+const CONFIG_DIR: &str = "config";
+/// Environment variable selecting the storage backend
+pub const BACKEND_ENV: &str = "ATTACHE_STORAGE";
+
+/// Errors that can occur during storage operations
+#[derive(Debug, Error)]
+pub enum StorageError {
+    /// File not found
+    #[error("File not found")]
+    #[allow(dead_code)]
+    NotFound,
+
+    /// I/O error occurred
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// JSON deserialization error
+    #[error("Deserialization error: {0}")]
+    Deserialize(#[from] serde_json::Error),
+
+    /// Unable to resolve config directory
+    #[error("Unable to resolve config directory")]
+    NoConfigDir,
+
+    /// Encrypted data could not be read or written
+    #[error("{0}")]
+    Security(#[from] SecurityError),
+
+    /// The database rejected a query
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+
+    /// The backend was used before `initialize` was called
+    #[error("Storage is not initialized")]
+    NotInitialized,
+
+    /// A read-only backend would have to change the data to open it
+    #[error("{0}, open the data with the app first")]
+    ReadOnly(String),
+
+    /// Another process holds the lock of the data directory
+    #[error("The data is in use by another attache process, close it first")]
+    Locked,
+}
+
+/// Size and freshness of a persisted data file, used in diagnostics
+#[derive(Debug, Clone, Serialize)]
+pub struct DataFileStats {
+    /// File name inside the config directory
+    pub name: String,
+    /// Whether the file exists
+    pub exists: bool,
+    /// File size in bytes
+    pub size_bytes: u64,
+    /// RFC 3339 timestamp of the last modification
+    pub modified: Option<String>,
+    /// Number of records if the file holds a JSON array
+    pub records: Option<usize>,
+}
+
+/// Every data set except the audit log and the passphrase configuration
+///
+/// Written as a whole by `StorageBackend::restore`, e.g. when importing a backup.
+#[derive(Debug, Clone, Default)]
+pub struct DataSnapshot {
+    /// All profiles
+    pub profiles: Vec<Profile>,
+    /// All briefcases
+    pub briefcases: Vec<BriefCase>,
+    /// All briefcase groups
+    pub groups: Vec<BriefCaseGroup>,
+    /// The application settings
+    pub settings: Settings,
+    /// The user-defined platforms
+    pub platforms: Vec<Platform>,
+    /// All recorded sessions
+    pub sessions: Vec<Session>,
+    /// The steps of all recorded sessions
+    pub steps: Vec<SessionStep>,
+}
+
+/// Available storage backends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageKind {
+    /// SQLite database in the config directory, migrated from the JSON files once
+    Sqlite,
+    /// JSON files in the config directory
+    Json,
+    /// Process memory only, nothing survives a restart
+    Memory,
+}
+
+impl StorageKind {
+    /// Reads the backend selected through the `ATTACHE_STORAGE` environment variable
+    ///
+    /// # Returns
+    /// The selected backend, `Sqlite` if none or an unknown one is selected
+    pub fn from_env() -> Self {
+        match std::env::var(BACKEND_ENV).as_deref() {
+            Ok("memory") => Self::Memory,
+            Ok("json") => Self::Json,
+            Ok("sqlite") | Err(_) => Self::Sqlite,
+            Ok(other) => {
+                log::warn!("Unknown storage backend {}, using sqlite", other);
+                Self::Sqlite
+            }
+        }
+    }
+
+    /// Opens the selected backend
+    ///
+    /// # Arguments
+    /// * `app_data_dir` - The app data directory file-based backends store their data in
+    pub fn open(self, app_data_dir: &Path) -> Arc<dyn StorageBackend> {
+        match self {
+            Self::Sqlite => Arc::new(SqliteStorage::new(config_dir(app_data_dir))),
+            Self::Json => Arc::new(JsonStorage::new(config_dir(app_data_dir))),
+            Self::Memory => Arc::new(MemoryStorage::default()),
+        }
+    }
+
+    /// Opens the selected backend for reading only
+    ///
+    /// `initialize` neither creates missing data nor migrates it; if the data
+    /// would have to be migrated first, it fails with `StorageError::ReadOnly`.
+    ///
+    /// # Arguments
+    /// * `app_data_dir` - The app data directory file-based backends store their data in
+    pub fn open_read_only(self, app_data_dir: &Path) -> Arc<dyn StorageBackend> {
+        match self {
+            Self::Sqlite => Arc::new(SqliteStorage::read_only(config_dir(app_data_dir))),
+            Self::Json => Arc::new(JsonStorage::read_only(config_dir(app_data_dir))),
+            Self::Memory => Arc::new(MemoryStorage::default()),
+        }
+    }
+}
+
+/// Persistence for all application data
+///
+/// The state and the commands only talk to this trait, so the backend can be
+/// swapped at startup and tests can run against `MemoryStorage`.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    /// Short name of the backend, used in logs and diagnostics
+    fn name(&self) -> &'static str;
+
+    /// Key the data of this backend is encrypted with
+    fn data_key(&self) -> &DataKey;
+
+    /// Prepares the backend, creating empty data sets if they don't exist
+    async fn initialize(&self) -> Result<(), StorageError>;
+
+    /// Reads all profiles
+    async fn read_profiles(&self) -> Result<Vec<Profile>, StorageError>;
+
+    /// Replaces all profiles
+    async fn write_profiles(&self, profiles: &[Profile]) -> Result<(), StorageError>;
+
+    /// Reads all briefcases
+    async fn read_briefcases(&self) -> Result<Vec<BriefCase>, StorageError>;
+
+    /// Replaces all briefcases
+    async fn write_briefcases(&self, briefcases: &[BriefCase]) -> Result<(), StorageError>;
+
+    /// Reads all briefcase groups, empty if no groups were saved yet
+    async fn read_groups(&self) -> Result<Vec<BriefCaseGroup>, StorageError>;
+
+    /// Replaces all briefcase groups
+    async fn write_groups(&self, groups: &[BriefCaseGroup]) -> Result<(), StorageError>;
+
+    /// Replaces profiles, briefcases and groups together
+    ///
+    /// Backends with transactions write all three or none of them.
+    async fn write_data(
+        &self,
+        profiles: &[Profile],
+        briefcases: &[BriefCase],
+        groups: &[BriefCaseGroup],
+    ) -> Result<(), StorageError> {
+        self.write_profiles(profiles).await?;
+        self.write_briefcases(briefcases).await?;
+        self.write_groups(groups).await
+    }
+
+    /// Reads the application settings, the defaults if none were saved yet
+    async fn read_settings(&self) -> Result<Settings, StorageError>;
+
+    /// Replaces the application settings
+    async fn write_settings(&self, settings: &Settings) -> Result<(), StorageError>;
+
+    /// Reads the user-defined platforms, empty if none were added
+    async fn read_platforms(&self) -> Result<Vec<Platform>, StorageError>;
+
+    /// Replaces the user-defined platforms
+    async fn write_platforms(&self, platforms: &[Platform]) -> Result<(), StorageError>;
+
+    /// Appends entries to the audit log, in the order they happened
+    async fn append_audit(&self, entries: &[AuditEntry]) -> Result<(), StorageError>;
+
+    /// Reads the audit log, oldest entry first
+    async fn read_audit(&self) -> Result<Vec<AuditEntry>, StorageError>;
+
+    /// Drops the oldest audit entries so that at most `keep` remain
+    async fn trim_audit(&self, keep: usize) -> Result<(), StorageError>;
+
+    /// Reads the passphrase configuration, None if no passphrase is set
+    async fn read_security(&self) -> Result<Option<SecurityConfig>, StorageError>;
+
+    /// Writes the passphrase configuration, or removes it when None
+    async fn write_security(&self, config: Option<&SecurityConfig>) -> Result<(), StorageError>;
+
+    /// Inserts a session or updates the stored one with the same ID
+    async fn write_session(&self, session: &Session) -> Result<(), StorageError>;
+
+    /// Records a step completed during a session
+    async fn append_session_step(&self, step: &SessionStep) -> Result<(), StorageError>;
+
+    /// Reads all recorded sessions, oldest first
+    async fn read_sessions(&self) -> Result<Vec<Session>, StorageError>;
+
+    /// Reads the steps completed during a session, in the order they were completed
+    async fn read_session_steps(&self, session_id: Uuid) -> Result<Vec<SessionStep>, StorageError>;
+
+    /// Replaces every data set except the audit log and the passphrase configuration
+    ///
+    /// Backends write all of it or, if anything fails, none of it.
+    async fn restore(&self, data: &DataSnapshot) -> Result<(), StorageError>;
+
+    /// Rewrites every data set, the audit log and the passphrase configuration together
+    ///
+    /// Used when the key changes: everything is sealed with the key active during
+    /// the call. Backends write all of it or, if anything fails, none of it.
+    async fn rekey(
+        &self,
+        data: &DataSnapshot,
+        audit: &[AuditEntry],
+        security: Option<&SecurityConfig>,
+    ) -> Result<(), StorageError>;
+
+    /// Collects size and record counts of all data sets for diagnostics
+    async fn data_file_stats(&self) -> Result<Vec<DataFileStats>, StorageError>;
+}
+
+/// Gets the config directory inside the app data directory
+pub fn config_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(CONFIG_DIR)
+}
+
+/// Serializes records into a JSON array file, encrypting it if enabled
+pub fn encode_list<T: Serialize>(key: &DataKey, items: &[T]) -> Result<String, StorageError> {
+    let data = serde_json::to_string_pretty(items)?;
+    Ok(key.seal_file(&data)?)
+}
+
+/// Parses a JSON array file, decrypting it if it was encrypted
+pub fn decode_list<T: DeserializeOwned>(key: &DataKey, data: &str) -> Result<Vec<T>, StorageError> {
+    Ok(serde_json::from_str(&key.open_file(data)?)?)
+}
+
+/// Serializes records into lines of an append-only file, encrypting each one if enabled
+pub fn encode_lines<T: Serialize>(key: &DataKey, items: &[T]) -> Result<String, StorageError> {
+    let mut data = String::new();
+    for item in items {
+        data.push_str(&key.seal_line(&serde_json::to_string(item)?)?);
+        data.push('\n');
+    }
+    Ok(data)
+}
+
+/// Parses the lines of an append-only file such as the audit log, decrypting encrypted lines
+///
+/// Lines that cannot be parsed are skipped so a truncated final line does not
+/// hide the rest of the history.
+///
+/// # Errors
+/// Returns an error if encrypted lines are present while the app is locked
+pub fn decode_lines<T: DeserializeOwned>(
+    key: &DataKey,
+    data: &str,
+) -> Result<Vec<T>, StorageError> {
+    let mut entries = Vec::new();
+    for line in data.lines() {
+        let line = match key.open_line(line) {
+            Ok(line) => line,
+            Err(SecurityError::Locked) => return Err(SecurityError::Locked.into()),
+            Err(_) => continue,
+        };
+        if let Ok(entry) = serde_json::from_str(&line) {
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
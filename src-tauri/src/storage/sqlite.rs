@@ -0,0 +1,1005 @@
+use super::{
+    DataFileStats, DataSnapshot, JsonStorage, StorageBackend, StorageError, AUDIT_FILE,
+    BRIEFCASES_FILE, DATABASE_FILE, GROUPS_FILE, PLATFORMS_FILE, PROFILES_FILE, SECURITY_FILE,
+    SESSIONS_FILE, SESSION_STEPS_FILE, SETTINGS_FILE,
+};
+use crate::audit::AuditEntry;
+use crate::models::profile::normalize_sort_order;
+use crate::models::{BriefCase, BriefCaseGroup, Profile, Session, SessionStep};
+use crate::platforms::Platform;
+use crate::security::{DataKey, SecurityConfig, SecurityError};
+use crate::settings::Settings;
+use async_trait::async_trait;
+use chrono::Utc;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::fs;
+use uuid::Uuid;
+
+/// Schema migrations, applied in order; `PRAGMA user_version` counts the applied ones
+///
+/// Searchable and joinable values get their own columns. The complete record is
+/// kept as JSON in `data`, encrypted like the JSON files when data encryption
+/// is enabled.
+const MIGRATIONS: &[&str] = &[
+    "
+    CREATE TABLE meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+
+    CREATE TABLE profiles (
+        id BLOB PRIMARY KEY,
+        sort_order INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+
+    CREATE TABLE briefcases (
+        id BLOB PRIMARY KEY,
+        profile_id BLOB NOT NULL,
+        platform TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX briefcases_profile ON briefcases (profile_id);
+    CREATE INDEX briefcases_platform ON briefcases (platform);
+
+    CREATE TABLE briefcase_groups (
+        id BLOB PRIMARY KEY,
+        position INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+
+    CREATE TABLE audit (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        id BLOB NOT NULL UNIQUE,
+        timestamp TEXT NOT NULL,
+        entity TEXT NOT NULL,
+        entity_id BLOB,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX audit_entity ON audit (entity, entity_id);
+
+    CREATE TABLE sessions (
+        id BLOB PRIMARY KEY,
+        started_at TEXT NOT NULL,
+        ended_at TEXT,
+        total_steps INTEGER NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX sessions_started ON sessions (started_at);
+
+    CREATE TABLE tasks (
+        session_id BLOB NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+        task_index INTEGER NOT NULL,
+        task_id BLOB NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (session_id, task_index)
+    );
+    CREATE INDEX tasks_task ON tasks (task_id);
+
+    CREATE TABLE steps (
+        seq INTEGER PRIMARY KEY AUTOINCREMENT,
+        session_id BLOB NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+        step_index INTEGER NOT NULL,
+        task_index INTEGER NOT NULL,
+        briefcase_id BLOB NOT NULL,
+        profile_id BLOB NOT NULL,
+        completed_at TEXT NOT NULL,
+        data TEXT NOT NULL
+    );
+    CREATE INDEX steps_session ON steps (session_id, step_index);
+    CREATE INDEX steps_briefcase ON steps (briefcase_id, completed_at);
+",
+    "
+    -- Platforms are identified by lowercase registry IDs instead of enum names
+    UPDATE briefcases SET platform = lower(platform);
+",
+    "
+    -- Databases created by the first schema have an unused screenshots table
+    DROP TABLE IF EXISTS screenshots;
+",
+];
+
+/// Meta key of the application settings
+const SETTINGS_KEY: &str = "settings";
+/// Meta key of the passphrase configuration
+const SECURITY_KEY: &str = "security";
+/// Meta key recording when the JSON files were migrated into the database
+const JSON_MIGRATED_KEY: &str = "json_migrated_at";
+/// Tables reported in diagnostics
+const STATS_TABLES: &[&str] = &[
+    "profiles",
+    "briefcases",
+    "briefcase_groups",
+    "audit",
+    "sessions",
+    "steps",
+];
+
+/// Storage backend keeping all data in an SQLite database in the config directory
+///
+/// On first use the JSON files of the config directory are migrated into the
+/// database in one transaction and renamed to `*.migrated`. If they are
+/// encrypted, the migration waits until the app is unlocked. The user-defined
+/// platforms stay in `platforms.json` so they can still be edited by hand.
+pub struct SqliteStorage {
+    /// Directory holding the database and any JSON files to migrate
+    config_dir: PathBuf,
+    /// The open database, None until `initialize` has run
+    connection: Arc<Mutex<Option<Connection>>>,
+    /// Whether the JSON files have been migrated, to skip the check afterwards
+    json_migrated: AtomicBool,
+    /// Whether the database is opened read-only, without any migrations
+    read_only: bool,
+    /// Key the `data` columns are encrypted with, shared with the JSON files
+    key: Arc<DataKey>,
+}
+
+/// Serializes a record for a `data` column, encrypting it if enabled
+fn seal<T: Serialize>(key: &DataKey, value: &T) -> Result<String, StorageError> {
+    Ok(key.seal_line(&serde_json::to_string(value)?)?)
+}
+
+/// Parses a record from a `data` column, decrypting it if it was encrypted
+fn open<T: DeserializeOwned>(key: &DataKey, data: &str) -> Result<T, StorageError> {
+    Ok(serde_json::from_str(&key.open_line(data)?)?)
+}
+
+/// Gets the serialized name of a unit enum variant, e.g. an audit entity
+fn variant_name<T: Serialize>(value: &T) -> Result<String, StorageError> {
+    Ok(serde_json::to_value(value)?
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Reads a value from the meta table
+fn meta_get(conn: &Connection, key: &str) -> Result<Option<String>, StorageError> {
+    Ok(conn
+        .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+            row.get(0)
+        })
+        .optional()?)
+}
+
+/// Writes a value to the meta table
+fn meta_set(conn: &Connection, key: &str, value: &str) -> Result<(), StorageError> {
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+/// Reads the `data` column of every row returned by a query
+fn read_data<T: DeserializeOwned>(
+    conn: &Connection,
+    key: &DataKey,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> Result<Vec<T>, StorageError> {
+    let mut stmt = conn.prepare(sql)?;
+    let rows = stmt.query_map(params, |row| row.get::<_, String>(0))?;
+
+    let mut records = Vec::new();
+    for data in rows {
+        records.push(open(key, &data?)?);
+    }
+    Ok(records)
+}
+
+/// Replaces all rows of the profiles table
+fn replace_profiles(
+    conn: &Connection,
+    key: &DataKey,
+    profiles: &[Profile],
+) -> Result<(), StorageError> {
+    conn.execute("DELETE FROM profiles", [])?;
+    let mut stmt =
+        conn.prepare("INSERT INTO profiles (id, sort_order, data) VALUES (?1, ?2, ?3)")?;
+    for profile in profiles {
+        stmt.execute(params![
+            profile.profile_id,
+            profile.sort_order,
+            seal(key, profile)?
+        ])?;
+    }
+    Ok(())
+}
+
+/// Replaces all rows of the briefcases table
+fn replace_briefcases(
+    conn: &Connection,
+    key: &DataKey,
+    briefcases: &[BriefCase],
+) -> Result<(), StorageError> {
+    conn.execute("DELETE FROM briefcases", [])?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO briefcases (id, profile_id, platform, data) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for briefcase in briefcases {
+        stmt.execute(params![
+            briefcase.id,
+            briefcase.profile_id,
+            variant_name(&briefcase.social_media)?,
+            seal(key, briefcase)?
+        ])?;
+    }
+    Ok(())
+}
+
+/// Replaces all rows of the groups table, keeping their order
+fn replace_groups(
+    conn: &Connection,
+    key: &DataKey,
+    groups: &[BriefCaseGroup],
+) -> Result<(), StorageError> {
+    conn.execute("DELETE FROM briefcase_groups", [])?;
+    let mut stmt =
+        conn.prepare("INSERT INTO briefcase_groups (id, position, data) VALUES (?1, ?2, ?3)")?;
+    for (position, group) in groups.iter().enumerate() {
+        stmt.execute(params![group.id, position, seal(key, group)?])?;
+    }
+    Ok(())
+}
+
+/// Inserts audit entries, skipping entries that are already recorded
+fn insert_audit(
+    conn: &Connection,
+    key: &DataKey,
+    entries: &[AuditEntry],
+) -> Result<(), StorageError> {
+    let mut stmt = conn.prepare(
+        "INSERT OR IGNORE INTO audit (id, timestamp, entity, entity_id, data)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for entry in entries {
+        stmt.execute(params![
+            entry.id,
+            entry.timestamp,
+            variant_name(&entry.entity)?,
+            entry.entity_id,
+            seal(key, entry)?
+        ])?;
+    }
+    Ok(())
+}
+
+/// Inserts or updates a session together with its tasks
+fn upsert_session(conn: &Connection, key: &DataKey, session: &Session) -> Result<(), StorageError> {
+    // The tasks get their own rows, the rest of the session is kept as data
+    let data = Session {
+        tasks: Vec::new(),
+        ..session.clone()
+    };
+
+    conn.execute(
+        "INSERT INTO sessions (id, started_at, ended_at, total_steps, data)
+         VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT (id) DO UPDATE SET
+             ended_at = excluded.ended_at,
+             total_steps = excluded.total_steps,
+             data = excluded.data",
+        params![
+            session.id,
+            session.started_at,
+            session.ended_at,
+            session.total_steps,
+            seal(key, &data)?
+        ],
+    )?;
+
+    conn.execute("DELETE FROM tasks WHERE session_id = ?1", [session.id])?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO tasks (session_id, task_index, task_id, data) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (index, task) in session.tasks.iter().enumerate() {
+        stmt.execute(params![session.id, index, task.task_id, seal(key, task)?])?;
+    }
+    Ok(())
+}
+
+/// Inserts a completed session step
+fn insert_step(conn: &Connection, key: &DataKey, step: &SessionStep) -> Result<(), StorageError> {
+    conn.execute(
+        "INSERT INTO steps
+             (session_id, step_index, task_index, briefcase_id, profile_id, completed_at, data)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            step.session_id,
+            step.step_index,
+            step.task_index,
+            step.briefcase_id,
+            step.profile_id,
+            step.completed_at,
+            seal(key, step)?
+        ],
+    )?;
+    Ok(())
+}
+
+/// Replaces all sessions, their tasks and their steps
+fn replace_history(
+    conn: &Connection,
+    key: &DataKey,
+    sessions: &[Session],
+    steps: &[SessionStep],
+) -> Result<(), StorageError> {
+    conn.execute("DELETE FROM steps", [])?;
+    conn.execute("DELETE FROM tasks", [])?;
+    conn.execute("DELETE FROM sessions", [])?;
+    for session in sessions {
+        upsert_session(conn, key, session)?;
+    }
+    for step in steps {
+        insert_step(conn, key, step)?;
+    }
+    Ok(())
+}
+
+/// Replaces every data set except the audit log and the passphrase configuration
+///
+/// # Arguments
+/// * `settings` - The snapshot's settings, already serialized
+fn restore_data(
+    conn: &Connection,
+    key: &DataKey,
+    data: &DataSnapshot,
+    settings: &str,
+) -> Result<(), StorageError> {
+    replace_profiles(conn, key, &data.profiles)?;
+    replace_briefcases(conn, key, &data.briefcases)?;
+    replace_groups(conn, key, &data.groups)?;
+    meta_set(conn, SETTINGS_KEY, settings)?;
+    replace_history(conn, key, &data.sessions, &data.steps)
+}
+
+/// Everything read from the JSON files for the migration
+struct JsonData {
+    profiles: Vec<Profile>,
+    briefcases: Vec<BriefCase>,
+    groups: Vec<BriefCaseGroup>,
+    audit: Vec<AuditEntry>,
+    sessions: Vec<Session>,
+    steps: Vec<SessionStep>,
+    /// Files that could not be parsed and are left in place
+    unreadable: Vec<&'static str>,
+}
+
+/// Keeps a data set read for the migration, or records its file as unreadable
+///
+/// A corrupt file is logged and migrated as empty so it does not keep the app
+/// from starting. Locked data is passed on so the migration waits for the passphrase.
+fn readable<T: Default>(
+    name: &'static str,
+    result: Result<T, StorageError>,
+    unreadable: &mut Vec<&'static str>,
+) -> Result<T, StorageError> {
+    match result {
+        Ok(data) => Ok(data),
+        Err(e @ StorageError::Security(SecurityError::Locked)) => Err(e),
+        Err(e) => {
+            log::error!("Failed to migrate {}, leaving it in place: {}", name, e);
+            unreadable.push(name);
+            Ok(T::default())
+        }
+    }
+}
+
+impl SqliteStorage {
+    /// Creates a backend storing its database in the given directory
+    ///
+    /// # Arguments
+    /// * `config_dir` - Directory holding the database, created by `initialize`
+    pub fn new(config_dir: PathBuf) -> Self {
+        Self {
+            config_dir,
+            connection: Arc::new(Mutex::new(None)),
+            json_migrated: AtomicBool::new(false),
+            read_only: false,
+            key: Arc::default(),
+        }
+    }
+
+    /// Creates a backend reading an existing, fully migrated database
+    ///
+    /// `initialize` fails with `StorageError::ReadOnly` if the database does
+    /// not exist yet, its schema is outdated or JSON files still have to be
+    /// migrated into it.
+    ///
+    /// # Arguments
+    /// * `config_dir` - Directory holding the database
+    pub fn read_only(config_dir: PathBuf) -> Self {
+        Self {
+            read_only: true,
+            ..Self::new(config_dir)
+        }
+    }
+
+    /// Runs a closure with the database connection and the data key on a blocking thread
+    async fn run<T, F>(&self, f: F) -> Result<T, StorageError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection, &DataKey) -> Result<T, StorageError> + Send + 'static,
+    {
+        let (connection, key) = (self.connection.clone(), self.key.clone());
+
+        tokio::task::spawn_blocking(move || {
+            let mut connection = connection.lock().unwrap_or_else(|e| e.into_inner());
+            let conn = connection.as_mut().ok_or(StorageError::NotInitialized)?;
+            f(conn, &key)
+        })
+        .await
+        .map_err(|e| StorageError::Io(std::io::Error::other(e)))?
+    }
+
+    /// Runs a closure inside a transaction, committing only if it succeeds
+    async fn transaction<T, F>(&self, f: F) -> Result<T, StorageError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection, &DataKey) -> Result<T, StorageError> + Send + 'static,
+    {
+        self.run(|conn, key| {
+            let tx = conn.transaction()?;
+            let result = f(&tx, key)?;
+            tx.commit()?;
+            Ok(result)
+        })
+        .await
+    }
+
+    /// Gets the JSON backend for the files kept next to the database
+    fn json(&self) -> JsonStorage {
+        JsonStorage::with_key(self.config_dir.clone(), self.key.clone())
+    }
+
+    /// Runs a transaction that also replaces the platforms file
+    ///
+    /// The file is staged first and only moved into place once the transaction
+    /// has been committed.
+    async fn transaction_with_platforms<F>(
+        &self,
+        platforms: &[Platform],
+        f: F,
+    ) -> Result<(), StorageError>
+    where
+        F: FnOnce(&Connection, &DataKey) -> Result<(), StorageError> + Send + 'static,
+    {
+        let json = self.json();
+        let platforms = serde_json::to_string_pretty(platforms)?;
+        json.stage_files(&[(PLATFORMS_FILE, platforms)]).await?;
+
+        match self.transaction(f).await {
+            Ok(()) => json.commit_files(&[PLATFORMS_FILE]).await,
+            Err(e) => {
+                json.discard_files(&[PLATFORMS_FILE]).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Opens the database read-only, refusing an outdated schema
+    fn open_database_read_only(path: PathBuf) -> Result<Connection, StorageError> {
+        if !path.exists() {
+            return Err(StorageError::ReadOnly(format!(
+                "{} does not exist yet",
+                DATABASE_FILE
+            )));
+        }
+
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version < MIGRATIONS.len() {
+            return Err(StorageError::ReadOnly(format!(
+                "The schema of {} is outdated",
+                DATABASE_FILE
+            )));
+        }
+
+        Ok(conn)
+    }
+
+    /// Opens the database and applies pending schema migrations
+    fn open_database(path: PathBuf) -> Result<Connection, StorageError> {
+        let mut conn = Connection::open(path)?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+
+        let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version < MIGRATIONS.len() {
+            let tx = conn.transaction()?;
+            for migration in &MIGRATIONS[version..] {
+                tx.execute_batch(migration)?;
+            }
+            tx.pragma_update(None, "user_version", MIGRATIONS.len())?;
+            tx.commit()?;
+            log::info!(
+                "Migrated database schema from version {} to {}",
+                version,
+                MIGRATIONS.len()
+            );
+        }
+
+        Ok(conn)
+    }
+
+    /// Reads the encrypted data sets from the JSON files
+    async fn read_json_data(json: &JsonStorage) -> Result<JsonData, StorageError> {
+        let mut unreadable = Vec::new();
+
+        let sessions = readable(SESSIONS_FILE, json.read_sessions().await, &mut unreadable)?;
+        let mut steps = Vec::new();
+        if unreadable.contains(&SESSIONS_FILE) {
+            // Steps cannot be attached to sessions that were not migrated
+            unreadable.push(SESSION_STEPS_FILE);
+        } else {
+            for session in &sessions {
+                let session_steps = readable(
+                    SESSION_STEPS_FILE,
+                    json.read_session_steps(session.id).await,
+                    &mut unreadable,
+                )?;
+                if unreadable.contains(&SESSION_STEPS_FILE) {
+                    steps.clear();
+                    break;
+                }
+                steps.extend(session_steps);
+            }
+        }
+
+        Ok(JsonData {
+            profiles: readable(PROFILES_FILE, json.read_profiles().await, &mut unreadable)?,
+            briefcases: readable(
+                BRIEFCASES_FILE,
+                json.read_briefcases().await,
+                &mut unreadable,
+            )?,
+            groups: readable(GROUPS_FILE, json.read_groups().await, &mut unreadable)?,
+            audit: readable(AUDIT_FILE, json.read_audit().await, &mut unreadable)?,
+            sessions,
+            steps,
+            unreadable,
+        })
+    }
+
+    /// Moves the data of the JSON backend into the database, once
+    ///
+    /// Settings and the passphrase configuration are copied right away. The
+    /// other data sets are copied in one transaction once they can be
+    /// decrypted; until then the migration is retried on every read. Files that
+    /// cannot be parsed are logged and left in place without being migrated.
+    async fn migrate_json(&self) -> Result<(), StorageError> {
+        if self.json_migrated.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        if self
+            .run(|conn, _| meta_get(conn, JSON_MIGRATED_KEY))
+            .await?
+            .is_some()
+        {
+            self.json_migrated.store(true, Ordering::SeqCst);
+            return Ok(());
+        }
+
+        let json = self.json();
+        let json_files: Vec<&str> = [
+            PROFILES_FILE,
+            BRIEFCASES_FILE,
+            GROUPS_FILE,
+            SETTINGS_FILE,
+            AUDIT_FILE,
+            SECURITY_FILE,
+            SESSIONS_FILE,
+            SESSION_STEPS_FILE,
+        ]
+        .into_iter()
+        .filter(|name| self.config_dir.join(name).exists())
+        .collect();
+
+        if self.read_only {
+            if !json_files.is_empty() {
+                return Err(StorageError::ReadOnly(format!(
+                    "The JSON files have not been migrated into {} yet",
+                    DATABASE_FILE
+                )));
+            }
+            self.json_migrated.store(true, Ordering::SeqCst);
+            return Ok(());
+        }
+
+        if !json_files.is_empty() {
+            // Plaintext parts first, so a locked app can still be unlocked
+            let mut unreadable = Vec::new();
+            let settings = match json.read_settings().await {
+                Ok(settings) => Some(serde_json::to_string(&settings)?),
+                Err(e) => {
+                    log::error!(
+                        "Failed to migrate {}, leaving it in place: {}",
+                        SETTINGS_FILE,
+                        e
+                    );
+                    unreadable.push(SETTINGS_FILE);
+                    None
+                }
+            };
+            let security = json
+                .read_security()
+                .await?
+                .map(|config| serde_json::to_string(&config))
+                .transpose()?;
+            self.run(move |conn, _| {
+                if let Some(settings) = settings {
+                    conn.execute(
+                        "INSERT OR IGNORE INTO meta (key, value) VALUES (?1, ?2)",
+                        params![SETTINGS_KEY, settings],
+                    )?;
+                }
+                if let Some(security) = security {
+                    conn.execute(
+                        "INSERT OR IGNORE INTO meta (key, value) VALUES (?1, ?2)",
+                        params![SECURITY_KEY, security],
+                    )?;
+                }
+                Ok(())
+            })
+            .await?;
+
+            let mut data = match Self::read_json_data(&json).await {
+                Ok(data) => data,
+                Err(StorageError::Security(SecurityError::Locked)) => {
+                    log::info!("JSON data is encrypted, migrating it once the app is unlocked");
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
+            };
+
+            unreadable.append(&mut data.unreadable);
+            log::info!(
+                "Migrating {} profiles, {} briefcases and {} sessions into {}",
+                data.profiles.len(),
+                data.briefcases.len(),
+                data.sessions.len(),
+                DATABASE_FILE
+            );
+            self.transaction(move |tx, key| {
+                replace_profiles(tx, key, &data.profiles)?;
+                replace_briefcases(tx, key, &data.briefcases)?;
+                replace_groups(tx, key, &data.groups)?;
+                insert_audit(tx, key, &data.audit)?;
+                for session in &data.sessions {
+                    upsert_session(tx, key, session)?;
+                }
+                for step in &data.steps {
+                    insert_step(tx, key, step)?;
+                }
+                meta_set(tx, JSON_MIGRATED_KEY, &Utc::now().to_rfc3339())
+            })
+            .await?;
+
+            // Keep the old files around, but out of the way of the JSON backend
+            for name in json_files
+                .into_iter()
+                .filter(|name| !unreadable.contains(name))
+            {
+                let path = self.config_dir.join(name);
+                fs::rename(&path, path.with_file_name(format!("{}.migrated", name))).await?;
+            }
+        } else {
+            self.run(|conn, _| meta_set(conn, JSON_MIGRATED_KEY, &Utc::now().to_rfc3339()))
+                .await?;
+        }
+
+        self.json_migrated.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StorageBackend for SqliteStorage {
+    fn name(&self) -> &'static str {
+        "sqlite"
+    }
+
+    fn data_key(&self) -> &DataKey {
+        &self.key
+    }
+
+    async fn initialize(&self) -> Result<(), StorageError> {
+        if !self.read_only {
+            fs::create_dir_all(&self.config_dir).await?;
+        }
+
+        let path = self.config_dir.join(DATABASE_FILE);
+        let read_only = self.read_only;
+        let conn = tokio::task::spawn_blocking(move || {
+            if read_only {
+                Self::open_database_read_only(path)
+            } else {
+                Self::open_database(path)
+            }
+        })
+        .await
+        .map_err(|e| StorageError::Io(std::io::Error::other(e)))??;
+        *self.connection.lock().unwrap_or_else(|e| e.into_inner()) = Some(conn);
+
+        self.migrate_json().await
+    }
+
+    async fn read_profiles(&self) -> Result<Vec<Profile>, StorageError> {
+        self.migrate_json().await?;
+
+        let mut profiles = self
+            .run(|conn, key| {
+                read_data(
+                    conn,
+                    key,
+                    "SELECT data FROM profiles ORDER BY sort_order",
+                    [],
+                )
+            })
+            .await?;
+        normalize_sort_order(&mut profiles);
+
+        Ok(profiles)
+    }
+
+    async fn write_profiles(&self, profiles: &[Profile]) -> Result<(), StorageError> {
+        let profiles = profiles.to_vec();
+        self.transaction(move |tx, key| replace_profiles(tx, key, &profiles))
+            .await
+    }
+
+    async fn read_briefcases(&self) -> Result<Vec<BriefCase>, StorageError> {
+        self.migrate_json().await?;
+
+        self.run(|conn, key| read_data(conn, key, "SELECT data FROM briefcases ORDER BY rowid", []))
+            .await
+    }
+
+    async fn write_briefcases(&self, briefcases: &[BriefCase]) -> Result<(), StorageError> {
+        let briefcases = briefcases.to_vec();
+        self.transaction(move |tx, key| replace_briefcases(tx, key, &briefcases))
+            .await
+    }
+
+    async fn read_groups(&self) -> Result<Vec<BriefCaseGroup>, StorageError> {
+        self.migrate_json().await?;
+
+        self.run(|conn, key| {
+            read_data(
+                conn,
+                key,
+                "SELECT data FROM briefcase_groups ORDER BY position",
+                [],
+            )
+        })
+        .await
+    }
+
+    async fn write_groups(&self, groups: &[BriefCaseGroup]) -> Result<(), StorageError> {
+        let groups = groups.to_vec();
+        self.transaction(move |tx, key| replace_groups(tx, key, &groups))
+            .await
+    }
+
+    async fn write_data(
+        &self,
+        profiles: &[Profile],
+        briefcases: &[BriefCase],
+        groups: &[BriefCaseGroup],
+    ) -> Result<(), StorageError> {
+        let (profiles, briefcases, groups) =
+            (profiles.to_vec(), briefcases.to_vec(), groups.to_vec());
+        self.transaction(move |tx, key| {
+            replace_profiles(tx, key, &profiles)?;
+            replace_briefcases(tx, key, &briefcases)?;
+            replace_groups(tx, key, &groups)
+        })
+        .await
+    }
+
+    async fn read_settings(&self) -> Result<Settings, StorageError> {
+        let settings = self.run(|conn, _| meta_get(conn, SETTINGS_KEY)).await?;
+        match settings {
+            Some(data) => Ok(serde_json::from_str(&data)?),
+            None => Ok(Settings::default()),
+        }
+    }
+
+    async fn write_settings(&self, settings: &Settings) -> Result<(), StorageError> {
+        let data = serde_json::to_string(settings)?;
+        self.run(move |conn, _| meta_set(conn, SETTINGS_KEY, &data))
+            .await
+    }
+
+    async fn read_platforms(&self) -> Result<Vec<Platform>, StorageError> {
+        self.json().read_platforms().await
+    }
+
+    async fn write_platforms(&self, platforms: &[Platform]) -> Result<(), StorageError> {
+        self.json().write_platforms(platforms).await
+    }
+
+    async fn append_audit(&self, entries: &[AuditEntry]) -> Result<(), StorageError> {
+        let entries = entries.to_vec();
+        self.transaction(move |tx, key| insert_audit(tx, key, &entries))
+            .await
+    }
+
+    async fn read_audit(&self) -> Result<Vec<AuditEntry>, StorageError> {
+        self.migrate_json().await?;
+
+        self.run(|conn, key| read_data(conn, key, "SELECT data FROM audit ORDER BY seq", []))
+            .await
+    }
+
+    async fn trim_audit(&self, keep: usize) -> Result<(), StorageError> {
+        let dropped = self
+            .run(move |conn, _| {
+                Ok(conn.execute(
+                    "DELETE FROM audit WHERE seq NOT IN
+                     (SELECT seq FROM audit ORDER BY seq DESC LIMIT ?1)",
+                    [keep as i64],
+                )?)
+            })
+            .await?;
+        if dropped > 0 {
+            log::info!("Dropped {} old audit entries", dropped);
+        }
+        Ok(())
+    }
+
+    async fn read_security(&self) -> Result<Option<SecurityConfig>, StorageError> {
+        let security = self.run(|conn, _| meta_get(conn, SECURITY_KEY)).await?;
+        Ok(security
+            .map(|data| serde_json::from_str(&data))
+            .transpose()?)
+    }
+
+    async fn write_security(&self, config: Option<&SecurityConfig>) -> Result<(), StorageError> {
+        let data = config.map(serde_json::to_string).transpose()?;
+        self.run(move |conn, _| {
+            match data {
+                Some(data) => meta_set(conn, SECURITY_KEY, &data)?,
+                None => {
+                    conn.execute("DELETE FROM meta WHERE key = ?1", [SECURITY_KEY])?;
+                }
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn write_session(&self, session: &Session) -> Result<(), StorageError> {
+        let session = session.clone();
+        self.transaction(move |tx, key| upsert_session(tx, key, &session))
+            .await
+    }
+
+    async fn append_session_step(&self, step: &SessionStep) -> Result<(), StorageError> {
+        let step = step.clone();
+        self.run(move |conn, key| insert_step(conn, key, &step))
+            .await
+    }
+
+    async fn read_sessions(&self) -> Result<Vec<Session>, StorageError> {
+        self.migrate_json().await?;
+
+        self.run(|conn, key| {
+            let mut stmt =
+                conn.prepare("SELECT id, data FROM sessions ORDER BY started_at, rowid")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, Uuid>(0)?, row.get::<_, String>(1)?))
+            })?;
+
+            let mut sessions = Vec::new();
+            for row in rows {
+                let (id, data) = row?;
+                let mut session: Session = open(key, &data)?;
+                session.tasks = read_data(
+                    conn,
+                    key,
+                    "SELECT data FROM tasks WHERE session_id = ?1 ORDER BY task_index",
+                    [id],
+                )?;
+                sessions.push(session);
+            }
+            Ok(sessions)
+        })
+        .await
+    }
+
+    async fn read_session_steps(&self, session_id: Uuid) -> Result<Vec<SessionStep>, StorageError> {
+        self.run(move |conn, key| {
+            read_data(
+                conn,
+                key,
+                "SELECT data FROM steps WHERE session_id = ?1 ORDER BY seq",
+                [session_id],
+            )
+        })
+        .await
+    }
+
+    async fn restore(&self, data: &DataSnapshot) -> Result<(), StorageError> {
+        let platforms = data.platforms.clone();
+        let data = data.clone();
+        let settings = serde_json::to_string(&data.settings)?;
+        self.transaction_with_platforms(&platforms, move |tx, key| {
+            restore_data(tx, key, &data, &settings)
+        })
+        .await
+    }
+
+    async fn rekey(
+        &self,
+        data: &DataSnapshot,
+        audit: &[AuditEntry],
+        security: Option<&SecurityConfig>,
+    ) -> Result<(), StorageError> {
+        let platforms = data.platforms.clone();
+        let (data, audit) = (data.clone(), audit.to_vec());
+        let settings = serde_json::to_string(&data.settings)?;
+        let security = security.map(serde_json::to_string).transpose()?;
+        self.transaction_with_platforms(&platforms, move |tx, key| {
+            restore_data(tx, key, &data, &settings)?;
+            tx.execute("DELETE FROM audit", [])?;
+            insert_audit(tx, key, &audit)?;
+            match security {
+                Some(security) => meta_set(tx, SECURITY_KEY, &security)?,
+                None => {
+                    tx.execute("DELETE FROM meta WHERE key = ?1", [SECURITY_KEY])?;
+                }
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn data_file_stats(&self) -> Result<Vec<DataFileStats>, StorageError> {
+        let path = self.config_dir.join(DATABASE_FILE);
+        let metadata = fs::metadata(&path).await?;
+        let mut stats = vec![DataFileStats {
+            name: DATABASE_FILE.to_string(),
+            exists: true,
+            size_bytes: metadata.len(),
+            modified: metadata
+                .modified()
+                .ok()
+                .map(|time| chrono::DateTime::<Utc>::from(time).to_rfc3339()),
+            records: None,
+        }];
+
+        let tables = self
+            .run(|conn, _| {
+                let mut tables = Vec::new();
+                for table in STATS_TABLES {
+                    let (records, size_bytes): (usize, u64) = conn.query_row(
+                        &format!(
+                            "SELECT COUNT(*), COALESCE(SUM(LENGTH(data)), 0) FROM {}",
+                            table
+                        ),
+                        [],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )?;
+                    tables.push(DataFileStats {
+                        name: table.to_string(),
+                        exists: true,
+                        size_bytes,
+                        modified: None,
+                        records: Some(records),
+                    });
+                }
+                Ok(tables)
+            })
+            .await?;
+        stats.extend(tables);
+
+        Ok(stats)
+    }
+}
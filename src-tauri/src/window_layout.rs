@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+
+/// Smallest share of the screen the profile window may take
+pub const MIN_SPLIT_RATIO: f64 = 0.2;
+/// Largest share of the screen the profile window may take
+pub const MAX_SPLIT_RATIO: f64 = 0.9;
+
+/// Side of the screen the control panel is placed on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub enum PanelSide {
+    /// Panel on the left, profile window on the right
+    Left,
+    /// Profile window on the left, panel on the right
+    Right,
+}
+
+/// How the profile window and the control panel share the screen
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowLayout {
+    /// Share of the screen width taken by the profile window (0.2 to 0.9)
+    pub split_ratio: f64,
+    /// Side of the screen the panel is placed on
+    pub panel_side: PanelSide,
+    /// Name of the monitor to tile on, None for the primary monitor
+    pub monitor: Option<String>,
+    /// Whether the panel stays above the profile window
+    pub panel_always_on_top: bool,
+}
+
+impl Default for WindowLayout {
+    fn default() -> Self {
+        Self {
+            split_ratio: 0.66,
+            panel_side: PanelSide::Right,
+            monitor: None,
+            panel_always_on_top: false,
+        }
+    }
+}
+
+/// A window rectangle in logical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Rect {
+    /// Left edge
+    pub x: f64,
+    /// Top edge
+    pub y: f64,
+    /// Width
+    pub width: f64,
+    /// Height
+    pub height: f64,
+}
+
+/// Description of a connected monitor for the layout settings UI
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorInfo {
+    /// OS name of the monitor, used as `WindowLayout::monitor`
+    pub name: Option<String>,
+    /// Logical area covered by the monitor
+    pub area: Rect,
+    /// Whether this is the primary monitor
+    pub is_primary: bool,
+}
+
+impl WindowLayout {
+    /// Checks that the layout can be applied
+    pub fn validate(&self) -> Result<(), String> {
+        if !(MIN_SPLIT_RATIO..=MAX_SPLIT_RATIO).contains(&self.split_ratio) {
+            return Err(format!(
+                "Split ratio must be between {} and {}",
+                MIN_SPLIT_RATIO, MAX_SPLIT_RATIO
+            ));
+        }
+        Ok(())
+    }
+
+    /// Brings the split ratio back into range, e.g. after the file was edited by hand
+    pub fn clamp(&mut self) {
+        if self.split_ratio.is_nan() {
+            self.split_ratio = Self::default().split_ratio;
+        }
+        self.split_ratio = self.split_ratio.clamp(MIN_SPLIT_RATIO, MAX_SPLIT_RATIO);
+    }
+
+    /// Picks the area of the monitor selected by the layout
+    ///
+    /// Falls back to the primary monitor when the configured one is
+    /// disconnected, and to the first one if none is reported as primary.
+    ///
+    /// # Returns
+    /// The monitor's area, None if no monitor is connected
+    pub fn monitor_area(&self, monitors: &[MonitorInfo]) -> Option<Rect> {
+        let configured = self
+            .monitor
+            .as_ref()
+            .and_then(|name| monitors.iter().find(|m| m.name.as_ref() == Some(name)));
+
+        configured
+            .or_else(|| monitors.iter().find(|m| m.is_primary))
+            .or_else(|| monitors.first())
+            .map(|monitor| monitor.area)
+    }
+
+    /// Splits a monitor area into the profile window and panel rectangles
+    ///
+    /// # Returns
+    /// A tuple of (profile_rect, panel_rect)
+    pub fn tile(&self, area: Rect) -> (Rect, Rect) {
+        let profile_width = (area.width * self.split_ratio).floor();
+        let panel_width = area.width - profile_width;
+
+        let (profile_x, panel_x) = match self.panel_side {
+            PanelSide::Right => (area.x, area.x + profile_width),
+            PanelSide::Left => (area.x + panel_width, area.x),
+        };
+
+        (
+            Rect {
+                x: profile_x,
+                y: area.y,
+                width: profile_width,
+                height: area.height,
+            },
+            Rect {
+                x: panel_x,
+                y: area.y,
+                width: panel_width,
+                height: area.height,
+            },
+        )
+    }
+}
@@ -0,0 +1,219 @@
+//! Checks what the audit log records and how its entries are reverted
+
+mod common;
+
+use attache_lib::audit::{self, AuditAction, AuditEntity, AuditEntry, AuditSource, Revert};
+use attache_lib::automation;
+use attache_lib::data;
+use attache_lib::models::{Profile, SocialMedia, Task};
+use attache_lib::settings::Settings;
+use common::{tasks_json, Fixture};
+use serde_json::json;
+
+/// The audit source the tests record with
+fn source() -> AuditSource {
+    AuditSource::command("test")
+}
+
+#[test]
+fn updates_record_only_the_changed_fields() {
+    let previous = Settings::default();
+    let mut settings = previous.clone();
+    settings.shortcuts.next = "CommandOrControl+N".to_string();
+    settings.layout.split_ratio = 0.5;
+
+    let entry = audit::update(source(), AuditEntity::Settings, None, &previous, &settings)
+        .expect("settings changed");
+    assert_eq!(entry.action, AuditAction::Update);
+    assert_eq!(
+        entry.before,
+        Some(json!({
+            "shortcuts": {"next": previous.shortcuts.next},
+            "layout": {"split_ratio": previous.layout.split_ratio},
+        }))
+    );
+    assert_eq!(
+        entry.after,
+        Some(json!({
+            "shortcuts": {"next": "CommandOrControl+N"},
+            "layout": {"split_ratio": 0.5},
+        }))
+    );
+
+    assert!(audit::update(source(), AuditEntity::Settings, None, &settings, &settings).is_none());
+}
+
+#[test]
+fn reverting_an_update_keeps_later_changes_to_other_fields() {
+    let previous = Settings::default();
+    let mut changed = previous.clone();
+    changed.shortcuts.next = "CommandOrControl+N".to_string();
+    let entry = audit::update(source(), AuditEntity::Settings, None, &previous, &changed).unwrap();
+
+    let mut current = changed.clone();
+    current.auto_copy_comment = !previous.auto_copy_comment;
+    current.layout.split_ratio = 0.5;
+
+    let reverted = audit::revert_fields(&entry, &current).unwrap();
+    assert_eq!(reverted.shortcuts.next, previous.shortcuts.next);
+    assert_eq!(reverted.auto_copy_comment, current.auto_copy_comment);
+    assert_eq!(reverted.layout.split_ratio, 0.5);
+}
+
+#[tokio::test]
+async fn reverting_a_profile_update_restores_only_its_fields() {
+    let fixture = Fixture::new().await;
+    let original = fixture.add_profile("original").await;
+
+    let mut renamed = original.clone();
+    renamed.profile_name = "renamed".to_string();
+    let entries = audit::diff(
+        source(),
+        AuditEntity::Profile,
+        std::slice::from_ref(&original),
+        std::slice::from_ref(&renamed),
+        |p| p.profile_id,
+    );
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].before, Some(json!({"profile_name": "original"})));
+
+    let mut current = renamed.clone();
+    current.notes = Some("added later".to_string());
+    let mut profiles = vec![current];
+    audit::revert_in(&entries[0], &mut profiles, |p| p.profile_id).unwrap();
+    assert_eq!(profiles[0].profile_name, "original");
+    assert_eq!(profiles[0].notes.as_deref(), Some("added later"));
+
+    // An update cannot be reverted once the record is gone
+    let mut none: Vec<Profile> = Vec::new();
+    assert!(audit::revert_in(&entries[0], &mut none, |p| p.profile_id).is_err());
+}
+
+#[tokio::test]
+async fn tasks_are_recorded_without_their_accounts() {
+    let fixture = Fixture::new().await;
+    let profile = fixture.add_profile("main").await;
+    let briefcase = fixture.add_briefcase(&profile, SocialMedia::X).await;
+
+    let task = Task::new(
+        "https://x.com/post/1".to_string(),
+        vec!["a".to_string(), "b".to_string()],
+        SocialMedia::X,
+        &[briefcase],
+        None,
+    );
+    let created = audit::diff_tasks(source(), &[], std::slice::from_ref(&task));
+    let recorded = created[0].after.as_ref().unwrap();
+    assert_eq!(recorded["link"], "https://x.com/post/1");
+    assert!(recorded.get("related_brief_cases").is_none());
+
+    let mut advanced = task.clone();
+    advanced.comment_index = 1;
+    let updated = audit::diff_tasks(source(), &[task], &[advanced]);
+    assert_eq!(updated[0].before, Some(json!({"comment_index": 0})));
+    assert_eq!(updated[0].after, Some(json!({"comment_index": 1})));
+}
+
+#[tokio::test]
+async fn the_log_keeps_only_the_newest_entries() {
+    let fixture = Fixture::new().await;
+    let state = &fixture.state;
+
+    let entries: Vec<AuditEntry> = (0..audit::MAX_AUDIT_ENTRIES + 10)
+        .map(|_| AuditEntry::new(source(), AuditEntity::Backup, None, None, None))
+        .collect();
+    let newest = entries.last().unwrap().id;
+    let oldest_kept = entries[10].id;
+    audit::record(state, entries).await.unwrap();
+
+    let log = state.storage().read_audit().await.unwrap();
+    assert_eq!(log.len(), audit::MAX_AUDIT_ENTRIES);
+    assert_eq!(log[0].id, oldest_kept);
+    assert_eq!(log.last().unwrap().id, newest);
+}
+
+#[tokio::test]
+async fn reverting_a_profile_rename_restores_the_old_name() {
+    let fixture = Fixture::new().await;
+    let profile = fixture.add_profile("original").await;
+    let state = &fixture.state;
+
+    let mut renamed = profile.clone();
+    renamed.profile_name = "renamed".to_string();
+    data::save_profiles(state, vec![renamed], source())
+        .await
+        .unwrap();
+    let entry = state.storage().read_audit().await.unwrap()[0].clone();
+
+    let Revert::Profiles(profiles) = audit::revert(state, entry.id).await.unwrap() else {
+        panic!("expected profiles");
+    };
+    assert_eq!(profiles[0].profile_name, "original");
+    // Nothing is saved until the revert is applied
+    assert_eq!(state.get_profiles().await[0].profile_name, "renamed");
+
+    data::save_profiles(state, profiles, AuditSource::revert(entry.id))
+        .await
+        .unwrap();
+    let log = state.storage().read_audit().await.unwrap();
+    assert_eq!(log.last().unwrap().reverts, Some(entry.id));
+}
+
+#[tokio::test]
+async fn reverting_settings_restores_only_the_changed_fields() {
+    let fixture = Fixture::new().await;
+    let state = &fixture.state;
+    let previous = state.get_settings().await;
+    let mut changed = previous.clone();
+    changed.hide_window_on_pause = !previous.hide_window_on_pause;
+    let entry = audit::update(source(), AuditEntity::Settings, None, &previous, &changed).unwrap();
+    audit::record(state, vec![entry.clone()]).await.unwrap();
+
+    let mut current = changed.clone();
+    current.auto_copy_comment = !previous.auto_copy_comment;
+    state.set_settings(current.clone()).await;
+
+    let Revert::Settings(settings) = audit::revert(state, entry.id).await.unwrap() else {
+        panic!("expected settings");
+    };
+    assert_eq!(settings.hide_window_on_pause, previous.hide_window_on_pause);
+    assert_eq!(settings.auto_copy_comment, current.auto_copy_comment);
+}
+
+#[tokio::test]
+async fn reverting_a_comment_change_finds_the_task_in_the_session() {
+    let fixture = Fixture::new().await;
+    let profile = fixture.add_profile("main").await;
+    fixture.add_briefcase(&profile, SocialMedia::X).await;
+    let state = &fixture.state;
+    let tasks = tasks_json(&[
+        ("https://x.com/post/1", SocialMedia::X, &["a"]),
+        ("https://x.com/post/2", SocialMedia::X, &["a", "b"]),
+    ]);
+    automation::start(state, &tasks, source()).await.unwrap();
+
+    let before = state.get_task(1).await.unwrap();
+    state.set_task_comment_index(1, 1).await;
+    let after = state.get_task(1).await.unwrap();
+    let entries = audit::diff_tasks(source(), &[before], &[after]);
+    audit::record(state, entries.clone()).await.unwrap();
+
+    match audit::revert(state, entries[0].id).await.unwrap() {
+        Revert::CommentIndex {
+            task_index,
+            comment_index,
+        } => assert_eq!((task_index, comment_index), (1, 0)),
+        other => panic!("unexpected revert {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn backup_imports_and_unknown_entries_cannot_be_reverted() {
+    let fixture = Fixture::new().await;
+    let state = &fixture.state;
+    let import = AuditEntry::new(source(), AuditEntity::Backup, None, None, None);
+    audit::record(state, vec![import.clone()]).await.unwrap();
+
+    assert!(audit::revert(state, import.id).await.is_err());
+    assert!(audit::revert(state, uuid::Uuid::new_v4()).await.is_err());
+}
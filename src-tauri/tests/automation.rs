@@ -0,0 +1,428 @@
+//! Drives complete automation runs through the session service without a window system
+
+mod common;
+
+use attache_lib::audit::AuditSource;
+use attache_lib::automation;
+use attache_lib::models::{SocialMedia, Task};
+use chrono::{Duration, Utc};
+use common::{tasks_json, Fixture};
+
+/// The audit source the tests start their sessions with
+fn source() -> AuditSource {
+    AuditSource::command("test")
+}
+
+#[tokio::test]
+async fn full_run_visits_every_account_once_grouped_by_profile() {
+    let fixture = Fixture::new().await;
+    let first = fixture.add_profile("first").await;
+    let second = fixture.add_profile("second").await;
+    fixture.add_briefcase(&first, SocialMedia::X).await;
+    fixture.add_briefcase(&first, SocialMedia::YOUTUBE).await;
+    fixture.add_briefcase(&second, SocialMedia::X).await;
+
+    let tasks = tasks_json(&[
+        ("https://x.com/post/1", SocialMedia::X, &["one", "two"]),
+        (
+            "https://youtube.com/watch?v=1",
+            SocialMedia::YOUTUBE,
+            &["hi"],
+        ),
+        ("https://x.com/post/2", SocialMedia::X, &["three"]),
+    ]);
+
+    let state = &fixture.state;
+    let mut result = automation::start(state, &tasks, source()).await.unwrap();
+    assert!(state.is_running().await);
+    assert!(result.should_change_profile);
+
+    let mut profiles_visited = vec![result.profile_id];
+    let mut comments = vec![result.comment.clone()];
+    loop {
+        automation::complete_current(state).await.unwrap();
+        result = automation::advance(state).await.unwrap();
+        if result.completed {
+            break;
+        }
+        if result.should_change_profile {
+            profiles_visited.push(result.profile_id);
+        }
+        comments.push(result.comment.clone());
+    }
+
+    // Each profile is opened exactly once, in the user's order
+    assert_eq!(profiles_visited, vec![first.profile_id, second.profile_id]);
+    assert_eq!(comments, vec!["one", "hi", "three", "two", "three"]);
+    assert_eq!(
+        state.get_task_progress().await,
+        vec![(0, 2, 2), (1, 1, 1), (2, 2, 2)]
+    );
+    assert!(state.get_session().await.is_none());
+
+    let sessions = state.storage().read_sessions().await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].total_steps, 5);
+    assert!(sessions[0].ended_at.is_some());
+
+    let steps = state
+        .storage()
+        .read_session_steps(sessions[0].id)
+        .await
+        .unwrap();
+    let recorded: Vec<_> = steps.iter().map(|s| s.comment.as_deref()).collect();
+    assert_eq!(
+        recorded,
+        vec![
+            Some("one"),
+            Some("hi"),
+            Some("three"),
+            Some("two"),
+            Some("three")
+        ]
+    );
+    assert!(steps.windows(2).all(|w| w[0].step_index < w[1].step_index));
+}
+
+#[tokio::test]
+async fn skipped_steps_are_not_recorded() {
+    let fixture = Fixture::new().await;
+    let profile = fixture.add_profile("only").await;
+    fixture.add_briefcase(&profile, SocialMedia::X).await;
+    let other = fixture.add_profile("other").await;
+    fixture.add_briefcase(&other, SocialMedia::X).await;
+
+    let tasks = tasks_json(&[("https://x.com/post/1", SocialMedia::X, &["a"])]);
+    let state = &fixture.state;
+    automation::start(state, &tasks, source()).await.unwrap();
+
+    // Skip the first account, post with the second
+    automation::advance(state).await.unwrap();
+    automation::complete_current(state).await.unwrap();
+    let result = automation::advance(state).await.unwrap();
+    assert!(result.completed);
+
+    assert_eq!(state.get_progress().await, (1, 2));
+    let session = &state.storage().read_sessions().await.unwrap()[0];
+    let steps = state
+        .storage()
+        .read_session_steps(session.id)
+        .await
+        .unwrap();
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].profile_id, other.profile_id);
+}
+
+#[tokio::test]
+async fn previous_returns_to_the_last_step() {
+    let fixture = Fixture::new().await;
+    let first = fixture.add_profile("first").await;
+    let second = fixture.add_profile("second").await;
+    fixture.add_briefcase(&first, SocialMedia::INSTAGRAM).await;
+    fixture.add_briefcase(&second, SocialMedia::INSTAGRAM).await;
+
+    let tasks = tasks_json(&[("https://instagram.com/p/1", SocialMedia::INSTAGRAM, &["a"])]);
+    let state = &fixture.state;
+    automation::start(state, &tasks, source()).await.unwrap();
+    assert!(automation::previous(state).await.is_err());
+
+    let result = automation::advance(state).await.unwrap();
+    assert_eq!(result.profile_id, second.profile_id);
+
+    let result = automation::previous(state).await.unwrap();
+    assert_eq!(result.profile_id, first.profile_id);
+    assert!(result.should_change_profile);
+    assert_eq!(state.current_position().await, Some(0));
+}
+
+#[tokio::test]
+async fn goto_jumps_to_a_task_account_without_rotating_comments() {
+    let fixture = Fixture::new().await;
+    let first = fixture.add_profile("first").await;
+    let second = fixture.add_profile("second").await;
+    fixture.add_briefcase(&first, SocialMedia::X).await;
+    let target = fixture.add_briefcase(&second, SocialMedia::X).await;
+
+    let tasks = tasks_json(&[
+        ("https://x.com/post/1", SocialMedia::X, &["a", "b"]),
+        ("https://x.com/post/2", SocialMedia::X, &["c"]),
+    ]);
+    let state = &fixture.state;
+    assert!(automation::goto_step(state, 0).await.is_err());
+    automation::start(state, &tasks, source()).await.unwrap();
+
+    let result = automation::goto_task(state, 0, Some(target.id))
+        .await
+        .unwrap();
+    assert_eq!(result.profile_id, second.profile_id);
+    assert!(result.should_change_profile);
+    assert_eq!(result.comment, "a");
+    assert_eq!(state.current_position().await, Some(2));
+
+    // Completing the jumped-to step rotates the comment for the next account
+    automation::complete_current(state).await.unwrap();
+    let result = automation::goto_task(state, 0, None).await.unwrap();
+    assert_eq!(result.profile_id, first.profile_id);
+    assert_eq!(result.comment, "b");
+    assert_eq!(state.current_position().await, Some(0));
+
+    // Jumping back to the completed step shows the comment posted on it, and
+    // redoing it leaves the rotation alone
+    let result = automation::goto_step(state, 2).await.unwrap();
+    assert_eq!(result.comment, "a");
+    automation::complete_current(state).await.unwrap();
+    assert_eq!(state.get_task(0).await.unwrap().comment_index, 1);
+    let progress = state.get_task_progress().await;
+    assert_eq!(progress[0], (0, 1, 2));
+
+    assert!(automation::goto_step(state, 4).await.is_err());
+    assert!(automation::goto_task(state, 5, None).await.is_err());
+    assert_eq!(state.current_position().await, Some(2));
+
+    automation::pause(state).await.unwrap();
+    assert!(automation::goto_step(state, 0).await.is_err());
+    assert!(automation::goto_task(state, 0, None).await.is_err());
+}
+
+#[tokio::test]
+async fn tasks_can_be_changed_while_the_session_runs() {
+    let fixture = Fixture::new().await;
+    let first = fixture.add_profile("first").await;
+    let second = fixture.add_profile("second").await;
+    fixture.add_briefcase(&first, SocialMedia::X).await;
+    fixture.add_briefcase(&second, SocialMedia::X).await;
+
+    let tasks = tasks_json(&[("https://x.com/post/1", SocialMedia::X, &["a"])]);
+    let state = &fixture.state;
+    automation::start(state, &tasks, source()).await.unwrap();
+    automation::complete_current(state).await.unwrap();
+
+    let added = |link: &str, social_media| {
+        Task::new(
+            link.to_string(),
+            vec!["c".to_string()],
+            social_media,
+            &[],
+            None,
+        )
+    };
+    assert!(automation::add_task(
+        state,
+        added("https://facebook.com/1", SocialMedia::FACEBOOK),
+        source()
+    )
+    .await
+    .is_err());
+    let task_index = automation::add_task(
+        state,
+        added("https://x.com/post/2", SocialMedia::X),
+        source(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(task_index, 1);
+    assert_eq!(state.get_progress().await, (1, 4));
+    assert_eq!(state.current_position().await, Some(0));
+
+    automation::edit_task_comments(state, 1, vec!["d".to_string()], source())
+        .await
+        .unwrap();
+
+    // Removing the current task moves on within the same profile
+    let result = automation::remove_task(state, 0, source())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(result.task_index, 0);
+    assert_eq!(result.profile_id, first.profile_id);
+    assert!(!result.should_change_profile);
+    assert_eq!(result.comment, "d");
+    // The completed step went with its task, although its account stays in the plan
+    assert_eq!(state.get_progress().await, (0, 2));
+    assert!(automation::remove_task(state, 3, source()).await.is_err());
+
+    automation::complete_current(state).await.unwrap();
+    automation::close(state).await.unwrap();
+
+    // The history keeps the removed task, so both recorded steps stay readable
+    let session = &state.storage().read_sessions().await.unwrap()[0];
+    assert_eq!(session.tasks.len(), 2);
+    assert_eq!(session.tasks[1].comments, vec!["d"]);
+    let steps = state
+        .storage()
+        .read_session_steps(session.id)
+        .await
+        .unwrap();
+    let recorded: Vec<usize> = steps.iter().map(|s| s.task_index).collect();
+    assert_eq!(recorded, vec![0, 1]);
+}
+
+#[tokio::test]
+async fn disabled_and_paused_accounts_are_left_out() {
+    let fixture = Fixture::new().await;
+    let profile = fixture.add_profile("main").await;
+    let active = fixture.add_briefcase(&profile, SocialMedia::FACEBOOK).await;
+    let mut briefcases = fixture.state.get_brief_cases().await;
+    let mut disabled = briefcases[0].clone();
+    disabled.id = uuid::Uuid::new_v4();
+    disabled.is_active = false;
+    let mut paused = briefcases[0].clone();
+    paused.id = uuid::Uuid::new_v4();
+    paused.paused_until = Some(Utc::now() + Duration::hours(1));
+    briefcases.extend([disabled, paused]);
+    fixture.state.set_brief_cases(briefcases).await;
+
+    let tasks = tasks_json(&[("https://facebook.com/1", SocialMedia::FACEBOOK, &["a"])]);
+    let preview = automation::preview(&fixture.state, &tasks).await.unwrap();
+    assert_eq!(preview.total_steps, 1);
+    assert_eq!(preview.excluded.len(), 2);
+
+    automation::start(&fixture.state, &tasks, source())
+        .await
+        .unwrap();
+    let task = fixture.state.get_task(0).await.unwrap();
+    assert_eq!(task.related_brief_cases, vec![active]);
+}
+
+#[tokio::test]
+async fn start_fails_without_matching_accounts() {
+    let fixture = Fixture::new().await;
+    let tasks = tasks_json(&[("https://x.com/post/1", SocialMedia::X, &["a"])]);
+    assert!(automation::start(&fixture.state, &tasks, source())
+        .await
+        .is_err());
+
+    let profile = fixture.add_profile("main").await;
+    fixture.add_briefcase(&profile, SocialMedia::YOUTUBE).await;
+    assert!(automation::start(&fixture.state, &tasks, source())
+        .await
+        .is_err());
+    assert!(!fixture.state.is_running().await);
+}
+
+#[tokio::test]
+async fn closing_ends_the_running_session() {
+    let fixture = Fixture::new().await;
+    let profile = fixture.add_profile("main").await;
+    fixture.add_briefcase(&profile, SocialMedia::X).await;
+
+    let tasks = tasks_json(&[("https://x.com/post/1", SocialMedia::X, &["a"])]);
+    automation::start(&fixture.state, &tasks, source())
+        .await
+        .unwrap();
+    automation::close(&fixture.state).await.unwrap();
+
+    assert!(!fixture.state.is_running().await);
+    let sessions = fixture.state.storage().read_sessions().await.unwrap();
+    assert!(sessions[0].ended_at.is_some());
+    assert!(fixture
+        .app_data_dir
+        .path()
+        .join("profiles")
+        .join(profile.profile_id.to_string())
+        .is_dir());
+}
+
+#[tokio::test]
+async fn starting_again_ends_the_running_session() {
+    let fixture = Fixture::new().await;
+    let profile = fixture.add_profile("main").await;
+    fixture.add_briefcase(&profile, SocialMedia::X).await;
+
+    let tasks = tasks_json(&[("https://x.com/post/1", SocialMedia::X, &["a"])]);
+    let state = &fixture.state;
+    automation::start(state, &tasks, source()).await.unwrap();
+    automation::pause(state).await.unwrap();
+    automation::start(state, &tasks, source()).await.unwrap();
+
+    let current = state.get_session().await.unwrap();
+    let sessions = state.storage().read_sessions().await.unwrap();
+    assert_eq!(sessions.len(), 2);
+    let first = sessions.iter().find(|s| s.id != current.id).unwrap();
+    assert!(first.ended_at.is_some());
+    assert!(!first.is_paused());
+    assert!(!state.is_paused().await);
+}
+
+#[tokio::test]
+async fn pausing_freezes_navigation_and_tracks_paused_time() {
+    let fixture = Fixture::new().await;
+    let first = fixture.add_profile("first").await;
+    let second = fixture.add_profile("second").await;
+    fixture.add_briefcase(&first, SocialMedia::X).await;
+    fixture.add_briefcase(&second, SocialMedia::X).await;
+
+    let tasks = tasks_json(&[("https://x.com/post/1", SocialMedia::X, &["a"])]);
+    let state = &fixture.state;
+    assert!(automation::pause(state).await.is_err());
+    automation::start(state, &tasks, source()).await.unwrap();
+
+    let time = automation::pause(state).await.unwrap();
+    assert!(time.paused);
+    assert!(automation::pause(state).await.is_err());
+    assert!(automation::complete_current(state).await.is_err());
+    assert!(automation::advance(state).await.is_err());
+    assert!(automation::previous(state).await.is_err());
+    assert_eq!(state.current_position().await, Some(0));
+
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    let time = automation::resume(state).await.unwrap();
+    assert!(!time.paused);
+    assert!(time.paused_ms >= 20);
+    assert!(automation::resume(state).await.is_err());
+
+    let result = automation::advance(state).await.unwrap();
+    assert_eq!(result.profile_id, second.profile_id);
+
+    // Closing while paused counts the open pause as paused time
+    automation::pause(state).await.unwrap();
+    automation::close(state).await.unwrap();
+    let session = &state.storage().read_sessions().await.unwrap()[0];
+    assert!(!session.is_paused());
+    assert!(session.paused_ms >= 20);
+    let ended_at = session.ended_at.unwrap();
+    assert_eq!(
+        session.active_duration(ended_at) + session.paused_duration(ended_at),
+        ended_at - session.started_at
+    );
+}
+
+#[tokio::test]
+async fn windows_closed_by_a_profile_switch_are_not_lost() {
+    let fixture = Fixture::new().await;
+    let profile = fixture.add_profile("main").await;
+    fixture.add_briefcase(&profile, SocialMedia::X).await;
+
+    let tasks = tasks_json(&[("https://x.com/post/1", SocialMedia::X, &["a"])]);
+    let state = &fixture.state;
+    automation::start(state, &tasks, source()).await.unwrap();
+    state
+        .set_current_window_label("profile-a".to_string())
+        .await;
+
+    // Switching to another profile closes the old window before its destroy event arrives
+    state.mark_window_closing("profile-a").await;
+    state
+        .set_current_window_label("profile-b".to_string())
+        .await;
+    assert!(!state.mark_window_lost("profile-a").await);
+
+    // Reopening the same profile reuses the label of the window being closed
+    state.mark_window_closing("profile-b").await;
+    state
+        .set_current_window_label("profile-b".to_string())
+        .await;
+    assert!(!state.mark_window_lost("profile-b").await);
+    assert_eq!(
+        state.get_current_window_label().await.as_deref(),
+        Some("profile-b")
+    );
+    assert!(state.get_lost_window_label().await.is_none());
+
+    // The user closing the new window is still reported
+    assert!(state.mark_window_lost("profile-b").await);
+    assert_eq!(
+        state.get_lost_window_label().await.as_deref(),
+        Some("profile-b")
+    );
+}
@@ -0,0 +1,203 @@
+//! Checks what backup archives contain and how they are read back
+
+mod common;
+
+use attache_lib::audit::{AuditEntity, AuditSource};
+use attache_lib::backup::{self, ImportMode};
+use attache_lib::data;
+use attache_lib::models::{Profile, SocialMedia};
+use attache_lib::storage::PROFILES_FILE;
+use common::Fixture;
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+/// The audit source the tests save with
+fn source() -> AuditSource {
+    AuditSource::command("test")
+}
+
+/// Exports the fixture's data into an unprotected archive
+async fn export(fixture: &Fixture) -> PathBuf {
+    let path = fixture.app_data_dir.path().join("backup.zip");
+    backup::export(
+        &fixture.state,
+        path.clone(),
+        false,
+        None,
+        "1.0.0".to_string(),
+    )
+    .await
+    .unwrap();
+    path
+}
+
+/// Prepares and applies an import
+async fn import(fixture: &Fixture, path: PathBuf, mode: ImportMode) {
+    let import = backup::prepare_import(&fixture.state, path, mode, None)
+        .await
+        .unwrap();
+    backup::apply_import(&fixture.state, import).await.unwrap();
+}
+
+/// Parses the profiles file of a backup
+fn profiles(contents: &backup::BackupContents) -> Vec<Profile> {
+    serde_json::from_slice(&contents.config_files[PROFILES_FILE]).unwrap()
+}
+
+#[tokio::test]
+async fn exported_data_only_depends_on_the_export_passphrase() {
+    let fixture = Fixture::new().await;
+    let profile = fixture.add_profile("main").await;
+    let path = fixture.app_data_dir.path().join("backup.zip");
+
+    let manifest = backup::export(
+        &fixture.state,
+        path.clone(),
+        false,
+        Some("export passphrase".to_string()),
+        "1.0.0".to_string(),
+    )
+    .await
+    .unwrap();
+    assert!(manifest.encryption.is_some());
+
+    assert!(backup::read_archive(&path, None).is_err());
+    assert!(backup::read_archive(&path, Some("wrong passphrase")).is_err());
+    let contents = backup::read_archive(&path, Some("export passphrase")).unwrap();
+    assert_eq!(profiles(&contents)[0].profile_id, profile.profile_id);
+
+    // Without a passphrase the data files are plain JSON
+    backup::export(
+        &fixture.state,
+        path.clone(),
+        false,
+        None,
+        "1.0.0".to_string(),
+    )
+    .await
+    .unwrap();
+    let contents = backup::read_archive(&path, None).unwrap();
+    assert!(contents.manifest.encryption.is_none());
+    assert_eq!(profiles(&contents)[0].profile_id, profile.profile_id);
+}
+
+#[test]
+fn unknown_data_files_are_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("backup.zip");
+    let files = BTreeMap::from([("../escape.json".to_string(), b"[]".to_vec())]);
+    backup::write_archive(&path, &files, dir.path(), false, None, "1.0.0".to_string()).unwrap();
+
+    let error = backup::read_archive(&path, None).err().unwrap();
+    assert!(error.contains("unexpected file"), "{}", error);
+}
+
+#[tokio::test]
+async fn merging_only_adds_records_whose_ids_are_missing() {
+    let fixture = Fixture::new().await;
+    let kept = fixture.add_profile("kept").await;
+    let removed = fixture.add_profile("removed").await;
+    fixture.add_briefcase(&removed, SocialMedia::X).await;
+    let state = &fixture.state;
+    let mut settings = state.get_settings().await;
+    settings.auto_copy_comment = !settings.auto_copy_comment;
+    state.set_settings(settings.clone()).await;
+    let path = export(&fixture).await;
+
+    // Local changes made after the export
+    let mut renamed = kept.clone();
+    renamed.profile_name = "renamed".to_string();
+    data::save_profiles(state, vec![renamed], source())
+        .await
+        .unwrap();
+    data::save_briefcases(state, Vec::new(), source())
+        .await
+        .unwrap();
+    let added = fixture.add_profile("added").await;
+    let mut local_settings = settings.clone();
+    local_settings.auto_copy_comment = !settings.auto_copy_comment;
+    state.set_settings(local_settings.clone()).await;
+
+    import(&fixture, path, ImportMode::Merge).await;
+
+    let profiles = state.get_profiles().await;
+    let name_of = |id| {
+        profiles
+            .iter()
+            .find(|p| p.profile_id == id)
+            .map(|p| p.profile_name.as_str())
+    };
+    assert_eq!(profiles.len(), 3);
+    assert_eq!(name_of(kept.profile_id), Some("renamed"));
+    assert_eq!(name_of(removed.profile_id), Some("removed"));
+    assert_eq!(name_of(added.profile_id), Some("added"));
+    assert_eq!(state.get_brief_cases().await.len(), 1);
+    assert_eq!(state.get_settings().await, local_settings);
+}
+
+#[tokio::test]
+async fn replacing_swaps_all_data_for_the_backup() {
+    let fixture = Fixture::new().await;
+    let kept = fixture.add_profile("kept").await;
+    let state = &fixture.state;
+    let path = export(&fixture).await;
+
+    let mut renamed = kept.clone();
+    renamed.profile_name = "renamed".to_string();
+    data::save_profiles(state, vec![renamed], source())
+        .await
+        .unwrap();
+    fixture.add_profile("added").await;
+    let mut settings = state.get_settings().await;
+    settings.auto_copy_comment = !settings.auto_copy_comment;
+    state.set_settings(settings).await;
+
+    import(&fixture, path, ImportMode::Replace).await;
+
+    let profiles = state.get_profiles().await;
+    assert_eq!(profiles.len(), 1);
+    assert_eq!(profiles[0].profile_name, "kept");
+    assert_eq!(
+        state.get_settings().await,
+        attache_lib::settings::Settings::default()
+    );
+}
+
+#[tokio::test]
+async fn importing_appends_only_audit_entries_the_log_lacks() {
+    let fixture = Fixture::new().await;
+    let profile = fixture.add_profile("main").await;
+    let state = &fixture.state;
+    let mut renamed = profile.clone();
+    renamed.profile_name = "renamed".to_string();
+    data::save_profiles(state, vec![renamed], source())
+        .await
+        .unwrap();
+    let path = export(&fixture).await;
+
+    import(&fixture, path.clone(), ImportMode::Merge).await;
+    import(&fixture, path, ImportMode::Merge).await;
+
+    let log = state.storage().read_audit().await.unwrap();
+    let ids: HashSet<_> = log.iter().map(|entry| entry.id).collect();
+    assert_eq!(ids.len(), log.len());
+    let imports = log
+        .iter()
+        .filter(|entry| entry.entity == AuditEntity::Backup)
+        .count();
+    assert_eq!(imports, 2);
+    assert_eq!(log.len(), 3);
+}
+
+#[tokio::test]
+async fn imports_are_refused_while_automation_runs() {
+    let fixture = Fixture::new().await;
+    let path = export(&fixture).await;
+    fixture.state.set_running(true).await;
+
+    let error = backup::prepare_import(&fixture.state, path, ImportMode::Merge, None)
+        .await
+        .err()
+        .unwrap();
+    assert!(error.contains("Stop the automation"), "{}", error);
+}
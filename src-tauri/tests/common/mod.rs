@@ -0,0 +1,74 @@
+//! Fixtures shared by the integration tests
+
+// Each test binary only uses some of the fixtures
+#![allow(dead_code)]
+
+use attache_lib::models::{BriefCase, Profile, SocialMedia, Task};
+use attache_lib::state::AppState;
+use attache_lib::storage::{MemoryStorage, StorageBackend};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// An application state backed by memory, with its profile directories in a temporary directory
+pub struct Fixture {
+    /// Keeps the temporary app data directory alive for the duration of the test
+    pub app_data_dir: TempDir,
+    /// The loaded application state
+    pub state: AppState,
+}
+
+impl Fixture {
+    /// Loads an empty state backed by `MemoryStorage`
+    pub async fn new() -> Self {
+        Self::with_storage(Arc::new(MemoryStorage::default())).await
+    }
+
+    /// Loads an empty state backed by the given storage
+    pub async fn with_storage(storage: Arc<dyn StorageBackend>) -> Self {
+        let app_data_dir = tempfile::tempdir().expect("create temporary directory");
+        let state = AppState::load(storage, app_data_dir.path().to_path_buf())
+            .await
+            .expect("load state");
+
+        Self {
+            app_data_dir,
+            state,
+        }
+    }
+
+    /// Creates a profile on disk and adds it after the existing ones
+    pub async fn add_profile(&self, name: &str) -> Profile {
+        let mut profile = Profile::new(name.to_string(), self.state.app_data_dir())
+            .await
+            .expect("create profile");
+        profile.sort_order = self.state.get_profiles().await.len() as u32;
+        self.state.add_profile(profile.clone()).await;
+        profile
+    }
+
+    /// Adds an account to a profile
+    pub async fn add_briefcase(&self, profile: &Profile, social_media: SocialMedia) -> BriefCase {
+        let user_name = format!("{}-{}", profile.profile_name, social_media);
+        let briefcase = BriefCase::new(social_media, profile.profile_id, user_name);
+        self.state.add_brief_case(briefcase.clone()).await;
+        briefcase
+    }
+}
+
+/// Serializes tasks the way the frontend sends them to `start_automation`
+pub fn tasks_json(tasks: &[(&str, SocialMedia, &[&str])]) -> String {
+    let tasks: Vec<Task> = tasks
+        .iter()
+        .map(|(link, social_media, comments)| {
+            Task::new(
+                link.to_string(),
+                comments.iter().map(|c| c.to_string()).collect(),
+                social_media.clone(),
+                &[],
+                None,
+            )
+        })
+        .collect();
+
+    serde_json::to_string(&tasks).expect("serialize tasks")
+}
@@ -0,0 +1,165 @@
+//! Exercises the data service shared by the app commands and the command line tool
+
+mod common;
+
+use attache_lib::audit::{AuditEntity, AuditEntry, AuditSource};
+use attache_lib::data;
+use attache_lib::models::{BriefCaseGroup, SocialMedia};
+use attache_lib::storage::DataSnapshot;
+use common::Fixture;
+
+/// The audit source the tests save with
+fn source() -> AuditSource {
+    AuditSource::command("test")
+}
+
+#[tokio::test]
+async fn deleting_a_briefcase_removes_it_from_its_groups() {
+    let fixture = Fixture::new().await;
+    let profile = fixture.add_profile("main").await;
+    let kept = fixture.add_briefcase(&profile, SocialMedia::X).await;
+    let removed = fixture.add_briefcase(&profile, SocialMedia::YOUTUBE).await;
+    let state = &fixture.state;
+
+    let group = BriefCaseGroup::new("group".to_string(), vec![kept.id, removed.id]);
+    data::save_groups(state, vec![group], source())
+        .await
+        .unwrap();
+
+    let pruned = data::save_briefcases(state, vec![kept.clone()], source())
+        .await
+        .unwrap();
+    assert!(pruned);
+    assert_eq!(state.get_groups().await[0].briefcase_ids, vec![kept.id]);
+
+    let stored = state.storage().read_groups().await.unwrap();
+    assert_eq!(stored[0].briefcase_ids, vec![kept.id]);
+    let audit = state.storage().read_audit().await.unwrap();
+    assert!(audit
+        .iter()
+        .any(|entry| entry.entity_id == Some(removed.id)));
+}
+
+#[tokio::test]
+async fn saving_profiles_renumbers_them_and_rejects_invalid_ones() {
+    let fixture = Fixture::new().await;
+    let mut first = fixture.add_profile("first").await;
+    let mut second = fixture.add_profile("second").await;
+    let state = &fixture.state;
+
+    // Gaps in the order are closed, the relative order is kept
+    first.sort_order = 8;
+    second.sort_order = 3;
+    data::save_profiles(state, vec![first.clone(), second.clone()], source())
+        .await
+        .unwrap();
+    let stored = state.storage().read_profiles().await.unwrap();
+    assert_eq!(stored[0].profile_id, second.profile_id);
+    assert_eq!(stored[0].sort_order, 0);
+    assert_eq!(stored[1].sort_order, 1);
+
+    let mut unnamed = first.clone();
+    unnamed.profile_name = String::new();
+    assert!(data::save_profiles(state, vec![unnamed], source())
+        .await
+        .is_err());
+    assert_eq!(state.get_profiles().await.len(), 2);
+}
+
+#[tokio::test]
+async fn saving_profiles_validates_only_new_and_changed_ones() {
+    let fixture = Fixture::new().await;
+    let mut legacy = fixture.add_profile("legacy").await;
+    legacy.color = Some("red".to_string());
+    fixture.state.set_profiles(vec![legacy.clone()]).await;
+    let added = fixture.add_profile("added").await;
+    let state = &fixture.state;
+
+    // A stored profile that predates a rule does not block the others
+    legacy.sort_order = 1;
+    let mut moved = added.clone();
+    moved.sort_order = 0;
+    data::save_profiles(state, vec![legacy.clone(), moved], source())
+        .await
+        .unwrap();
+    assert_eq!(state.get_profiles().await[0].profile_id, added.profile_id);
+
+    legacy.notes = Some("edited".to_string());
+    assert!(data::save_profiles(state, vec![legacy], source())
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn validate_reports_inconsistent_data() {
+    let fixture = Fixture::new().await;
+    let profile = fixture.add_profile("main").await;
+    let briefcase = fixture.add_briefcase(&profile, SocialMedia::X).await;
+    let state = &fixture.state;
+    data::save_profiles(state, vec![profile.clone()], source())
+        .await
+        .unwrap();
+    data::save_briefcases(state, vec![briefcase.clone()], source())
+        .await
+        .unwrap();
+    assert!(data::validate(state).await.is_empty());
+
+    // An account whose profile is gone and a directory nobody owns
+    data::save_profiles(state, Vec::new(), source())
+        .await
+        .unwrap();
+    let problems = data::validate(state).await;
+    assert_eq!(problems.len(), 2, "{:?}", problems);
+    assert!(problems[0].contains("unknown profile"));
+    assert!(problems[1].contains("belongs to no profile"));
+}
+
+#[tokio::test]
+async fn restoring_validates_first_and_appends_to_the_audit_log() {
+    let fixture = Fixture::new().await;
+    let kept = fixture.add_profile("kept").await;
+    let state = &fixture.state;
+    data::save_profiles(state, vec![kept.clone()], source())
+        .await
+        .unwrap();
+    let recorded = state.storage().read_audit().await.unwrap().len();
+
+    // A group without a name rejects the whole set before anything is written
+    let restored = fixture.add_profile("restored").await;
+    let briefcase = fixture.add_briefcase(&restored, SocialMedia::X).await;
+    let invalid = DataSnapshot {
+        profiles: vec![restored.clone()],
+        briefcases: vec![briefcase.clone()],
+        groups: vec![BriefCaseGroup::new(" ".to_string(), Vec::new())],
+        ..DataSnapshot::default()
+    };
+    assert!(data::restore(state, invalid, Vec::new(), source())
+        .await
+        .is_err());
+    let stored = state.storage().read_profiles().await.unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].profile_id, kept.profile_id);
+    assert!(state.storage().read_briefcases().await.unwrap().is_empty());
+
+    let carried = AuditEntry::new(source(), AuditEntity::Backup, None, None, None);
+    let valid = DataSnapshot {
+        profiles: vec![restored.clone()],
+        briefcases: vec![briefcase.clone()],
+        ..DataSnapshot::default()
+    };
+    data::restore(state, valid, vec![carried.clone()], source())
+        .await
+        .unwrap();
+    assert_eq!(
+        state.get_profiles().await[0].profile_id,
+        restored.profile_id
+    );
+    assert_eq!(state.storage().read_briefcases().await.unwrap().len(), 1);
+
+    // Earlier entries stay, the carried entry comes before the restore's changes
+    let audit = state.storage().read_audit().await.unwrap();
+    assert_eq!(audit[recorded].id, carried.id);
+    assert!(audit[recorded + 1..]
+        .iter()
+        .any(|entry| entry.entity_id == Some(kept.profile_id)));
+}
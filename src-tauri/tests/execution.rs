@@ -0,0 +1,578 @@
+//! Unit and property tests for `ExecutionPlan`
+
+use attache_lib::execution::{ExecutionPlan, ExecutionStep};
+use attache_lib::models::{BriefCase, LoginStatus, LoginVerification, Profile, SocialMedia, Task};
+use chrono::{Duration, Utc};
+use proptest::prelude::*;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// All platforms, indexed by the generators
+const PLATFORMS: [SocialMedia; 4] = [
+    SocialMedia::YOUTUBE,
+    SocialMedia::X,
+    SocialMedia::INSTAGRAM,
+    SocialMedia::FACEBOOK,
+];
+
+/// Creates a profile at a position of the user-defined order
+fn profile(sort_order: u32) -> Profile {
+    Profile {
+        profile_id: Uuid::new_v4(),
+        profile_name: format!("profile {}", sort_order),
+        notes: None,
+        color: None,
+        tags: Vec::new(),
+        sort_order,
+    }
+}
+
+/// Creates an available account on a profile
+fn briefcase(profile: &Profile, social_media: SocialMedia) -> BriefCase {
+    BriefCase::new(social_media, profile.profile_id, "user".to_string())
+}
+
+/// Creates a task on a platform with the given accounts assigned
+fn task(social_media: SocialMedia, briefcases: &[BriefCase]) -> Task {
+    Task::new(
+        format!("https://example.com/{}", Uuid::new_v4()),
+        vec!["comment".to_string()],
+        social_media,
+        briefcases,
+        None,
+    )
+}
+
+/// Walks the plan to its end, returning every step in order
+fn drain(plan: &mut ExecutionPlan) -> Vec<ExecutionStep> {
+    std::iter::from_fn(|| plan.advance()).collect()
+}
+
+/// A profile with two X accounts and a profile with one, planned for a single X task
+fn two_profile_plan() -> (Vec<Profile>, Vec<Task>, ExecutionPlan) {
+    let profiles = vec![profile(0), profile(1)];
+    let briefcases = vec![
+        briefcase(&profiles[0], SocialMedia::X),
+        briefcase(&profiles[0], SocialMedia::X),
+        briefcase(&profiles[1], SocialMedia::X),
+    ];
+    let tasks = vec![task(SocialMedia::X, &briefcases)];
+    let plan = ExecutionPlan::new(&tasks, &profiles);
+    (profiles, tasks, plan)
+}
+
+#[test]
+fn empty_plan_has_no_current_step() {
+    let mut plan = ExecutionPlan::new(&[], &[]);
+
+    assert_eq!(plan.total_steps(), 0);
+    assert!(plan.is_complete());
+    assert!(plan.current_step().is_none());
+    assert!(plan.current_profile_id().is_none());
+    assert!(plan.current_position().is_none());
+    assert!(!plan.should_change_profile());
+    assert!(plan.advance().is_none());
+    assert!(plan.previous().is_none());
+    assert_eq!(plan.visited_count(), 0);
+    assert!(plan.task_progress(&[]).is_empty());
+}
+
+#[test]
+fn fresh_plan_points_at_the_first_step_before_advance() {
+    let (profiles, _, plan) = two_profile_plan();
+
+    // Before `advance` the current step already reports the first step
+    assert_eq!(plan.visited_count(), 0);
+    assert_eq!(plan.current_step().unwrap().briefcase_index, 0);
+    assert_eq!(plan.current_profile_id(), Some(profiles[0].profile_id));
+    assert_eq!(plan.current_position(), Some(0));
+    assert!(plan.should_change_profile());
+    assert!(!plan.is_complete());
+}
+
+#[test]
+fn next_returns_the_step_it_makes_current() {
+    let (profiles, _, mut plan) = two_profile_plan();
+
+    let first = plan.advance().unwrap();
+    assert_eq!(first.briefcase_index, 0);
+    assert_eq!(plan.current_step().unwrap().briefcase_index, 0);
+    assert_eq!(plan.current_position(), Some(0));
+
+    // The second step stays on the first profile
+    assert!(!plan.should_change_profile());
+    let second = plan.advance().unwrap();
+    assert_eq!(second.briefcase_index, 1);
+    assert_eq!(plan.current_position(), Some(1));
+
+    // The third step moves to the second profile
+    assert!(plan.should_change_profile());
+    let third = plan.advance().unwrap();
+    assert_eq!(third.profile_id, profiles[1].profile_id);
+    assert_eq!(plan.current_profile_id(), Some(profiles[1].profile_id));
+}
+
+#[test]
+fn finished_plan_keeps_the_last_step_current() {
+    let (profiles, _, mut plan) = two_profile_plan();
+    drain(&mut plan);
+
+    assert!(plan.is_complete());
+    assert!(plan.advance().is_none());
+    assert!(!plan.should_change_profile());
+    assert_eq!(plan.current_step().unwrap().briefcase_index, 2);
+    assert_eq!(plan.current_profile_id(), Some(profiles[1].profile_id));
+    assert_eq!(plan.current_position(), Some(2));
+    // Passing steps does not complete them
+    assert_eq!(plan.visited_count(), 0);
+}
+
+#[test]
+fn previous_stops_at_the_first_step() {
+    let (_, _, mut plan) = two_profile_plan();
+
+    assert!(plan.previous().is_none());
+    plan.advance();
+    assert!(plan.previous().is_none());
+    assert_eq!(plan.current_position(), Some(0));
+
+    plan.advance();
+    plan.advance();
+    let step = plan.previous().unwrap();
+    assert_eq!(step.briefcase_index, 1);
+    assert_eq!(plan.current_position(), Some(1));
+
+    // Moving forward again lands on the step after the current one
+    assert_eq!(plan.advance().unwrap().briefcase_index, 2);
+}
+
+#[test]
+fn goto_makes_any_step_current() {
+    let (profiles, _, mut plan) = two_profile_plan();
+    plan.advance();
+    plan.complete_current(None);
+
+    let step = plan.goto(2).unwrap();
+    assert_eq!(step.profile_id, profiles[1].profile_id);
+    assert_eq!(plan.current_position(), Some(2));
+    assert!(plan.is_complete());
+
+    // Jumping back keeps what was completed and continues from the new position
+    assert_eq!(plan.goto(0).unwrap().briefcase_index, 0);
+    assert_eq!(plan.visited_count(), 1);
+    assert_eq!(plan.advance().unwrap().briefcase_index, 1);
+
+    assert!(plan.goto(3).is_none());
+    assert_eq!(plan.current_position(), Some(1));
+}
+
+#[test]
+fn task_position_finds_accounts_and_the_next_open_step() {
+    let (_, tasks, mut plan) = two_profile_plan();
+    let ids: Vec<Uuid> = tasks[0].related_brief_cases.iter().map(|b| b.id).collect();
+
+    assert_eq!(plan.task_position(&tasks, 0, Some(ids[2])), Some(2));
+    assert_eq!(plan.task_position(&tasks, 0, None), Some(0));
+    assert_eq!(plan.task_position(&tasks, 0, Some(Uuid::new_v4())), None);
+    assert_eq!(plan.task_position(&tasks, 1, None), None);
+
+    plan.advance();
+    assert!(plan.complete_current(None));
+    assert_eq!(plan.task_position(&tasks, 0, None), Some(1));
+
+    // Once every step is completed, the task starts over at its first step
+    for position in 1..ids.len() {
+        plan.goto(position);
+        plan.complete_current(None);
+    }
+    assert_eq!(plan.task_position(&tasks, 0, None), Some(0));
+}
+
+#[test]
+fn completed_steps_keep_the_comment_posted_on_them() {
+    let (_, mut tasks, mut plan) = two_profile_plan();
+    tasks[0].comments = vec!["a".to_string(), "b".to_string()];
+
+    plan.advance();
+    assert_eq!(plan.current_comment_index(&tasks), Some(0));
+    assert!(plan.complete_current(Some(0)));
+    tasks[0].comment_index = 1;
+
+    // The open step follows the rotation, the completed one keeps its comment
+    plan.advance();
+    assert_eq!(plan.current_comment_index(&tasks), Some(1));
+    plan.goto(0);
+    assert_eq!(plan.current_comment_index(&tasks), Some(0));
+    assert!(!plan.complete_current(Some(0)));
+
+    // Picking another comment on a completed step changes only that step
+    assert!(plan.assign_comment(0, 1));
+    assert_eq!(plan.current_comment_index(&tasks), Some(1));
+    plan.goto(1);
+    assert!(!plan.assign_comment(0, 0));
+}
+
+#[test]
+fn added_tasks_join_profiles_that_are_not_done_yet() {
+    let (profiles, mut tasks, mut plan) = two_profile_plan();
+    let briefcases = tasks[0].related_brief_cases.clone();
+    plan.advance();
+
+    tasks.push(task(SocialMedia::X, &briefcases));
+    assert_eq!(plan.add_task(&tasks, 1, &profiles), 3);
+    assert_eq!(plan.total_steps(), 6);
+    assert_eq!(plan.current_position(), Some(0));
+
+    // Both profiles are still ahead, so each is opened only once
+    let order: Vec<(usize, Uuid)> = drain(&mut plan)
+        .iter()
+        .map(|s| (s.task_index, s.profile_id))
+        .collect();
+    let (first, second) = (profiles[0].profile_id, profiles[1].profile_id);
+    assert_eq!(
+        order,
+        vec![(0, first), (1, first), (1, first), (0, second), (1, second)]
+    );
+
+    // The first profile is done now, so its steps for another task run last
+    tasks.push(task(SocialMedia::X, &briefcases));
+    plan.add_task(&tasks, 2, &profiles);
+    let order: Vec<(usize, Uuid)> = drain(&mut plan)
+        .iter()
+        .map(|s| (s.task_index, s.profile_id))
+        .collect();
+    assert_eq!(order, vec![(2, second), (2, first), (2, first)]);
+}
+
+#[test]
+fn removed_tasks_keep_the_current_step_and_renumber_the_rest() {
+    let (profiles, mut tasks, mut plan) = two_profile_plan();
+    tasks.push(task(SocialMedia::X, &tasks[0].related_brief_cases.clone()));
+    plan.add_task(&tasks, 1, &profiles);
+    for _ in 0..3 {
+        plan.advance();
+    }
+    assert_eq!(plan.current_step().unwrap().task_index, 1);
+
+    tasks.remove(0);
+    assert!(!plan.remove_task(0));
+    assert_eq!(plan.total_steps(), 3);
+    assert_eq!(plan.current_position(), Some(0));
+    let current = plan.current_step().unwrap();
+    assert_eq!((current.task_index, current.briefcase_index), (0, 0));
+
+    // Removing the current task leaves the cursor before the following steps
+    tasks.remove(0);
+    assert!(plan.remove_task(0));
+    assert_eq!(plan.total_steps(), 0);
+    assert!(plan.advance().is_none());
+    assert_eq!(plan.visited_count(), 0);
+}
+
+#[test]
+fn completed_steps_are_counted_once() {
+    let (_, tasks, mut plan) = two_profile_plan();
+
+    plan.advance();
+    plan.complete_current(None);
+    plan.complete_current(None);
+    assert_eq!(plan.visited_count(), 1);
+    assert_eq!(plan.task_progress(&tasks), vec![(0, 1, 3)]);
+
+    plan.reset();
+    assert_eq!(plan.visited_count(), 0);
+    assert_eq!(plan.task_progress(&tasks), vec![(0, 0, 3)]);
+    assert!(!plan.current_step().unwrap().completed);
+}
+
+#[test]
+fn accounts_in_several_tasks_count_once_per_task() {
+    let (profiles, mut tasks, mut plan) = two_profile_plan();
+    let briefcases = tasks[0].related_brief_cases.clone();
+    tasks.push(task(SocialMedia::X, &briefcases));
+    plan.add_task(&tasks, 1, &profiles);
+
+    // The same account posts on both tasks
+    for task_index in 0..2 {
+        let position = plan
+            .task_position(&tasks, task_index, Some(briefcases[0].id))
+            .unwrap();
+        plan.goto(position);
+        plan.complete_current(None);
+    }
+
+    assert_eq!(plan.visited_count(), 2);
+    assert_eq!(plan.task_progress(&tasks), vec![(0, 1, 3), (1, 1, 3)]);
+}
+
+#[test]
+fn disabled_accounts_do_not_count_towards_task_progress() {
+    let profiles = vec![profile(0)];
+    let mut disabled = briefcase(&profiles[0], SocialMedia::X);
+    disabled.is_active = false;
+    let briefcases = vec![briefcase(&profiles[0], SocialMedia::X), disabled];
+    let tasks = vec![task(SocialMedia::X, &briefcases)];
+    let mut plan = ExecutionPlan::new(&tasks, &profiles);
+
+    plan.advance();
+    plan.complete_current(None);
+
+    assert_eq!(plan.visited_count(), plan.total_steps());
+    assert_eq!(plan.task_progress(&tasks), vec![(0, 1, 1)]);
+}
+
+#[test]
+fn profiles_run_in_their_user_defined_order() {
+    let unknown = profile(0);
+    let profiles = vec![profile(2), profile(0), profile(1)];
+    let briefcases: Vec<BriefCase> = profiles
+        .iter()
+        .chain([&unknown])
+        .map(|p| briefcase(p, SocialMedia::YOUTUBE))
+        .collect();
+    let tasks = vec![task(SocialMedia::YOUTUBE, &briefcases)];
+
+    let mut plan = ExecutionPlan::new(&tasks, &profiles);
+    let order: Vec<Uuid> = drain(&mut plan).iter().map(|s| s.profile_id).collect();
+
+    // Profiles missing from the list run last
+    assert_eq!(
+        order,
+        vec![
+            profiles[1].profile_id,
+            profiles[2].profile_id,
+            profiles[0].profile_id,
+            unknown.profile_id,
+        ]
+    );
+}
+
+#[test]
+fn unavailable_briefcases_get_no_steps() {
+    let profiles = vec![profile(0)];
+    let mut inactive = briefcase(&profiles[0], SocialMedia::INSTAGRAM);
+    inactive.is_active = false;
+    let mut paused = briefcase(&profiles[0], SocialMedia::INSTAGRAM);
+    paused.paused_until = Some(Utc::now() + Duration::hours(1));
+    let mut expired = briefcase(&profiles[0], SocialMedia::INSTAGRAM);
+    expired.paused_until = Some(Utc::now() - Duration::hours(1));
+
+    // Build the task by hand so the plan, not `Task::new`, has to filter
+    let mut task = task(SocialMedia::INSTAGRAM, &[]);
+    task.related_brief_cases = vec![inactive, paused, expired];
+
+    let mut plan = ExecutionPlan::new(&[task], &profiles);
+    let steps = drain(&mut plan);
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].briefcase_index, 2);
+}
+
+#[test]
+fn tasks_without_accounts_are_previewed() {
+    let (profiles, mut tasks, _) = two_profile_plan();
+    tasks.push(task(SocialMedia::FACEBOOK, &[]));
+    let all: Vec<BriefCase> = tasks[0].related_brief_cases.clone();
+
+    let plan = ExecutionPlan::new(&tasks, &profiles);
+    let preview = plan.preview(&tasks, &all, &profiles, &[]);
+
+    assert_eq!(preview.total_steps, 3);
+    assert_eq!(preview.tasks_without_accounts, vec![1]);
+    let counts: Vec<(Uuid, usize)> = preview
+        .profiles
+        .iter()
+        .map(|p| (p.profile_id, p.step_count))
+        .collect();
+    assert_eq!(
+        counts,
+        vec![(profiles[0].profile_id, 2), (profiles[1].profile_id, 1)]
+    );
+}
+
+#[test]
+fn accounts_without_a_verified_login_are_previewed() {
+    let (profiles, mut tasks, _) = two_profile_plan();
+    let now = Utc::now();
+    let verified = |status| {
+        Some(LoginVerification {
+            status,
+            verified_at: now,
+        })
+    };
+    tasks[0].related_brief_cases[0].last_verified = verified(LoginStatus::LoggedIn);
+    tasks[0].related_brief_cases[1].last_verified = verified(LoginStatus::Expired);
+    // The same account on a second task is listed once
+    tasks.push(tasks[0].clone());
+
+    let plan = ExecutionPlan::new(&tasks, &profiles);
+    let preview = plan.preview(&tasks, &[], &profiles, &[]);
+
+    let unverified: Vec<Uuid> = preview.unverified_logins.iter().map(|b| b.id).collect();
+    let briefcases = &tasks[0].related_brief_cases;
+    assert_eq!(unverified, vec![briefcases[1].id, briefcases[2].id]);
+}
+
+/// A generated world: profiles, the accounts on them and tasks over those accounts
+#[derive(Debug)]
+struct World {
+    profiles: Vec<Profile>,
+    tasks: Vec<Task>,
+}
+
+/// Generates up to 5 profiles, 12 accounts and 8 tasks, some accounts disabled
+fn world() -> impl Strategy<Value = World> {
+    (1usize..=5)
+        .prop_flat_map(|profile_count| {
+            (
+                Just(profile_count),
+                prop::collection::vec(0u32..10, profile_count),
+                prop::collection::vec((0..profile_count, 0usize..4, any::<bool>()), 0..=12),
+                prop::collection::vec(0usize..4, 0..=8),
+            )
+        })
+        .prop_map(|(_, sort_orders, accounts, task_platforms)| {
+            let profiles: Vec<Profile> = sort_orders.into_iter().map(profile).collect();
+            let briefcases: Vec<BriefCase> = accounts
+                .into_iter()
+                .map(|(profile_index, platform, active)| {
+                    let mut bc = briefcase(&profiles[profile_index], PLATFORMS[platform].clone());
+                    bc.is_active = active;
+                    bc
+                })
+                .collect();
+
+            // Assign every account on the platform, including disabled ones, so the plan filters
+            let tasks = task_platforms
+                .into_iter()
+                .map(|platform| {
+                    let mut t = task(PLATFORMS[platform].clone(), &[]);
+                    t.related_brief_cases = briefcases
+                        .iter()
+                        .filter(|bc| bc.social_media == t.social_media)
+                        .cloned()
+                        .collect();
+                    t
+                })
+                .collect();
+
+            World { profiles, tasks }
+        })
+}
+
+proptest! {
+    #[test]
+    fn every_available_pair_appears_exactly_once(world in world()) {
+        let mut plan = ExecutionPlan::new(&world.tasks, &world.profiles);
+        let steps = drain(&mut plan);
+
+        let planned: Vec<(usize, usize)> =
+            steps.iter().map(|s| (s.task_index, s.briefcase_index)).collect();
+        let unique: HashSet<(usize, usize)> = planned.iter().copied().collect();
+        prop_assert_eq!(unique.len(), planned.len());
+
+        let expected: HashSet<(usize, usize)> = world
+            .tasks
+            .iter()
+            .enumerate()
+            .flat_map(|(ti, t)| {
+                t.related_brief_cases
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, bc)| bc.is_active)
+                    .map(move |(bi, _)| (ti, bi))
+            })
+            .collect();
+        prop_assert_eq!(unique, expected);
+
+        for step in &steps {
+            let task = &world.tasks[step.task_index];
+            prop_assert_eq!(&step.link, &task.link);
+            prop_assert_eq!(
+                step.profile_id,
+                task.related_brief_cases[step.briefcase_index].profile_id
+            );
+        }
+    }
+
+    #[test]
+    fn each_profile_is_switched_to_once(world in world()) {
+        let mut plan = ExecutionPlan::new(&world.tasks, &world.profiles);
+
+        let mut switches = Vec::new();
+        while !plan.is_complete() {
+            let should_change = plan.should_change_profile();
+            let step = plan.advance().unwrap();
+            if should_change {
+                switches.push(step.profile_id);
+            }
+        }
+
+        // Grouping by profile means one switch per profile with steps
+        let distinct: HashSet<Uuid> = switches.iter().copied().collect();
+        prop_assert_eq!(distinct.len(), switches.len());
+        prop_assert_eq!(distinct.len(), plan.profile_executions.len());
+
+        // Switches follow the user-defined order
+        let positions: Vec<u32> = switches
+            .iter()
+            .map(|id| {
+                world
+                    .profiles
+                    .iter()
+                    .find(|p| p.profile_id == *id)
+                    .unwrap()
+                    .sort_order
+            })
+            .collect();
+        prop_assert!(positions.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn progress_never_goes_backwards(world in world()) {
+        let mut plan = ExecutionPlan::new(&world.tasks, &world.profiles);
+        let total = plan.total_steps();
+
+        let mut last_visited = plan.visited_count();
+        let mut last_task_progress = plan.task_progress(&world.tasks);
+
+        while plan.advance().is_some() {
+            plan.complete_current(None);
+
+            prop_assert!(plan.visited_count() >= last_visited);
+            prop_assert!(plan.visited_count() <= total);
+
+            let task_progress = plan.task_progress(&world.tasks);
+            for (before, after) in last_task_progress.iter().zip(&task_progress) {
+                prop_assert!(after.1 >= before.1);
+                prop_assert!(after.1 <= after.2);
+            }
+
+            last_visited = plan.visited_count();
+            last_task_progress = task_progress;
+        }
+
+        prop_assert!(plan.is_complete());
+        prop_assert_eq!(plan.visited_count(), total);
+        let task_progress = plan.task_progress(&world.tasks);
+        prop_assert!(task_progress.iter().all(|(_, visited, total)| visited == total));
+    }
+
+    #[test]
+    fn previous_then_next_returns_to_the_same_step(world in world(), moves in 0usize..20) {
+        let mut plan = ExecutionPlan::new(&world.tasks, &world.profiles);
+        for _ in 0..moves {
+            plan.advance();
+        }
+
+        let before = plan.current_position();
+        if plan.previous().is_some() {
+            let step = plan.advance().unwrap();
+            prop_assert_eq!(plan.current_position(), before);
+            let current = plan.current_step().unwrap();
+            prop_assert_eq!(
+                (step.task_index, step.briefcase_index),
+                (current.task_index, current.briefcase_index)
+            );
+        }
+    }
+}
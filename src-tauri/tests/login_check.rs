@@ -0,0 +1,130 @@
+//! Walks login checks through the session service without a window system
+
+mod common;
+
+use attache_lib::audit::AuditSource;
+use attache_lib::automation;
+use attache_lib::login_check::{self, LoginCheck};
+use attache_lib::models::{BriefCase, SocialMedia};
+use attache_lib::platforms::PlatformRegistry;
+use chrono::{Duration, Utc};
+use common::{tasks_json, Fixture};
+
+#[tokio::test]
+async fn each_profile_is_opened_once_with_its_platforms_in_registry_order() {
+    let fixture = Fixture::new().await;
+    let first = fixture.add_profile("first").await;
+    let second = fixture.add_profile("second").await;
+    let x = fixture.add_briefcase(&first, SocialMedia::X).await;
+    let youtube = fixture.add_briefcase(&first, SocialMedia::YOUTUBE).await;
+    let other_x = fixture.add_briefcase(&first, SocialMedia::X).await;
+    let instagram = fixture.add_briefcase(&second, SocialMedia::INSTAGRAM).await;
+
+    // Disabled and paused accounts and platforms without a home page are not checked
+    let mut disabled = BriefCase::new(SocialMedia::FACEBOOK, second.profile_id, "off".into());
+    disabled.is_active = false;
+    let mut paused = BriefCase::new(SocialMedia::X, second.profile_id, "later".into());
+    paused.paused_until = Some(Utc::now() + Duration::hours(1));
+    let unknown = BriefCase::new(SocialMedia::new("tiktok"), second.profile_id, "t".into());
+    fixture.state.add_brief_case(disabled).await;
+    fixture.state.add_brief_case(paused).await;
+    fixture.state.add_brief_case(unknown).await;
+
+    let check = LoginCheck::new(
+        &fixture.state.get_profiles().await,
+        &fixture.state.get_brief_cases().await,
+        &PlatformRegistry::builtin(),
+    );
+    let stops: Vec<_> = check
+        .stops()
+        .iter()
+        .map(|s| {
+            (
+                s.profile_id,
+                s.social_media.clone(),
+                s.briefcase_ids.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        stops,
+        vec![
+            (first.profile_id, SocialMedia::YOUTUBE, vec![youtube.id]),
+            (first.profile_id, SocialMedia::X, vec![x.id, other_x.id]),
+            (
+                second.profile_id,
+                SocialMedia::INSTAGRAM,
+                vec![instagram.id]
+            ),
+        ]
+    );
+    assert_eq!(check.stops()[0].home_url, "https://www.youtube.com/");
+}
+
+#[tokio::test]
+async fn login_check_walks_every_page_and_ends_after_the_last() {
+    let fixture = Fixture::new().await;
+    let first = fixture.add_profile("first").await;
+    let second = fixture.add_profile("second").await;
+    fixture.add_briefcase(&first, SocialMedia::X).await;
+    fixture.add_briefcase(&first, SocialMedia::YOUTUBE).await;
+    let second_x = fixture.add_briefcase(&second, SocialMedia::X).await;
+    let state = &fixture.state;
+
+    let progress = login_check::start(state).await.unwrap();
+    assert_eq!((progress.position, progress.total), (0, 3));
+    assert!(progress.should_change_profile);
+
+    // Only the accounts on the current page can be confirmed
+    let shown = progress.stop.briefcase_ids[0];
+    assert!(login_check::ensure_on_current_page(state, shown)
+        .await
+        .is_ok());
+    assert!(login_check::ensure_on_current_page(state, second_x.id)
+        .await
+        .is_err());
+
+    // A login check and automation never share the profile window
+    let tasks = tasks_json(&[("https://x.com/post/1", SocialMedia::X, &["a"])]);
+    assert!(
+        automation::start(state, &tasks, AuditSource::command("test"))
+            .await
+            .is_err()
+    );
+
+    let progress = login_check::next(state).await.unwrap().unwrap();
+    assert!(!progress.should_change_profile);
+    let progress = login_check::next(state).await.unwrap().unwrap();
+    assert_eq!(progress.stop.profile_id, second.profile_id);
+    assert!(progress.should_change_profile);
+    assert_eq!(
+        login_check::current(state).await.unwrap().position,
+        progress.position
+    );
+
+    assert!(login_check::next(state).await.unwrap().is_none());
+    assert!(login_check::current(state).await.is_none());
+    assert!(login_check::next(state).await.is_err());
+    assert!(login_check::ensure_on_current_page(state, second_x.id)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn login_check_needs_an_idle_app_and_active_accounts() {
+    let fixture = Fixture::new().await;
+    let state = &fixture.state;
+    assert!(login_check::start(state).await.is_err());
+
+    let profile = fixture.add_profile("only").await;
+    fixture.add_briefcase(&profile, SocialMedia::X).await;
+    state.set_locked(true).await;
+    assert!(login_check::start(state).await.is_err());
+    state.set_locked(false).await;
+
+    let tasks = tasks_json(&[("https://x.com/post/1", SocialMedia::X, &["a"])]);
+    automation::start(state, &tasks, AuditSource::command("test"))
+        .await
+        .unwrap();
+    assert!(login_check::start(state).await.is_err());
+}
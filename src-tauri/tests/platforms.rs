@@ -0,0 +1,164 @@
+//! Checks the built-in platforms, link handling and the user platform file
+
+use attache_lib::models::SocialMedia;
+use attache_lib::platforms::{LinkRules, Platform, PlatformRegistry};
+use attache_lib::storage::{JsonStorage, PLATFORMS_FILE};
+
+/// A user-defined platform
+fn custom(id: &str, name: &str, host: &str) -> Platform {
+    Platform {
+        id: SocialMedia::new(id),
+        name: name.to_string(),
+        hosts: vec![host.to_string()],
+        home_url: format!("https://{}/", host),
+        link_rules: LinkRules::default(),
+    }
+}
+
+#[test]
+fn links_are_detected_by_host_and_subdomain() {
+    let registry = PlatformRegistry::builtin();
+    let detect = |link: &str| registry.detect(link).map(|p| p.id.clone());
+
+    assert_eq!(
+        detect("https://m.youtube.com/watch?v=1"),
+        Some(SocialMedia::YOUTUBE)
+    );
+    assert_eq!(detect("https://youtu.be/abc"), Some(SocialMedia::YOUTUBE));
+    assert_eq!(
+        detect("https://twitter.com/user/status/1"),
+        Some(SocialMedia::X)
+    );
+    assert_eq!(
+        detect("https://www.instagram.com/p/1/"),
+        Some(SocialMedia::INSTAGRAM)
+    );
+
+    // Hosts only match whole labels, and text that is not a link matches nothing
+    assert_eq!(detect("https://notx.com/post"), None);
+    assert_eq!(detect("https://example.com/?u=x.com"), None);
+    assert_eq!(detect("x.com/post"), None);
+}
+
+#[test]
+fn links_are_canonicalized_by_platform_rules() {
+    let registry = PlatformRegistry::builtin();
+    let canonical = |id: SocialMedia, link: &str| registry.get(&id).unwrap().canonicalize(link);
+
+    assert_eq!(
+        canonical(
+            SocialMedia::YOUTUBE,
+            "https://m.youtube.com/watch?v=abc&si=track&lc=c1#t=5"
+        ),
+        "https://www.youtube.com/watch?v=abc&lc=c1"
+    );
+    assert_eq!(
+        canonical(
+            SocialMedia::X,
+            " https://mobile.twitter.com/user/status/1/?s=20 "
+        ),
+        "https://x.com/user/status/1"
+    );
+    assert_eq!(
+        canonical(SocialMedia::INSTAGRAM, "https://instagram.com/p/abc/"),
+        "https://www.instagram.com/p/abc"
+    );
+
+    // Short links keep their host, unparsable links are left alone
+    assert_eq!(
+        canonical(SocialMedia::YOUTUBE, "https://youtu.be/abc?si=track"),
+        "https://youtu.be/abc"
+    );
+    assert_eq!(canonical(SocialMedia::X, "not a link"), "not a link");
+}
+
+#[test]
+fn platform_ids_are_case_insensitive() {
+    let legacy: Vec<SocialMedia> =
+        serde_json::from_str(r#"["Youtube", "X", "Instagram", "Facebook"]"#).unwrap();
+    assert_eq!(
+        legacy,
+        vec![
+            SocialMedia::YOUTUBE,
+            SocialMedia::X,
+            SocialMedia::INSTAGRAM,
+            SocialMedia::FACEBOOK
+        ]
+    );
+    assert_eq!(
+        serde_json::to_string(&SocialMedia::YOUTUBE).unwrap(),
+        "\"youtube\""
+    );
+    assert_eq!(SocialMedia::new(" TikTok "), SocialMedia::new("tiktok"));
+}
+
+#[test]
+fn custom_platforms_extend_and_replace_built_in_ones() {
+    let mut renamed = custom("X", "Twitter", "x.com");
+    renamed.home_url = "https://x.com/home".to_string();
+    let mut invalid = custom("reddit", "Reddit", "reddit.com");
+    invalid.home_url = "http://reddit.com/".to_string();
+
+    let registry = PlatformRegistry::with_custom(vec![
+        custom("tiktok", "TikTok", "tiktok.com"),
+        renamed,
+        invalid,
+    ]);
+
+    let ids: Vec<&str> = registry.all().iter().map(|p| p.id.as_str()).collect();
+    assert_eq!(ids, vec!["youtube", "x", "instagram", "facebook", "tiktok"]);
+    assert_eq!(registry.name(&SocialMedia::X), "Twitter");
+    assert_eq!(
+        registry
+            .detect("https://www.tiktok.com/@user/video/1")
+            .unwrap()
+            .name,
+        "TikTok"
+    );
+    assert_eq!(registry.name(&SocialMedia::new("reddit")), "reddit");
+}
+
+#[tokio::test]
+async fn platforms_file_is_loaded_from_the_config_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = JsonStorage::new(dir.path().to_path_buf());
+    assert_eq!(
+        PlatformRegistry::load(&storage).await,
+        PlatformRegistry::builtin()
+    );
+
+    let platforms = serde_json::json!([{
+        "id": "linkedin",
+        "name": "LinkedIn",
+        "hosts": ["linkedin.com"],
+        "home_url": "https://www.linkedin.com/feed/",
+        "link_rules": {"host": "www.linkedin.com", "aliases": ["linkedin.com"], "keep_query": []}
+    }, {
+        "id": "mastodon",
+        "name": "Mastodon",
+        "hosts": ["mastodon.social"],
+        "home_url": "https://mastodon.social/"
+    }]);
+    std::fs::write(dir.path().join(PLATFORMS_FILE), platforms.to_string()).unwrap();
+
+    let registry = PlatformRegistry::load(&storage).await;
+    let linkedin = registry.get(&SocialMedia::new("LinkedIn")).unwrap();
+    assert_eq!(
+        linkedin.canonicalize("https://linkedin.com/posts/1?utm_source=share"),
+        "https://www.linkedin.com/posts/1"
+    );
+
+    // Without a list of parameters to keep, the query is left alone
+    let mastodon = registry.get(&SocialMedia::new("mastodon")).unwrap();
+    assert_eq!(
+        mastodon.canonicalize("https://mastodon.social/@user/1?lang=de#top"),
+        "https://mastodon.social/@user/1?lang=de"
+    );
+
+    // A broken file falls back to the built-in platforms
+    std::fs::write(dir.path().join(PLATFORMS_FILE), "[{").unwrap();
+    assert_eq!(
+        PlatformRegistry::load(&storage).await,
+        PlatformRegistry::builtin()
+    );
+}
@@ -0,0 +1,76 @@
+//! Checks cache clearing and orphan cleanup of the profile data directories
+
+use attache_lib::profile_data;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Writes a file of the given size, creating its parent directories
+fn write_file(path: &Path, size: usize) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, vec![0u8; size]).unwrap();
+}
+
+#[tokio::test]
+async fn clearing_the_cache_keeps_site_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let profile = dir.path().join("profile");
+    write_file(&profile.join("Default/Cache/data_0"), 100);
+    write_file(&profile.join("Default/Code Cache/js/index"), 50);
+    write_file(
+        &profile.join("Default/Service Worker/CacheStorage/entry"),
+        10,
+    );
+    write_file(&profile.join("Default/Cookies"), 5);
+
+    let usage = profile_data::disk_usage(Uuid::new_v4(), profile.clone())
+        .await
+        .unwrap();
+    assert_eq!((usage.size_bytes, usage.cache_bytes), (165, 150));
+
+    assert_eq!(
+        profile_data::clear_cache(profile.clone()).await.unwrap(),
+        150
+    );
+    assert!(!profile.join("Default/Cache").exists());
+    assert!(profile
+        .join("Default/Service Worker/CacheStorage/entry")
+        .exists());
+    assert!(profile.join("Default/Cookies").exists());
+}
+
+#[tokio::test]
+async fn only_orphan_directories_inside_the_profiles_directory_are_deleted() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().join("profiles");
+    let known = Uuid::new_v4();
+    let orphan = Uuid::new_v4();
+    write_file(&root.join(known.to_string()).join("Cookies"), 10);
+    write_file(&root.join(orphan.to_string()).join("Cookies"), 20);
+    write_file(&root.join("not-a-profile").join("file"), 30);
+
+    // A link named like a profile must not take its target with it
+    let outside = dir.path().join("outside");
+    write_file(&outside.join("keep"), 40);
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&outside, root.join(Uuid::new_v4().to_string())).unwrap();
+
+    let freed = profile_data::delete_orphans(root.clone(), HashSet::from([known]))
+        .await
+        .unwrap();
+    assert_eq!(freed, 20);
+    assert!(!root.join(orphan.to_string()).exists());
+    assert!(root.join(known.to_string()).exists());
+    assert!(root.join("not-a-profile").exists());
+    assert!(outside.join("keep").exists());
+
+    // A missing profiles directory has nothing to delete
+    let missing = dir.path().join("missing");
+    assert_eq!(
+        profile_data::delete_orphans(missing, HashSet::new())
+            .await
+            .unwrap(),
+        0
+    );
+}
@@ -0,0 +1,142 @@
+//! Turns data encryption on against the file-based backends and checks what reaches the disk
+
+mod common;
+
+use attache_lib::audit::AuditSource;
+use attache_lib::data;
+use attache_lib::security::SecurityError;
+use attache_lib::state::AppState;
+use attache_lib::storage::{config_dir, StorageError, StorageKind};
+use common::Fixture;
+use std::path::Path;
+use tempfile::TempDir;
+
+const PASSPHRASE: &str = "correct horse battery";
+/// Profile name looked for in the data files
+const SECRET: &str = "confidential-profile";
+
+/// Backends that write the data to disk
+const FILE_BACKENDS: [StorageKind; 2] = [StorageKind::Json, StorageKind::Sqlite];
+
+/// Loads a state whose data lives in `data_dir`, adds a profile and encrypts the data
+async fn encrypted(kind: StorageKind, data_dir: &TempDir) -> Fixture {
+    let fixture = Fixture::with_storage(kind.open(data_dir.path())).await;
+    fixture.add_profile(SECRET).await;
+    data::set_passphrase(&fixture.state, None, PASSPHRASE.to_string(), true)
+        .await
+        .expect("set passphrase");
+    fixture
+}
+
+/// Loads the data of `data_dir` again, as on the next start of the app
+async fn reopen(kind: StorageKind, data_dir: &TempDir, fixture: &Fixture) -> AppState {
+    AppState::load(
+        kind.open(data_dir.path()),
+        fixture.app_data_dir.path().to_path_buf(),
+    )
+    .await
+    .expect("load state")
+}
+
+/// Whether any file the backend wrote contains the text
+fn on_disk(data_dir: &Path, text: &str) -> bool {
+    std::fs::read_dir(config_dir(data_dir))
+        .expect("read config directory")
+        .map(|entry| std::fs::read(entry.expect("read entry").path()).unwrap_or_default())
+        .any(|data| String::from_utf8_lossy(&data).contains(text))
+}
+
+#[tokio::test]
+async fn encrypted_data_reaches_the_disk_sealed() {
+    for kind in FILE_BACKENDS {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = encrypted(kind, &dir).await;
+
+        assert!(!on_disk(dir.path(), SECRET), "{:?} wrote plaintext", kind);
+        // The key stays usable for the running app
+        assert_eq!(
+            fixture.state.storage().read_profiles().await.unwrap()[0].profile_name,
+            SECRET
+        );
+    }
+}
+
+#[tokio::test]
+async fn a_wrong_passphrase_does_not_unlock_the_data() {
+    for kind in FILE_BACKENDS {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = encrypted(kind, &dir).await;
+        let state = reopen(kind, &dir, &fixture).await;
+
+        assert!(data::unlock(&state, "wrong passphrase".to_string())
+            .await
+            .is_err());
+        assert!(state.is_locked().await);
+        assert!(state.get_profiles().await.is_empty());
+        assert!(matches!(
+            state.storage().read_profiles().await,
+            Err(StorageError::Security(SecurityError::Locked))
+        ));
+    }
+}
+
+#[tokio::test]
+async fn nothing_is_written_while_locked() {
+    for kind in FILE_BACKENDS {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = encrypted(kind, &dir).await;
+        let profiles = fixture.state.get_profiles().await;
+
+        assert!(data::lock(&fixture.state).await.unwrap());
+        assert!(fixture.state.get_profiles().await.is_empty());
+        assert!(matches!(
+            fixture.state.storage().write_profiles(&profiles).await,
+            Err(StorageError::Security(SecurityError::Locked))
+        ));
+        assert!(
+            data::save_profiles(&fixture.state, profiles, AuditSource::command("test"))
+                .await
+                .is_err()
+        );
+        assert!(!on_disk(dir.path(), SECRET), "{:?} wrote plaintext", kind);
+    }
+}
+
+#[tokio::test]
+async fn unlocking_loads_the_encrypted_data() {
+    for kind in FILE_BACKENDS {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = encrypted(kind, &dir).await;
+        let state = reopen(kind, &dir, &fixture).await;
+        assert!(state.is_locked().await);
+        assert!(state.get_profiles().await.is_empty());
+
+        assert!(data::unlock(&state, PASSPHRASE.to_string()).await.unwrap());
+
+        assert!(!state.is_locked().await);
+        let profiles = state.get_profiles().await;
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].profile_name, SECRET);
+    }
+}
+
+#[tokio::test]
+async fn removing_the_passphrase_rewrites_the_data_in_plaintext() {
+    for kind in FILE_BACKENDS {
+        let dir = tempfile::tempdir().unwrap();
+        let fixture = encrypted(kind, &dir).await;
+
+        data::remove_passphrase(&fixture.state, PASSPHRASE.to_string())
+            .await
+            .unwrap();
+
+        assert!(
+            on_disk(dir.path(), SECRET),
+            "{:?} kept the data sealed",
+            kind
+        );
+        let state = reopen(kind, &dir, &fixture).await;
+        assert!(!state.is_locked().await);
+        assert_eq!(state.get_profiles().await[0].profile_name, SECRET);
+    }
+}
@@ -0,0 +1,212 @@
+//! Checks how settings and the window layout are validated, repaired and applied to the screen
+
+use attache_lib::settings::Settings;
+use attache_lib::shortcut_bindings::ShortcutBindings;
+use attache_lib::window_layout::{
+    MonitorInfo, PanelSide, Rect, WindowLayout, MAX_SPLIT_RATIO, MIN_SPLIT_RATIO,
+};
+
+/// A monitor area that does not start at the origin, as a secondary monitor would
+const AREA: Rect = Rect {
+    x: 100.0,
+    y: 50.0,
+    width: 1000.0,
+    height: 800.0,
+};
+
+/// A layout with the given split ratio and defaults otherwise
+fn layout(split_ratio: f64) -> WindowLayout {
+    WindowLayout {
+        split_ratio,
+        ..WindowLayout::default()
+    }
+}
+
+/// A connected monitor whose area starts at `x`
+fn monitor(name: &str, x: f64, is_primary: bool) -> MonitorInfo {
+    MonitorInfo {
+        name: Some(name.to_string()),
+        area: Rect { x, ..AREA },
+        is_primary,
+    }
+}
+
+#[test]
+fn split_ratio_limits_are_inclusive() {
+    assert!(layout(MIN_SPLIT_RATIO).validate().is_ok());
+    assert!(layout(MAX_SPLIT_RATIO).validate().is_ok());
+
+    assert!(layout(MIN_SPLIT_RATIO - 0.01).validate().is_err());
+    assert!(layout(MAX_SPLIT_RATIO + 0.01).validate().is_err());
+    assert!(layout(f64::NAN).validate().is_err());
+}
+
+#[test]
+fn clamping_brings_the_split_ratio_into_range() {
+    let clamped = |split_ratio| {
+        let mut layout = layout(split_ratio);
+        layout.clamp();
+        layout.split_ratio
+    };
+
+    assert_eq!(clamped(0.0), MIN_SPLIT_RATIO);
+    assert_eq!(clamped(1.5), MAX_SPLIT_RATIO);
+    assert_eq!(clamped(f64::NAN), WindowLayout::default().split_ratio);
+    assert_eq!(clamped(0.5), 0.5);
+}
+
+#[test]
+fn tiling_puts_the_panel_on_the_right() {
+    let layout = WindowLayout {
+        panel_side: PanelSide::Right,
+        ..layout(0.6)
+    };
+
+    let (profile, panel) = layout.tile(AREA);
+    assert_eq!(
+        profile,
+        Rect {
+            width: 600.0,
+            ..AREA
+        }
+    );
+    assert_eq!(
+        panel,
+        Rect {
+            x: 700.0,
+            width: 400.0,
+            ..AREA
+        }
+    );
+}
+
+#[test]
+fn tiling_puts_the_panel_on_the_left() {
+    let layout = WindowLayout {
+        panel_side: PanelSide::Left,
+        ..layout(0.6)
+    };
+
+    let (profile, panel) = layout.tile(AREA);
+    assert_eq!(
+        profile,
+        Rect {
+            x: 500.0,
+            width: 600.0,
+            ..AREA
+        }
+    );
+    assert_eq!(
+        panel,
+        Rect {
+            width: 400.0,
+            ..AREA
+        }
+    );
+}
+
+#[test]
+fn tiling_covers_the_whole_area_without_overlap() {
+    let area = Rect {
+        width: 1001.0,
+        ..AREA
+    };
+
+    for panel_side in [PanelSide::Left, PanelSide::Right] {
+        for split_ratio in [MIN_SPLIT_RATIO, 0.333, MAX_SPLIT_RATIO] {
+            let layout = WindowLayout {
+                panel_side,
+                ..layout(split_ratio)
+            };
+            let (profile, panel) = layout.tile(area);
+
+            assert_eq!(profile.width + panel.width, area.width);
+            let (left, right) = match panel_side {
+                PanelSide::Left => (panel, profile),
+                PanelSide::Right => (profile, panel),
+            };
+            assert_eq!(left.x, area.x);
+            assert_eq!(left.x + left.width, right.x);
+        }
+    }
+}
+
+#[test]
+fn the_configured_monitor_is_used_while_it_is_connected() {
+    let monitors = [
+        monitor("primary", 0.0, true),
+        monitor("side", 1000.0, false),
+    ];
+    let layout = WindowLayout {
+        monitor: Some("side".to_string()),
+        ..WindowLayout::default()
+    };
+
+    assert_eq!(layout.monitor_area(&monitors), Some(monitors[1].area));
+}
+
+#[test]
+fn a_disconnected_monitor_falls_back_to_the_primary_one() {
+    let monitors = [
+        monitor("side", 1000.0, false),
+        monitor("primary", 0.0, true),
+    ];
+    let disconnected = WindowLayout {
+        monitor: Some("projector".to_string()),
+        ..WindowLayout::default()
+    };
+
+    assert_eq!(disconnected.monitor_area(&monitors), Some(monitors[1].area));
+    assert_eq!(
+        WindowLayout::default().monitor_area(&monitors),
+        Some(monitors[1].area)
+    );
+
+    // Without a primary monitor the first one is used, without any monitor none
+    let no_primary = [monitor("side", 1000.0, false)];
+    assert_eq!(
+        disconnected.monitor_area(&no_primary),
+        Some(no_primary[0].area)
+    );
+    assert_eq!(disconnected.monitor_area(&[]), None);
+}
+
+#[test]
+fn settings_with_invalid_shortcuts_do_not_validate() {
+    assert!(Settings::default().validate().is_ok());
+
+    let mut unparsable = Settings::default();
+    unparsable.shortcuts.next = "not a shortcut".to_string();
+    assert!(unparsable.validate().is_err());
+
+    let mut duplicated = Settings::default();
+    duplicated.shortcuts.skip = duplicated.shortcuts.next.clone();
+    assert!(duplicated.validate().is_err());
+
+    // Unbound actions are allowed
+    let mut unbound = Settings::default();
+    unbound.shortcuts.pause = String::new();
+    assert!(unbound.validate().is_ok());
+}
+
+#[test]
+fn repairing_resets_invalid_shortcuts_and_keeps_valid_ones() {
+    let mut broken = Settings {
+        auto_copy_comment: false,
+        ..Settings::default()
+    };
+    broken.shortcuts.previous = "not a shortcut".to_string();
+    broken.layout.split_ratio = 0.1;
+    broken.repair();
+
+    assert!(broken.validate().is_ok());
+    assert_eq!(broken.shortcuts, ShortcutBindings::default());
+    assert_eq!(broken.layout.split_ratio, MIN_SPLIT_RATIO);
+    assert!(!broken.auto_copy_comment);
+
+    let mut custom = Settings::default();
+    custom.shortcuts.next = "CommandOrControl+Shift+N".to_string();
+    let expected = custom.clone();
+    custom.repair();
+    assert_eq!(custom, expected);
+}
@@ -0,0 +1,457 @@
+//! Exercises the storage backends, the file-based ones against a temporary directory
+
+mod common;
+
+use attache_lib::audit::{AuditEntity, AuditEntry, AuditSource};
+use attache_lib::automation;
+use attache_lib::models::{BriefCase, BriefCaseGroup, Profile, Session, SessionStep, SocialMedia};
+use attache_lib::platforms::{LinkRules, Platform};
+use attache_lib::security::SecurityConfig;
+use attache_lib::settings::Settings;
+use attache_lib::state::AppState;
+use attache_lib::storage::{
+    DataSnapshot, JsonStorage, MemoryStorage, SqliteStorage, StorageBackend, StorageError,
+    StoreLock, BRIEFCASES_FILE, DATABASE_FILE, PROFILES_FILE, SECURITY_FILE,
+};
+use attache_lib::window_layout::MAX_SPLIT_RATIO;
+use chrono::Utc;
+use common::{tasks_json, Fixture};
+use std::path::Path;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Writes a profile with one account and a group containing it
+async fn write_sample(storage: &dyn StorageBackend) -> (Profile, BriefCase, BriefCaseGroup) {
+    let profile = Profile {
+        profile_id: Uuid::new_v4(),
+        profile_name: "main".to_string(),
+        notes: Some("notes".to_string()),
+        color: None,
+        tags: vec!["tag".to_string()],
+        sort_order: 0,
+    };
+    let briefcase = BriefCase::new(SocialMedia::X, profile.profile_id, "user".to_string());
+    let group = BriefCaseGroup::new("group".to_string(), vec![briefcase.id]);
+
+    storage
+        .write_data(
+            std::slice::from_ref(&profile),
+            std::slice::from_ref(&briefcase),
+            std::slice::from_ref(&group),
+        )
+        .await
+        .unwrap();
+
+    (profile, briefcase, group)
+}
+
+/// Checks that a backend returns the sample written by `write_sample`
+async fn assert_sample(
+    storage: &dyn StorageBackend,
+    (profile, briefcase, group): &(Profile, BriefCase, BriefCaseGroup),
+) {
+    assert_eq!(
+        storage.read_profiles().await.unwrap(),
+        vec![profile.clone()]
+    );
+    assert_eq!(
+        storage.read_briefcases().await.unwrap(),
+        vec![briefcase.clone()]
+    );
+    assert_eq!(storage.read_groups().await.unwrap(), vec![group.clone()]);
+}
+
+/// Opens a fresh SQLite backend in a directory
+async fn open_sqlite(config_dir: &Path) -> SqliteStorage {
+    let storage = SqliteStorage::new(config_dir.to_path_buf());
+    storage.initialize().await.unwrap();
+    storage
+}
+
+/// A user-defined platform
+fn platform(id: &str) -> Platform {
+    Platform {
+        id: SocialMedia::new(id),
+        name: id.to_string(),
+        hosts: vec![format!("{}.com", id)],
+        home_url: format!("https://{}.com/", id),
+        link_rules: LinkRules::default(),
+    }
+}
+
+/// Restores a data set over the sample and checks that only the audit log survives
+async fn assert_restore(storage: &dyn StorageBackend) {
+    write_sample(storage).await;
+    storage.write_platforms(&[platform("old")]).await.unwrap();
+    let entry = AuditEntry::new(
+        AuditSource::command("test"),
+        AuditEntity::Backup,
+        None,
+        None,
+        None,
+    );
+    storage
+        .append_audit(std::slice::from_ref(&entry))
+        .await
+        .unwrap();
+
+    let session = Session::new(Vec::new(), 1);
+    let step = SessionStep {
+        session_id: session.id,
+        step_index: 0,
+        task_index: 0,
+        briefcase_id: Uuid::new_v4(),
+        profile_id: Uuid::new_v4(),
+        link: "https://x.com/post".to_string(),
+        comment: Some("a".to_string()),
+        completed_at: Utc::now(),
+    };
+    let data = DataSnapshot {
+        settings: Settings {
+            auto_copy_comment: true,
+            ..Settings::default()
+        },
+        platforms: vec![platform("new")],
+        sessions: vec![session.clone()],
+        steps: vec![step.clone()],
+        ..DataSnapshot::default()
+    };
+    storage.restore(&data).await.unwrap();
+
+    assert!(storage.read_profiles().await.unwrap().is_empty());
+    assert!(storage.read_briefcases().await.unwrap().is_empty());
+    assert!(storage.read_groups().await.unwrap().is_empty());
+    assert!(storage.read_settings().await.unwrap().auto_copy_comment);
+    assert_eq!(
+        storage.read_platforms().await.unwrap(),
+        vec![platform("new")]
+    );
+    assert_eq!(storage.read_sessions().await.unwrap().len(), 1);
+    assert_eq!(
+        storage.read_session_steps(session.id).await.unwrap(),
+        vec![step]
+    );
+    let audit = storage.read_audit().await.unwrap();
+    assert_eq!(audit.len(), 1);
+    assert_eq!(audit[0].id, entry.id);
+}
+
+/// Trims an audit log of five entries down to the newest three
+async fn assert_trim_audit(storage: &dyn StorageBackend) {
+    let entries: Vec<AuditEntry> = (0..5)
+        .map(|_| {
+            AuditEntry::new(
+                AuditSource::command("test"),
+                AuditEntity::Backup,
+                None,
+                None,
+                None,
+            )
+        })
+        .collect();
+    storage.append_audit(&entries).await.unwrap();
+
+    storage.trim_audit(10).await.unwrap();
+    assert_eq!(storage.read_audit().await.unwrap().len(), 5);
+
+    storage.trim_audit(3).await.unwrap();
+    let ids: Vec<Uuid> = storage
+        .read_audit()
+        .await
+        .unwrap()
+        .iter()
+        .map(|entry| entry.id)
+        .collect();
+    let newest: Vec<Uuid> = entries[2..].iter().map(|entry| entry.id).collect();
+    assert_eq!(ids, newest);
+}
+
+/// Rekeys a backend holding the sample, first setting and then removing a passphrase
+async fn assert_rekey(storage: &dyn StorageBackend) {
+    let sample = write_sample(storage).await;
+    let (config, _) = SecurityConfig::create("passphrase", false).unwrap();
+    let entry = AuditEntry::new(
+        AuditSource::command("test"),
+        AuditEntity::Backup,
+        None,
+        None,
+        None,
+    );
+    let data = DataSnapshot {
+        profiles: vec![sample.0.clone()],
+        briefcases: vec![sample.1.clone()],
+        groups: vec![sample.2.clone()],
+        ..DataSnapshot::default()
+    };
+
+    storage
+        .rekey(&data, std::slice::from_ref(&entry), Some(&config))
+        .await
+        .unwrap();
+    assert_sample(storage, &sample).await;
+    assert_eq!(storage.read_audit().await.unwrap()[0].id, entry.id);
+    let stored = storage.read_security().await.unwrap().unwrap();
+    assert_eq!(stored.salt, config.salt);
+
+    storage.rekey(&data, &[], None).await.unwrap();
+    assert_sample(storage, &sample).await;
+    assert!(storage.read_audit().await.unwrap().is_empty());
+    assert!(storage.read_security().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn memory_backend_round_trips_data() {
+    let storage = MemoryStorage::default();
+    storage.initialize().await.unwrap();
+    assert_eq!(storage.read_settings().await.unwrap(), Settings::default());
+    assert!(storage.read_security().await.unwrap().is_none());
+
+    let sample = write_sample(&storage).await;
+    assert_sample(&storage, &sample).await;
+
+    // Sessions are upserted and their steps kept apart
+    let mut session = Session::new(Vec::new(), 2);
+    storage.write_session(&session).await.unwrap();
+    session.ended_at = Some(Utc::now());
+    storage.write_session(&session).await.unwrap();
+    let sessions = storage.read_sessions().await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert!(sessions[0].ended_at.is_some());
+    assert!(storage
+        .read_session_steps(session.id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let stats = storage.data_file_stats().await.unwrap();
+    let profiles = stats.iter().find(|s| s.name == PROFILES_FILE).unwrap();
+    assert_eq!(profiles.records, Some(1));
+}
+
+#[tokio::test]
+async fn every_backend_restores_all_data_but_the_audit_log() {
+    assert_restore(&MemoryStorage::default()).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let json = JsonStorage::new(dir.path().join("json"));
+    json.initialize().await.unwrap();
+    assert_restore(&json).await;
+
+    assert_restore(&open_sqlite(&dir.path().join("sqlite")).await).await;
+}
+
+#[tokio::test]
+async fn every_backend_trims_the_audit_log_to_the_newest_entries() {
+    assert_trim_audit(&MemoryStorage::default()).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let json = JsonStorage::new(dir.path().join("json"));
+    json.initialize().await.unwrap();
+    assert_trim_audit(&json).await;
+
+    assert_trim_audit(&open_sqlite(&dir.path().join("sqlite")).await).await;
+}
+
+#[tokio::test]
+async fn every_backend_rekeys_the_data_together_with_the_passphrase_configuration() {
+    assert_rekey(&MemoryStorage::default()).await;
+
+    let dir = tempfile::tempdir().unwrap();
+    let json = JsonStorage::new(dir.path().join("json"));
+    json.initialize().await.unwrap();
+    assert_rekey(&json).await;
+    assert!(!dir.path().join("json").join(SECURITY_FILE).exists());
+
+    assert_rekey(&open_sqlite(&dir.path().join("sqlite")).await).await;
+}
+
+#[tokio::test]
+async fn json_backend_round_trips_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = JsonStorage::new(dir.path().to_path_buf());
+    storage.initialize().await.unwrap();
+
+    let sample = write_sample(&storage).await;
+    let reopened = JsonStorage::new(dir.path().to_path_buf());
+    assert_sample(&reopened, &sample).await;
+}
+
+#[tokio::test]
+async fn sqlite_backend_round_trips_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let sample = write_sample(&open_sqlite(dir.path()).await).await;
+
+    let reopened = open_sqlite(dir.path()).await;
+    assert_sample(&reopened, &sample).await;
+    assert!(dir.path().join(DATABASE_FILE).exists());
+}
+
+#[tokio::test]
+async fn sqlite_backend_migrates_json_files_once() {
+    let dir = tempfile::tempdir().unwrap();
+    let json = JsonStorage::new(dir.path().to_path_buf());
+    json.initialize().await.unwrap();
+    let sample = write_sample(&json).await;
+    let settings = Settings {
+        auto_copy_comment: true,
+        ..Settings::default()
+    };
+    json.write_settings(&settings).await.unwrap();
+
+    let sqlite = open_sqlite(dir.path()).await;
+    assert_sample(&sqlite, &sample).await;
+    assert!(sqlite.read_settings().await.unwrap().auto_copy_comment);
+    assert!(!dir.path().join(PROFILES_FILE).exists());
+
+    // Data written after the migration is not overwritten by a second start
+    sqlite.write_profiles(&[]).await.unwrap();
+    let reopened = open_sqlite(dir.path()).await;
+    assert!(reopened.read_profiles().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn sqlite_backend_skips_corrupt_json_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let json = JsonStorage::new(dir.path().to_path_buf());
+    json.initialize().await.unwrap();
+    let (_, briefcase, _) = write_sample(&json).await;
+    std::fs::write(dir.path().join(PROFILES_FILE), "[{").unwrap();
+
+    let sqlite = open_sqlite(dir.path()).await;
+    assert!(sqlite.read_profiles().await.unwrap().is_empty());
+    assert_eq!(sqlite.read_briefcases().await.unwrap(), vec![briefcase]);
+
+    // The corrupt file stays where it was, the readable ones are moved aside
+    assert!(dir.path().join(PROFILES_FILE).exists());
+    assert!(!dir.path().join(BRIEFCASES_FILE).exists());
+}
+
+#[tokio::test]
+async fn read_only_backends_neither_create_nor_migrate_data() {
+    let dir = tempfile::tempdir().unwrap();
+    let json_dir = dir.path().join("json");
+    let json = JsonStorage::read_only(json_dir.clone());
+    json.initialize().await.unwrap();
+    assert!(json.read_profiles().await.unwrap().is_empty());
+    assert!(!json_dir.exists());
+
+    let sqlite = SqliteStorage::read_only(dir.path().to_path_buf());
+    assert!(matches!(
+        sqlite.initialize().await,
+        Err(StorageError::ReadOnly(_))
+    ));
+    assert!(!dir.path().join(DATABASE_FILE).exists());
+
+    let sample = write_sample(&open_sqlite(dir.path()).await).await;
+    let sqlite = SqliteStorage::read_only(dir.path().to_path_buf());
+    sqlite.initialize().await.unwrap();
+    assert_sample(&sqlite, &sample).await;
+    assert!(sqlite.write_profiles(&[]).await.is_err());
+}
+
+#[test]
+fn only_one_process_at_a_time_holds_the_data_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let lock = StoreLock::acquire(dir.path()).unwrap();
+    assert!(matches!(
+        StoreLock::acquire(dir.path()),
+        Err(StorageError::Locked)
+    ));
+
+    drop(lock);
+    StoreLock::acquire(dir.path()).unwrap();
+}
+
+#[tokio::test]
+async fn loading_fails_if_the_passphrase_configuration_is_unreadable() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_dir = dir.path().join("config");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(config_dir.join(SECURITY_FILE), "{").unwrap();
+
+    // Starting without it would save encrypted data back in plaintext
+    let storage = Arc::new(JsonStorage::new(config_dir));
+    let loaded = AppState::load(storage, dir.path().to_path_buf()).await;
+    assert!(loaded.is_err());
+}
+
+#[tokio::test]
+async fn loading_repairs_settings_that_cannot_be_applied() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = Arc::new(MemoryStorage::default());
+    let mut settings = Settings::default();
+    settings.layout.split_ratio = 3.0;
+    settings.shortcuts.next = "not a shortcut".to_string();
+    storage.write_settings(&settings).await.unwrap();
+
+    let state = AppState::load(storage, dir.path().to_path_buf())
+        .await
+        .unwrap();
+    let loaded = state.get_settings().await;
+    assert_eq!(loaded.layout.split_ratio, MAX_SPLIT_RATIO);
+    assert_eq!(loaded.shortcuts, Settings::default().shortcuts);
+    assert!(loaded.validate().is_ok());
+}
+
+#[tokio::test]
+async fn platforms_stored_by_their_old_enum_names_still_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let profile_id = Uuid::new_v4();
+    let legacy = serde_json::json!([
+        {"id": Uuid::new_v4(), "social_media": "Youtube", "profile_id": profile_id, "user_name": "a"},
+        {"id": Uuid::new_v4(), "social_media": "X", "profile_id": profile_id, "user_name": "b"},
+    ]);
+    std::fs::write(dir.path().join(BRIEFCASES_FILE), legacy.to_string()).unwrap();
+
+    let json = JsonStorage::new(dir.path().to_path_buf());
+    let platforms: Vec<SocialMedia> = json
+        .read_briefcases()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|b| b.social_media)
+        .collect();
+    assert_eq!(platforms, vec![SocialMedia::YOUTUBE, SocialMedia::X]);
+
+    // The migrated rows are written with the registry IDs
+    let sqlite = open_sqlite(dir.path()).await;
+    let briefcases = sqlite.read_briefcases().await.unwrap();
+    assert_eq!(briefcases[0].social_media, SocialMedia::YOUTUBE);
+    assert_eq!(
+        serde_json::to_value(&briefcases[1]).unwrap()["social_media"],
+        "x"
+    );
+}
+
+#[tokio::test]
+async fn sqlite_backend_records_a_full_run() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = Arc::new(SqliteStorage::new(dir.path().join("config")));
+    let fixture = Fixture::with_storage(storage.clone()).await;
+    let profile = fixture.add_profile("main").await;
+    fixture.add_briefcase(&profile, SocialMedia::YOUTUBE).await;
+
+    let tasks = tasks_json(&[(
+        "https://youtube.com/watch?v=1",
+        SocialMedia::YOUTUBE,
+        &["a"],
+    )]);
+    let state = &fixture.state;
+    automation::start(state, &tasks, AuditSource::command("test"))
+        .await
+        .unwrap();
+    automation::complete_current(state).await.unwrap();
+    assert!(automation::advance(state).await.unwrap().completed);
+
+    let sessions = storage.read_sessions().await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].tasks.len(), 1);
+    assert!(sessions[0].ended_at.is_some());
+
+    let steps = storage.read_session_steps(sessions[0].id).await.unwrap();
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].comment.as_deref(), Some("a"));
+
+    let audit = storage.read_audit().await.unwrap();
+    assert!(audit.iter().any(|entry| entry.command == "test"));
+}
@@ -7,6 +7,7 @@ import {Toaster} from "@/components/ui/toaster"
 import {useStore, setupEventListeners} from './store';
 import {ErrorSystem} from './components/ui/ErrorSystem';
 import {MetricsHeader} from './components/profiles/MetricsHeader';
+import {LoginCheckBar} from './components/profiles/LoginCheckBar';
 import {ProfileCard} from './components/profiles/ProfileCard';
 import {TaskManager} from './components/tasks/TaskManager';
 import {TabNavigation} from './components/ui/TabNavigation';
@@ -88,6 +89,7 @@ export default function App() {
 
                                      <Box flexShrink={0}>
                                          <MetricsHeader/>
+                                         <LoginCheckBar/>
                                      </Box>
 
                                      {/* Scrollable Container for Cards */}
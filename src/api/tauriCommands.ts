@@ -1,6 +1,6 @@
 import {invoke} from '@tauri-apps/api/core';
 import {writeText} from '@tauri-apps/plugin-clipboard-manager';
-import {BriefCase, Profile} from '../types';
+import {BriefCase, BriefCaseGroup, LoginStatus, Platform, Profile, SocialMedia, Task} from '../types';
 
 
 export interface ScreenshotInfo {
@@ -46,16 +46,69 @@ export const saveBriefcases = (briefcases: BriefCase[]): Promise<void> =>
 export const saveAllData = (profiles: Profile[], briefcases: BriefCase[]): Promise<void> =>
     invoke('save_all_data', {profiles, briefcases});
 
+export const listPlatforms = (): Promise<Platform[]> =>
+    invoke('list_platforms');
 
-// export const prevWorkspaceItem = (): Promise<void> =>
-//     invoke('prev_task_execution');
+export const detectPlatform = (link: string): Promise<Platform | null> =>
+    invoke('detect_platform', {link});
+
+
+export interface ExecutionResult {
+    completed: boolean;
+    profile_id: string;
+    link: string;
+    should_change_profile: boolean;
+    task_index: number;
+    comment: string;
+    // Set when the step was loaded but something around it failed, e.g. copying the comment
+    warning: string | null;
+}
+
+export const prevWorkspaceItem = (): Promise<ExecutionResult> =>
+    invoke('previous_execution');
+
+export const skipWorkspaceItem = (): Promise<ExecutionResult> =>
+    invoke('skip_execution');
 
 export const changeWebviewUrl = (url: string): Promise<void> =>
     invoke('change_webview_url', {url});
 
-export const nextWorkspaceItem = (): Promise<void> =>
+export const restoreProfileWindow = (): Promise<string> =>
+    invoke('restore_profile_window');
+
+export const nextWorkspaceItem = (): Promise<ExecutionResult> =>
     invoke('next_execution');
 
+export const gotoStep = (stepIndex: number): Promise<ExecutionResult> =>
+    invoke('goto_step', {stepIndex});
+
+export const gotoTask = (taskIndex: number, briefcaseId: string | null): Promise<ExecutionResult> =>
+    invoke('goto_task', {taskIndex, briefcaseId});
+
+export const addTaskToSession = (task: Task): Promise<number> =>
+    invoke('add_task_to_session', {task});
+
+export const removeTaskFromSession = (taskIndex: number): Promise<ExecutionResult | null> =>
+    invoke('remove_task_from_session', {taskIndex});
+
+export const editTaskComments = (taskIndex: number, comments: string[]): Promise<void> =>
+    invoke('edit_task_comments', {taskIndex, comments});
+
+export interface SessionTime {
+    paused: boolean;
+    active_ms: number;
+    paused_ms: number;
+}
+
+export const pauseAutomation = (): Promise<SessionTime> =>
+    invoke('pause_automation');
+
+export const resumeAutomation = (): Promise<SessionTime> =>
+    invoke('resume_automation');
+
+export const getSessionTime = (): Promise<SessionTime | null> =>
+    invoke('get_session_time');
+
 export const setCommentIndex = (taskIndex: number, commentIndex: number): Promise<void> =>
     invoke('set_comment_index', {task_index: taskIndex, comment_index: commentIndex});
 
@@ -63,6 +116,266 @@ export const copyToClipboard = async (text: string): Promise<void> => {
     await writeText(text);
 };
 
+export const copyCurrentComment = (): Promise<string> =>
+    invoke('copy_current_comment');
+
+export const setAutoCopyComment = (enabled: boolean): Promise<void> =>
+    invoke('set_auto_copy_comment', {enabled});
+
+export const getAutoCopyComment = (): Promise<boolean> =>
+    invoke('get_auto_copy_comment');
+
+export interface ShortcutBindings {
+    next: string;
+    previous: string;
+    skip: string;
+    copy_comment: string;
+    pause: string;
+}
+
+export const getShortcuts = (): Promise<ShortcutBindings> =>
+    invoke('get_shortcuts');
+
+export const setShortcuts = (shortcuts: ShortcutBindings): Promise<void> =>
+    invoke('set_shortcuts', {shortcuts});
+
+export interface WindowLayout {
+    split_ratio: number;
+    panel_side: 'Left' | 'Right';
+    monitor: string | null;
+    panel_always_on_top: boolean;
+}
+
+export interface MonitorInfo {
+    name: string | null;
+    area: { x: number, y: number, width: number, height: number };
+    is_primary: boolean;
+}
+
+export const getWindowLayout = (): Promise<WindowLayout> =>
+    invoke('get_window_layout');
+
+export const setWindowLayout = (layout: WindowLayout): Promise<void> =>
+    invoke('set_window_layout', {layout});
+
+export const listMonitors = (): Promise<MonitorInfo[]> =>
+    invoke('list_monitors');
+
+export interface Settings {
+    auto_copy_comment: boolean;
+    close_previous_profile_window: boolean;
+    hide_window_on_pause: boolean;
+    shortcuts: ShortcutBindings;
+    layout: WindowLayout;
+}
+
+export const getSettings = (): Promise<Settings> =>
+    invoke('get_settings');
+
+export const updateSettings = (settings: Settings): Promise<void> =>
+    invoke('update_settings', {settings});
+
+export interface LogEntry {
+    timestamp: string;
+    level: string;
+    module: string;
+    message: string;
+}
+
+export const getRecentLogs = (limit?: number, level?: string, module?: string): Promise<LogEntry[]> =>
+    invoke('get_recent_logs', {limit, level, module});
+
+export const copyDiagnostics = (): Promise<unknown> =>
+    invoke('copy_diagnostics');
+
+export const updateProfileDetails = (
+    profileId: string,
+    profileName: string,
+    notes: string | null,
+    color: string | null,
+    tags: string[],
+): Promise<Profile> =>
+    invoke('update_profile_details', {profileId, profileName, notes, color, tags});
+
+export const reorderProfiles = (profileIds: string[]): Promise<void> =>
+    invoke('reorder_profiles', {profileIds});
+
+export const cloneProfile = (
+    sourceProfileId: string,
+    profileName: string,
+    includeSessionData: boolean,
+    copyBriefcases: boolean,
+): Promise<Profile> =>
+    invoke('clone_profile', {sourceProfileId, profileName, includeSessionData, copyBriefcases});
+
+export const setBriefcaseAvailability = (
+    briefcaseId: string,
+    isActive: boolean,
+    pausedUntil: string | null,
+): Promise<void> =>
+    invoke('set_briefcase_availability', {briefcaseId, isActive, pausedUntil});
+
+export const loadGroups = (): Promise<BriefCaseGroup[]> =>
+    invoke('load_groups');
+
+export const createGroup = (name: string, briefcaseIds: string[]): Promise<BriefCaseGroup> =>
+    invoke('create_group', {name, briefcaseIds});
+
+export const updateGroup = (groupId: string, name: string, briefcaseIds: string[]): Promise<BriefCaseGroup> =>
+    invoke('update_group', {groupId, name, briefcaseIds});
+
+export const deleteGroup = (groupId: string): Promise<void> =>
+    invoke('delete_group', {groupId});
+
+export interface ExcludedBriefCase {
+    briefcase: BriefCase;
+    reason: 'Inactive' | 'Paused';
+}
+
+export interface PlanPreview {
+    total_steps: number;
+    profiles: { profile_id: string; profile_name: string | null; step_count: number }[];
+    excluded: ExcludedBriefCase[];
+    tasks_without_accounts: number[];
+    task_groups: { task_index: number; group_id: string; group_name: string | null; briefcase_ids: string[] }[];
+    unverified_logins: BriefCase[];
+}
+
+export const previewPlan = (tasks: Task[]): Promise<PlanPreview> =>
+    invoke('preview_plan', {tasksJson: JSON.stringify(tasks)});
+
+export interface LoginCheckProgress {
+    position: number;
+    total: number;
+    stop: {
+        profile_id: string;
+        social_media: SocialMedia;
+        home_url: string;
+        briefcase_ids: string[];
+    };
+    should_change_profile: boolean;
+}
+
+export const startLoginCheck = (): Promise<LoginCheckProgress> =>
+    invoke('start_login_check');
+
+export const getLoginCheck = (): Promise<LoginCheckProgress | null> =>
+    invoke('get_login_check');
+
+export const nextLoginCheck = (): Promise<LoginCheckProgress | null> =>
+    invoke('next_login_check');
+
+export const endLoginCheck = (): Promise<void> =>
+    invoke('end_login_check');
+
+export const setLoginStatus = (briefcaseId: string, status: LoginStatus): Promise<void> =>
+    invoke('set_login_status', {briefcaseId, status});
+
+export interface ProfileDiskUsage {
+    profile_id: string;
+    size_bytes: number;
+    cache_bytes: number;
+}
+
+export interface OrphanProfileDir {
+    profile_id: string;
+    path: string;
+    size_bytes: number;
+}
+
+export const getProfileDiskUsage = (): Promise<ProfileDiskUsage[]> =>
+    invoke('get_profile_disk_usage');
+
+export const clearProfileCache = (profileId: string): Promise<number> =>
+    invoke('clear_profile_cache', {profileId});
+
+export const wipeProfileData = (profileId: string): Promise<number> =>
+    invoke('wipe_profile_data', {profileId});
+
+export const findOrphanProfileDirs = (): Promise<OrphanProfileDir[]> =>
+    invoke('find_orphan_profile_dirs');
+
+export const deleteOrphanProfileDirs = (): Promise<number> =>
+    invoke('delete_orphan_profile_dirs');
+
+export type AuditEntity = 'Profile' | 'BriefCase' | 'Group' | 'Task' | 'Settings' | 'Backup';
+
+export interface AuditEntry {
+    id: string;
+    timestamp: string;
+    actor: string;
+    command: string;
+    entity: AuditEntity;
+    entity_id: string | null;
+    action: 'Create' | 'Update' | 'Delete';
+    before: unknown | null;
+    after: unknown | null;
+    reverts: string | null;
+}
+
+export const getAuditLog = (entity?: AuditEntity, entityId?: string, limit?: number): Promise<AuditEntry[]> =>
+    invoke('get_audit_log', {entity, entityId, limit});
+
+export const revertAuditEntry = (entryId: string): Promise<void> =>
+    invoke('revert_audit_entry', {entryId});
+
+export interface BackupManifest {
+    format_version: number;
+    app_version: string;
+    created_at: string;
+    includes_profile_data: boolean;
+    files: { path: string; size_bytes: number; sha256: string }[];
+    encryption: {
+        salt: string;
+        memory_kib: number;
+        iterations: number;
+        parallelism: number;
+        verifier: string;
+        encrypt_data: boolean;
+    } | null;
+}
+
+export type ImportMode = 'Merge' | 'Replace';
+
+export const exportBackup = (
+    path: string,
+    includeProfileData: boolean,
+    passphrase: string | null = null
+): Promise<BackupManifest> =>
+    invoke('export_backup', {path, includeProfileData, passphrase});
+
+export const importBackup = (
+    path: string,
+    mode: ImportMode,
+    passphrase: string | null = null
+): Promise<BackupManifest> =>
+    invoke('import_backup', {path, mode, passphrase});
+
+export interface SecurityStatus {
+    passphrase_set: boolean;
+    encrypted: boolean;
+    locked: boolean;
+}
+
+export const getSecurityStatus = (): Promise<SecurityStatus> =>
+    invoke('get_security_status');
+
+export const setPassphrase = (
+    current: string | null,
+    passphrase: string,
+    encryptData: boolean
+): Promise<SecurityStatus> =>
+    invoke('set_passphrase', {current, passphrase, encryptData});
+
+export const removePassphrase = (current: string): Promise<SecurityStatus> =>
+    invoke('remove_passphrase', {current});
+
+export const unlockApp = (passphrase: string): Promise<SecurityStatus> =>
+    invoke('unlock_app', {passphrase});
+
+export const lockApp = (): Promise<SecurityStatus> =>
+    invoke('lock_app');
+
 export enum WindowSize {
     Partial = "Partial",
     Full = "Full"
@@ -13,10 +13,25 @@ import {
     Text,
     VStack
 } from '@chakra-ui/react';
-import {FaArrowRight} from 'react-icons/fa';
+import {FaArrowLeft, FaArrowRight, FaPause, FaPlay} from 'react-icons/fa';
 
-import {closeWorkspace, getPanelData, nextWorkspaceItem, copyToClipboard, setCommentIndex} from '../../api/tauriCommands';
-import {BriefCase, Profile, Task} from '@/types';
+import {listen} from '@tauri-apps/api/event';
+
+import {
+    closeWorkspace,
+    getPanelData,
+    getSessionTime,
+    gotoTask,
+    listPlatforms,
+    nextWorkspaceItem,
+    pauseAutomation,
+    prevWorkspaceItem,
+    restoreProfileWindow,
+    resumeAutomation,
+    SessionTime,
+    setCommentIndex
+} from '../../api/tauriCommands';
+import {BriefCase, Platform, Profile, Task} from '@/types';
 
 export const Panel = () => {
     const [currentTask, setCurrentTask] = useState<Task | null>(null);
@@ -27,18 +42,41 @@ export const Panel = () => {
     const [isComplete, setIsComplete] = useState(false);
     const [isLoading, setIsLoading] = useState(false);
     const [currentComment, setCurrentComment] = useState<string | null>(null);
+    const [currentCommentIndex, setCurrentCommentIndex] = useState<number | null>(null);
     const [copiedText, setCopiedText] = useState<string | null>(null);
+    const [windowLost, setWindowLost] = useState(false);
+    const [isPaused, setIsPaused] = useState(false);
+    const [currentTaskIndex, setCurrentTaskIndex] = useState<number | null>(null);
+    const [totalTasks, setTotalTasks] = useState(0);
 
     const [taskProgress, setTaskProgress] = useState<[number, number] | null>(null);
+    const [platforms, setPlatforms] = useState<Platform[]>([]);
 
     useEffect(() => {
-        if (currentComment && copiedText !== currentComment) {
-            copyToClipboard(currentComment).catch(err => {
-                console.error('Failed to copy comment to clipboard:', err);
-            });
-            setCopiedText(currentComment);
-        }
-    }, [currentComment]);
+        listPlatforms().then(setPlatforms).catch(() => undefined);
+    }, []);
+
+    // The backend copies the comment on every step and tells us what it copied
+    useEffect(() => {
+        const unlisten = listen<{ task_index: number, comment: string }>('comment-copied', (event) => {
+            setCopiedText(event.payload.comment);
+        });
+        return () => {
+            unlisten.then(fn => fn());
+        };
+    }, []);
+
+    // Pausing can also come from the global shortcut, so follow the backend
+    useEffect(() => {
+        getSessionTime().then(time => setIsPaused(Boolean(time?.paused))).catch(() => undefined);
+
+        const unlisteners = ['automation-paused', 'automation-resumed'].map(event =>
+            listen<SessionTime>(event, (e) => setIsPaused(e.payload.paused))
+        );
+        return () => {
+            unlisteners.forEach(unlisten => unlisten.then(fn => fn()));
+        };
+    }, []);
 
     useEffect(() => {
         const loadData = async () => {
@@ -57,12 +95,16 @@ export const Panel = () => {
                     setIsComplete(overallProgress[0] >= overallProgress[1] && overallProgress[1] > 0);
 
                     const currentIdx = data.current_task_index;
+                    setCurrentTaskIndex(currentIdx ?? null);
+                    setTotalTasks(data.total_tasks || 0);
                     const taskProgressList = data.task_progress || [];
                     const currentTaskProgress = currentIdx !== null ? taskProgressList[currentIdx] : null;
                     setTaskProgress(currentTaskProgress ? [currentTaskProgress[1], currentTaskProgress[2]] : null);
 
                     const newComment = data.current_comment || null;
                     setCurrentComment(newComment);
+                    setCurrentCommentIndex(data.current_comment_index ?? null);
+                    setWindowLost(Boolean(data.window_lost));
 
                     const currentTaskId = data.current_task?.task_id;
                     const currentProfileId = data.current_profile?.profile_id;
@@ -70,11 +112,6 @@ export const Panel = () => {
                     if (previousTaskId !== currentTaskId || previousProfileId !== currentProfileId) {
                         setIsLoading(false);
                     }
-
-                    const newTask = data.current_task;
-                    if (newTask && newComment && newTask.comments[newTask.comment_index] === newComment) {
-                        setCopiedText(newComment);
-                    }
                 }
             } catch (error) {
                 console.error('Failed to load panel data:', error);
@@ -83,8 +120,18 @@ export const Panel = () => {
 
         loadData();
 
-        const interval = setInterval(loadData, 1000);
-        return () => clearInterval(interval);
+        // The backend emits these whenever the execution plan moves
+        const unlisteners = [
+            'step-changed',
+            'profile-switched',
+            'progress-updated',
+            'session-completed',
+            'window-lost',
+        ].map(event => listen(event, loadData));
+
+        return () => {
+            unlisteners.forEach(unlisten => unlisten.then(fn => fn()));
+        };
     }, [currentTask, currentProfile]);
 
     const currentTaskBriefcaseCount = taskProgress ? taskProgress[1] : (currentTask?.related_brief_cases?.length || 1);
@@ -105,7 +152,10 @@ export const Panel = () => {
         setIsLoading(true);
         const timeout = setTimeout(() => setIsLoading(false), 3000);
         try {
-            await nextWorkspaceItem();
+            const result = await nextWorkspaceItem();
+            if (result.warning) {
+                console.warn(result.warning);
+            }
         } catch (error) {
             console.error('Failed to go to next:', error);
             setIsLoading(false);
@@ -113,6 +163,55 @@ export const Panel = () => {
         }
     }, []);
 
+    const handlePrevious = useCallback(async () => {
+        setIsLoading(true);
+        const timeout = setTimeout(() => setIsLoading(false), 3000);
+        try {
+            const result = await prevWorkspaceItem();
+            if (result.warning) {
+                console.warn(result.warning);
+            }
+        } catch (error) {
+            console.error('Failed to go to previous:', error);
+            setIsLoading(false);
+            clearTimeout(timeout);
+        }
+    }, []);
+
+    // Jumps to a task, to the given account's step or to its next unvisited one
+    const handleGoto = useCallback(async (taskIndex: number, briefcaseId: string | null) => {
+        setIsLoading(true);
+        const timeout = setTimeout(() => setIsLoading(false), 3000);
+        try {
+            const result = await gotoTask(taskIndex, briefcaseId);
+            if (result.warning) {
+                console.warn(result.warning);
+            }
+        } catch (error) {
+            console.error('Failed to jump:', error);
+            setIsLoading(false);
+            clearTimeout(timeout);
+        }
+    }, []);
+
+    const handleTogglePause = useCallback(async () => {
+        try {
+            const time = isPaused ? await resumeAutomation() : await pauseAutomation();
+            setIsPaused(time.paused);
+        } catch (error) {
+            console.error('Failed to pause or resume:', error);
+        }
+    }, [isPaused]);
+
+    const handleRestoreWindow = useCallback(async () => {
+        try {
+            await restoreProfileWindow();
+            setWindowLost(false);
+        } catch (error) {
+            console.error('Failed to reopen profile window:', error);
+        }
+    }, []);
+
     const handleCommentSelect = useCallback(async (commentIndex: number) => {
         if (currentTask) {
             try {
@@ -125,13 +224,14 @@ export const Panel = () => {
 
     useEffect(() => {
         const handleKeyDown = (e: KeyboardEvent) => {
-            if (e.key === "ArrowRight" && !isLoading) handleNext();
+            if (e.key === "ArrowRight" && !isLoading && !isPaused) handleNext();
+            if (e.key === "ArrowLeft" && !isLoading && !isPaused) handlePrevious();
             if (e.key === "Escape") handleQuit();
         };
 
         window.addEventListener('keydown', handleKeyDown);
         return () => window.removeEventListener('keydown', handleKeyDown);
-    }, [handleQuit, handleNext, isLoading]);
+    }, [handleQuit, handleNext, handlePrevious, isLoading, isPaused]);
 
     if (!currentTask) {
         return (
@@ -158,7 +258,8 @@ export const Panel = () => {
                             </Heading>
                             <HStack justify="space-between" w="100%">
                                 <Text fontSize="sm" color="whiteAlpha.700">
-                                    Platform: {currentTask?.social_media}
+                                    Platform: {platforms.find(p => p.id === currentTask?.social_media)?.name
+                                    ?? currentTask?.social_media}
                                 </Text>
                                 <Text fontSize="sm" color="whiteAlpha.700">
                                     Briefcases: {visitedBriefcases}/{totalBriefcases}
@@ -171,6 +272,17 @@ export const Panel = () => {
                     </CardBody>
                 </CardRoot>
 
+                {windowLost && (
+                    <Box bg="orange.900" p={3} borderRadius="md">
+                        <HStack justify="space-between">
+                            <Text fontSize="sm" color="orange.200">The profile window was closed</Text>
+                            <Button size="xs" variant="outline" colorPalette="orange" onClick={handleRestoreWindow}>
+                                Reopen
+                            </Button>
+                        </HStack>
+                    </Box>
+                )}
+
                 {/* Progress Section */}
                 <CardRoot bg="whiteAlpha.100" backdropFilter="blur(10px)" border="1px solid"
                           borderColor="whiteAlpha.200">
@@ -214,6 +326,43 @@ export const Panel = () => {
                     </CardBody>
                 </CardRoot>
 
+                {/* Jump to a task or account */}
+                {currentTaskIndex !== null && (
+                    <CardRoot bg="whiteAlpha.100" backdropFilter="blur(10px)" border="1px solid"
+                              borderColor="whiteAlpha.200">
+                        <CardBody>
+                            <VStack align="stretch" gap={3}>
+                                <HStack justify="space-between">
+                                    <Button size="xs" variant="ghost"
+                                            onClick={() => handleGoto(currentTaskIndex - 1, null)}
+                                            disabled={isLoading || isPaused || currentTaskIndex === 0}>
+                                        <FaArrowLeft/>
+                                    </Button>
+                                    <Heading size="sm">Task {currentTaskIndex + 1}/{totalTasks}</Heading>
+                                    <Button size="xs" variant="ghost"
+                                            onClick={() => handleGoto(currentTaskIndex + 1, null)}
+                                            disabled={isLoading || isPaused || currentTaskIndex + 1 >= totalTasks}>
+                                        <FaArrowRight/>
+                                    </Button>
+                                </HStack>
+                                <HStack wrap="wrap" gap={2}>
+                                    {currentTask.related_brief_cases.map(briefcase => (
+                                        <Button
+                                            key={briefcase.id}
+                                            size="xs"
+                                            variant="outline"
+                                            onClick={() => handleGoto(currentTaskIndex, briefcase.id)}
+                                            disabled={isLoading || isPaused}
+                                        >
+                                            {briefcase.user_name}
+                                        </Button>
+                                    ))}
+                                </HStack>
+                            </VStack>
+                        </CardBody>
+                    </CardRoot>
+                )}
+
                 {/* Current Comment */}
                 {currentTask && currentTask.comments.length > 0 && (
                     <CardRoot bg="whiteAlpha.100" backdropFilter="blur(10px)" border="1px solid"
@@ -234,19 +383,19 @@ export const Panel = () => {
                                             borderRadius="md"
                                             cursor="pointer"
                                             transition="all 0.2s"
-                                            bg={index === currentTask.comment_index ? "blue.900" : "whiteAlpha.50"}
+                                            bg={index === currentCommentIndex ? "blue.900" : "whiteAlpha.50"}
                                             border="1px solid"
-                                            borderColor={index === currentTask.comment_index ? "blue.500" : "transparent"}
+                                            borderColor={index === currentCommentIndex ? "blue.500" : "transparent"}
                                             _hover={{
-                                                bg: index === currentTask.comment_index ? "blue.800" : "whiteAlpha.100",
-                                                borderColor: index === currentTask.comment_index ? "blue.400" : "blue.500"
+                                                bg: index === currentCommentIndex ? "blue.800" : "whiteAlpha.100",
+                                                borderColor: index === currentCommentIndex ? "blue.400" : "blue.500"
                                             }}
                                             onClick={() => handleCommentSelect(index)}
                                             w="100%"
                                         >
                                             <Text
                                                 fontSize="sm"
-                                                color={index === currentTask.comment_index ? "white" : "whiteAlpha.900"}
+                                                color={index === currentCommentIndex ? "white" : "whiteAlpha.900"}
                                             >
                                                 {index + 1}. {comment}
                                             </Text>
@@ -265,20 +414,20 @@ export const Panel = () => {
                         <VStack gap={3}>
                                 {/* Navigation */}
                                 <HStack w="100%" gap={3}>
-                                    {/* Previous button disabled - backend functionality not implemented */}
-                                    {/* <Button
+                                    <Button
                                         flex={1}
                                         variant="ghost"
-                                        onClick={() => prevWorkspaceItem().catch(console.error)}
+                                        onClick={handlePrevious}
+                                        disabled={isLoading || isPaused}
                                     >
                                         <FaArrowLeft style={{marginRight: '8px'}}/>
                                         Previous
-                                    </Button> */}
+                                    </Button>
                                     <Button
                                         flex={1}
                                         variant="ghost"
                                         onClick={handleNext}
-                                        disabled={isLoading}
+                                        disabled={isLoading || isPaused}
                                     >
                                         {isLoading ? (
                                             <Spinner size="sm" />
@@ -290,6 +439,21 @@ export const Panel = () => {
                                     </Button>
                                 </HStack>
 
+                            {/* Pause */}
+                            <Button
+                                w="full"
+                                variant="outline"
+                                colorPalette={isPaused ? "green" : "yellow"}
+                                onClick={handleTogglePause}
+                                disabled={isComplete}
+                            >
+                                {isPaused ? (
+                                    <><FaPlay style={{marginRight: '8px'}}/>Resume</>
+                                ) : (
+                                    <><FaPause style={{marginRight: '8px'}}/>Pause</>
+                                )}
+                            </Button>
+
                             {/* Quit */}
                             <Button
                                 w="full"
@@ -14,11 +14,11 @@ import {
 export const AddBriefcaseModal = () => {
     const {
         isAddBriefcaseOpen, setAddBriefcaseOpen, addBriefcase,
-        activeProfileIdForBriefcase, activePlatformForBriefcase, profiles
+        activeProfileIdForBriefcase, activePlatformForBriefcase, profiles, platforms
     } = useStore();
 
     const [username, setUsername] = useState('');
-    const [selectedPlatform, setSelectedPlatform] = useState<SocialMedia | string>(activePlatformForBriefcase || SocialMedia.Youtube);
+    const [selectedPlatform, setSelectedPlatform] = useState<SocialMedia>(activePlatformForBriefcase || platforms[0]?.id || '');
 
     // Update local state when store state changes
     useEffect(() => {
@@ -27,13 +27,13 @@ export const AddBriefcaseModal = () => {
 
     const handleSave = () => {
         if (activeProfileIdForBriefcase) {
-            addBriefcase(activeProfileIdForBriefcase, selectedPlatform as SocialMedia, username);
+            addBriefcase(activeProfileIdForBriefcase, selectedPlatform, username);
             setUsername('');
         }
     };
 
     const platformCollection = createListCollection({
-        items: Object.values(SocialMedia).map(p => ({label: p, value: p}))
+        items: platforms.map(p => ({label: p.name, value: p.id}))
     });
 
     return (
@@ -1,28 +1,19 @@
 import {Box, Button, Flex, HStack, IconButton, Menu, Text} from '@chakra-ui/react';
 import {FaCheckCircle, FaEllipsisV, FaSignInAlt, FaTrash} from 'react-icons/fa';
 import {useStore} from '@/store.ts';
-import {BriefCase, SocialMedia} from '@/types.ts';
+import {BriefCase} from '@/types.ts';
 import {getPlatformConfig} from '@/utils/platformConfig.ts';
 import {openLoginWindow} from "@/api/tauriCommands.ts";
 
 export const BriefcaseItem = ({briefcase}: { briefcase: BriefCase }) => {
-    const {removeBriefcase, toggleBriefcaseActive, profiles} = useStore();
+    const {removeBriefcase, toggleBriefcaseActive, profiles, getPlatform} = useStore();
     const config = getPlatformConfig(briefcase.social_media);
+    const platform = getPlatform(briefcase.social_media);
 
     const handleLoginClick = async () => {
-        let link: String = "";
-
-        if (briefcase.social_media == SocialMedia.Facebook) {
-            link = "https://facebook.com/";
-        } else if (briefcase.social_media == SocialMedia.Instagram) {
-            link = "https://instagram.com/";
-        } else if (briefcase.social_media == SocialMedia.Youtube) {
-            link = "https://www.youtube.com/";
-        } else if (briefcase.social_media == SocialMedia.X) {
-            link = "https://www.x.com/";
-        } else {
-            link = "https://example.com/"
-        }
+        // Accounts on platforms removed from platforms.json have no login page
+        if (!platform) return;
+        const link = platform.home_url;
 
         profiles.map(async (profile) => {
             if (profile.profile_id === briefcase.profile_id) {
@@ -63,7 +54,12 @@ export const BriefcaseItem = ({briefcase}: { briefcase: BriefCase }) => {
                     <config.icon size={20}/>
                 </Flex>
                 <Box>
-                    <Text fontSize="xs" color="whiteAlpha.600">{briefcase.social_media}</Text>
+                    <Text fontSize="xs" color="whiteAlpha.600">{platform?.name ?? briefcase.social_media}</Text>
+                    {briefcase.last_verified?.status !== 'LoggedIn' && (
+                        <Text fontSize="2xs" color={briefcase.last_verified ? 'red.400' : 'whiteAlpha.500'}>
+                            {briefcase.last_verified ? 'Login expired' : 'Login not checked'}
+                        </Text>
+                    )}
                 </Box>
                 <Button
                     size="sm"
@@ -0,0 +1,100 @@
+import {useEffect, useState} from 'react';
+import {Button, Flex, HStack, Text, VStack} from '@chakra-ui/react';
+import {FaCheck, FaTimes, FaUserCheck} from 'react-icons/fa';
+import {useStore} from '@/store.ts';
+import {LoginStatus} from '@/types.ts';
+import {
+    endLoginCheck,
+    getLoginCheck,
+    LoginCheckProgress,
+    nextLoginCheck,
+    setLoginStatus,
+    startLoginCheck
+} from '@/api/tauriCommands.ts';
+
+/**
+ * Walks the operator through every profile window at each platform's home page
+ * to confirm which accounts are still logged in
+ */
+export const LoginCheckBar = () => {
+    const {profiles, briefcases, getPlatform, setError} = useStore();
+    const [progress, setProgress] = useState<LoginCheckProgress | null>(null);
+
+    // Pick up a check that is still running, e.g. after a reload
+    useEffect(() => {
+        getLoginCheck().then(setProgress).catch(() => undefined);
+    }, []);
+
+    const run = async (action: () => Promise<LoginCheckProgress | null>) => {
+        try {
+            setProgress(await action());
+        } catch (error) {
+            setError(`Login check failed: ${error}`);
+        }
+    };
+
+    const confirm = async (briefcaseId: string, status: LoginStatus) => {
+        try {
+            await setLoginStatus(briefcaseId, status);
+        } catch (error) {
+            setError(`Failed to record login: ${error}`);
+        }
+    };
+
+    if (!progress) {
+        return (
+            <Button size="sm" variant="ghost" colorPalette="blue" mb={4} onClick={() => run(startLoginCheck)}>
+                <FaUserCheck style={{marginRight: '6px'}}/> Check logins
+            </Button>
+        );
+    }
+
+    const {stop} = progress;
+    const profile = profiles.find(p => p.profile_id === stop.profile_id);
+    const accounts = briefcases.filter(b => stop.briefcase_ids.includes(b.id));
+    const isLast = progress.position + 1 >= progress.total;
+
+    return (
+        <VStack align="stretch" gap={3} p={4} mb={4} borderRadius="xl" bg="whiteAlpha.100"
+                border="1px solid" borderColor="whiteAlpha.200">
+            <Flex justify="space-between" align="center">
+                <Text fontSize="sm" fontWeight="bold">
+                    {getPlatform(stop.social_media)?.name ?? stop.social_media} on {profile?.profile_name ?? 'Unknown Profile'}
+                </Text>
+                <Text fontSize="xs" color="whiteAlpha.600">
+                    {progress.position + 1} / {progress.total}
+                </Text>
+            </Flex>
+
+            {accounts.map(account => (
+                <Flex key={account.id} justify="space-between" align="center">
+                    <Text fontSize="sm">{account.user_name}</Text>
+                    <HStack gap={2}>
+                        <Button size="xs" colorPalette="green"
+                                variant={account.last_verified?.status === 'LoggedIn' ? 'solid' : 'outline'}
+                                onClick={() => confirm(account.id, 'LoggedIn')}>
+                            <FaCheck/> Logged in
+                        </Button>
+                        <Button size="xs" colorPalette="red"
+                                variant={account.last_verified?.status === 'Expired' ? 'solid' : 'outline'}
+                                onClick={() => confirm(account.id, 'Expired')}>
+                            <FaTimes/> Expired
+                        </Button>
+                    </HStack>
+                </Flex>
+            ))}
+
+            <HStack justify="flex-end" gap={2}>
+                <Button size="sm" variant="ghost" onClick={() => run(async () => {
+                    await endLoginCheck();
+                    return null;
+                })}>
+                    Stop
+                </Button>
+                <Button size="sm" colorPalette="blue" onClick={() => run(nextLoginCheck)}>
+                    {isLast ? 'Finish' : 'Next'}
+                </Button>
+            </HStack>
+        </VStack>
+    );
+};
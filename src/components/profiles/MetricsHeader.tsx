@@ -5,7 +5,7 @@ import {SocialMedia} from '@/types.ts';
 import {getPlatformConfig} from '@/utils/platformConfig.ts'; // Extract the switch case to a util file
 
 export const MetricsHeader = () => {
-    const {getBriefcaseCount, profiles, briefcases, setAddProfileOpen, setAddBriefcaseOpen} = useStore();
+    const {getBriefcaseCount, profiles, briefcases, platforms, setAddProfileOpen, setAddBriefcaseOpen} = useStore();
 
     const handlePlatformCheck = (platform: SocialMedia) => {
         let targetProfileIndex = -1;
@@ -34,15 +34,15 @@ export const MetricsHeader = () => {
 
     return (
         <Flex gap={4} mb={6} overflowX="auto" pb={2} css={{'&::-webkit-scrollbar': {display: 'none'}}}>
-            {Object.values(SocialMedia).map(platform => {
-                const config = getPlatformConfig(platform);
+            {platforms.map(({id, name}) => {
+                const config = getPlatformConfig(id);
                 return (
-                    <HStack key={platform} bg="whiteAlpha.100" p={2} borderRadius="lg" gap={3} border="1px solid"
+                    <HStack key={id} title={name} bg="whiteAlpha.100" p={2} borderRadius="lg" gap={3} border="1px solid"
                             borderColor="whiteAlpha.100" minW="140px">
                         <config.icon color={config.color}/>
-                        <Text fontSize="sm" fontWeight="bold">{getBriefcaseCount(platform)}</Text>
-                        <IconButton aria-label="Add" size="xs" borderRadius="full"
-                                    onClick={() => handlePlatformCheck(platform)}>
+                        <Text fontSize="sm" fontWeight="bold">{getBriefcaseCount(id)}</Text>
+                        <IconButton aria-label={`Add ${name} briefcase`} size="xs" borderRadius="full"
+                                    onClick={() => handlePlatformCheck(id)}>
                             <FaPlus/>
                         </IconButton>
                     </HStack>
@@ -1,4 +1,4 @@
-import {Accordion, Box, Button, Card, Flex, HStack, IconButton, Menu, Text} from '@chakra-ui/react';
+import {Accordion, Badge, Box, Button, Card, Flex, HStack, IconButton, Menu, Text} from '@chakra-ui/react';
 import {FaEllipsisV, FaPlus, FaTrash} from 'react-icons/fa';
 import {useStore} from '../../store';
 import {Profile,} from '../../types';
@@ -18,6 +18,8 @@ export const ProfileCard = ({profile}: { profile: Profile }) => {
             h="full" // Ensure card takes full height of grid cell
             display="flex"
             flexDirection="column"
+            borderTopWidth={profile.color ? "3px" : undefined}
+            borderTopColor={profile.color ?? undefined}
         >
             <Accordion.Root collapsible defaultValue={["info"]}>
                 <Accordion.Item value="info" border="none" h="full" display="flex" flexDirection="column">
@@ -27,6 +29,13 @@ export const ProfileCard = ({profile}: { profile: Profile }) => {
                         <Box flex={1}>
                             <Text fontWeight="bold" fontSize="md">{profile.profile_name}</Text>
                             <Text fontSize="xs" color="gray.400">{profileBriefcases.length} Briefcases</Text>
+                            {profile.tags.length > 0 && (
+                                <HStack gap={1} mt={1} flexWrap="wrap">
+                                    {profile.tags.map(tag => (
+                                        <Badge key={tag} size="sm" variant="subtle">{tag}</Badge>
+                                    ))}
+                                </HStack>
+                            )}
                         </Box>
 
                         <HStack>
@@ -61,6 +70,11 @@ export const ProfileCard = ({profile}: { profile: Profile }) => {
                             display="flex" 
                             flexDirection="column"
                         >
+                            {profile.notes && (
+                                <Text fontSize="xs" color="gray.400" px={2} pb={3} whiteSpace="pre-wrap">
+                                    {profile.notes}
+                                </Text>
+                            )}
                             <Box flex={1}>
                                 {profileBriefcases.length > 0 ? (
                                     profileBriefcases.map(b => <BriefcaseItem key={b.id} briefcase={b}/>)
@@ -4,8 +4,8 @@ import {FaLink, FaParagraph, FaPlay, FaPlus, FaTrash} from 'react-icons/fa';
 
 // Internal Imports
 import {useStore} from '@/store.ts';
-import {SocialMedia} from '@/types.ts';
 import {getPlatformConfig} from '@/utils/platformConfig.ts';
+import {detectPlatform} from '@/api/tauriCommands.ts';
 import {invoke} from "@tauri-apps/api/core";
 
 export const TaskManager = () => {
@@ -35,34 +35,23 @@ export const TaskManager = () => {
     /**
      * Handles adding a task to the scheduler
      */
-    const handleAddToScheduler = () => {
+    const handleAddToScheduler = async () => {
         // 1. Basic Validation
         if (!link.trim() || !isValidLink) {
             setError("Please provide a valid URL starting with https://");
             return;
         }
 
-        // 2. Platform Detection
-        let platform: SocialMedia | null = null;
-        const lowerLink = link.toLowerCase();
-
-        if (lowerLink.includes('youtube.com') || lowerLink.includes('youtu.be')) {
-            platform = SocialMedia.Youtube;
-        } else if (lowerLink.includes('facebook.com')) {
-            platform = SocialMedia.Facebook;
-        } else if (lowerLink.includes('instagram.com')) {
-            platform = SocialMedia.Instagram;
-        } else if (lowerLink.includes('twitter.com') || lowerLink.includes('x.com')) {
-            platform = SocialMedia.X;
-        }
+        // 2. Platform Detection by the hosts in the platform registry
+        const platform = await detectPlatform(link.trim()).catch(() => null);
 
         if (!platform) {
-            setError("Supported platform (YouTube, FB, IG, X) not detected in link.");
+            setError("No known platform detected in link. Platforms can be added in platforms.json.");
             return;
         }
 
         // 3. Add to Store
-        addTask(link, comment, platform);
+        addTask(link, comment, platform.id);
 
         // 4. Reset Inputs
         setLink('');
@@ -1,9 +1,10 @@
 import {create} from 'zustand';
 import {v4 as uuidv4} from 'uuid';
-import {BriefCase, Profile, SocialMedia, Task} from './types';
+import {BriefCase, Platform, Profile, SocialMedia, Task} from './types';
 import {listen} from '@tauri-apps/api/event';
 import {
     createProfile,
+    listPlatforms,
     loadBriefcases,
     loadProfiles,
     saveAllData,
@@ -15,6 +16,7 @@ interface AppState {
     profiles: Profile[];
     briefcases: BriefCase[];
     tasks: Task[];
+    platforms: Platform[];
 
     // UI State for Modals
     isAddProfileOpen: boolean;
@@ -43,6 +45,7 @@ interface AppState {
     removeTask: (id: string) => void;
 
     getBriefcaseCount: (platform: SocialMedia) => number;
+    getPlatform: (id: SocialMedia) => Platform | undefined;
 
     // Backend integration actions
     loadData: () => Promise<void>;
@@ -55,6 +58,7 @@ export const useStore = create<AppState>((set, get) => ({
     profiles: [],
     briefcases: [],
     tasks: [],
+    platforms: [],
 
     isAddProfileOpen: false,
     isAddBriefcaseOpen: false,
@@ -66,14 +70,16 @@ export const useStore = create<AppState>((set, get) => ({
     // Load data from backend on initialization
     loadData: async () => {
         try {
-            const [profilesData, briefcasesData] = await Promise.all([
+            const [profilesData, briefcasesData, platformsData] = await Promise.all([
                 loadProfiles(),
-                loadBriefcases()
+                loadBriefcases(),
+                listPlatforms()
             ]);
 
             set({
                 profiles: profilesData,
-                briefcases: briefcasesData
+                briefcases: briefcasesData,
+                platforms: platformsData
             });
         } catch (error) {
             console.error('Failed to load data:', error);
@@ -161,7 +167,9 @@ export const useStore = create<AppState>((set, get) => ({
             social_media,
             profile_id: profileId,
             user_name: username,
-            is_active: false
+            is_active: true,
+            paused_until: null,
+            last_verified: null
         };
 
         set((state) => ({
@@ -223,6 +231,10 @@ export const useStore = create<AppState>((set, get) => ({
 
     getBriefcaseCount: (platform) => {
         return get().briefcases.filter(b => b.social_media === platform).length;
+    },
+
+    getPlatform: (id) => {
+        return get().platforms.find(p => p.id === id);
     }
 }));
 
@@ -252,5 +264,15 @@ export const setupEventListeners = () => {
         }
     });
 
+    // Listen for platform changes, e.g. from a backup import
+    listen('platforms-changed', async () => {
+        try {
+            const platformsData = await listPlatforms();
+            useStore.setState({platforms: platformsData});
+        } catch (error) {
+            console.error('Failed to reload platforms:', error);
+        }
+    });
+
     eventListenersSetup = true;
 };
\ No newline at end of file
@@ -1,9 +1,19 @@
 // types.ts
-export enum SocialMedia {
-    Youtube = "Youtube",
-    Facebook = "Facebook",
-    Instagram = "Instagram",
-    X = "X",
+// ID of a platform from the platform registry, e.g. "youtube"
+export type SocialMedia = string;
+
+export interface LinkRules {
+    host: string | null;
+    aliases: string[];
+    keep_query: string[] | null;
+}
+
+export interface Platform {
+    id: SocialMedia;
+    name: string;
+    hosts: string[];
+    home_url: string;
+    link_rules: LinkRules;
 }
 
 export interface BriefCase {
@@ -12,11 +22,30 @@ export interface BriefCase {
     profile_id: string; // Uuid
     user_name: string;
     is_active: boolean;
+    paused_until: string | null; // RFC 3339
+    last_verified: LoginVerification | null;
+}
+
+export type LoginStatus = 'LoggedIn' | 'Expired';
+
+export interface LoginVerification {
+    status: LoginStatus;
+    verified_at: string; // RFC 3339
 }
 
 export interface Profile {
     profile_id: string; // Uuid
     profile_name: string;
+    notes: string | null;
+    color: string | null; // #rrggbb
+    tags: string[];
+    sort_order: number;
+}
+
+export interface BriefCaseGroup {
+    id: string; // Uuid
+    name: string;
+    briefcase_ids: string[]; // Uuid
 }
 
 export interface Task {
@@ -28,4 +57,5 @@ export interface Task {
     social_media: SocialMedia;
     related_brief_cases: BriefCase[];
     comment_index: number;
+    target_group_id?: string | null; // Uuid
 }
\ No newline at end of file